@@ -0,0 +1,362 @@
+use std::{path::PathBuf, rc::Rc, time::Duration};
+
+use anyhow::Context as _;
+use futures::lock::{Mutex, MutexGuard};
+use gpui::{App, ForegroundExecutor, Global};
+use memex_cef::{BeforeUnloadRequest, FindResult, JsDialogRequest};
+use memex_core::{
+    Browser, BrowserDelegate, FaviconFetch, Id, LocalTask, SitePermissionPrompt, Tab, TabCrash,
+    TabGroupMarker, TabMarker, WorkspaceMarker,
+    db::{TabGroupData, WorkspaceIconData},
+};
+
+/// memex-coreからの通知。`BrowserWindow`がUIスレッドで順に受け取り、UIに反映する。
+pub enum BrowserEvent {
+    WorkspaceLoadingStart(Id<WorkspaceMarker>),
+    WorkspaceLoad(Id<WorkspaceMarker>),
+    WorkspaceUnload(Id<WorkspaceMarker>),
+    WorkspaceCreate(Id<WorkspaceMarker>),
+    WorkspaceRename {
+        id: Id<WorkspaceMarker>,
+        name: String,
+    },
+    WorkspaceIconChange {
+        id: Id<WorkspaceMarker>,
+        icon: WorkspaceIconData,
+    },
+    WorkspaceDelete(Id<WorkspaceMarker>),
+    WorkspaceOrderChange(Vec<Id<WorkspaceMarker>>),
+    WorkspaceSelect(Id<WorkspaceMarker>),
+    TabOpen {
+        workspace_id: Id<WorkspaceMarker>,
+        id: Id<TabMarker>,
+    },
+    TabClose {
+        workspace_id: Id<WorkspaceMarker>,
+        id: Id<TabMarker>,
+    },
+    TabMove {
+        id: Id<TabMarker>,
+        from: Id<WorkspaceMarker>,
+        to: Id<WorkspaceMarker>,
+    },
+    TabOrderChange {
+        workspace_id: Id<WorkspaceMarker>,
+        order: Vec<Id<TabMarker>>,
+    },
+    TabSelect {
+        workspace_id: Id<WorkspaceMarker>,
+        id: Option<Id<TabMarker>>,
+    },
+    TabPinnedChange {
+        id: Id<TabMarker>,
+        is_pinned: bool,
+    },
+    TabGroupChange {
+        id: Id<TabMarker>,
+        group_id: Option<Id<TabGroupMarker>>,
+    },
+    TabGroupsChange {
+        workspace_id: Id<WorkspaceMarker>,
+        groups: Vec<TabGroupData>,
+    },
+    TabTitleChange {
+        id: Id<TabMarker>,
+        title: String,
+    },
+    TabNavigationCommit {
+        id: Id<TabMarker>,
+        url: String,
+    },
+    TabFaviconChange {
+        id: Id<TabMarker>,
+        path: Option<PathBuf>,
+    },
+    /// 保存済みの判断がなく、ユーザーに尋ねる必要がある権限の要求。
+    SitePermissionRequest(SitePermissionPrompt),
+    SitePermissionPromptDismiss {
+        id: Id<TabMarker>,
+        prompt_id: u64,
+    },
+    TabJsDialog {
+        id: Id<TabMarker>,
+        request: JsDialogRequest,
+    },
+    TabBeforeUnloadDialog {
+        id: Id<TabMarker>,
+        request: BeforeUnloadRequest,
+    },
+    TabJsDialogReset(Id<TabMarker>),
+    TabFindResult {
+        id: Id<TabMarker>,
+        result: FindResult,
+    },
+    TabCrash {
+        id: Id<TabMarker>,
+        crash: TabCrash,
+    },
+    TabDiscardChange {
+        id: Id<TabMarker>,
+        is_discarded: bool,
+    },
+}
+
+/// memex-coreの通知を[`BrowserEvent`]としてチャンネルに送る`BrowserDelegate`。
+/// 通知はCEFのコールバックやmemex-coreの処理の途中で届くので、その場ではUIを更新しない。
+pub struct AppBrowserDelegate {
+    executor: ForegroundExecutor,
+    events: async_channel::Sender<BrowserEvent>,
+}
+
+impl AppBrowserDelegate {
+    /// 通知を受け取るチャンネルと共に作る。
+    pub fn new(cx: &App) -> (Self, async_channel::Receiver<BrowserEvent>) {
+        let (events, receiver) = async_channel::unbounded();
+
+        (
+            Self {
+                executor: cx.foreground_executor().clone(),
+                events,
+            },
+            receiver,
+        )
+    }
+
+    fn send(&self, event: BrowserEvent) {
+        // 受け取る側のウィンドウが閉じられた後の通知は捨てる。
+        _ = self.events.try_send(event);
+    }
+}
+
+impl BrowserDelegate for AppBrowserDelegate {
+    fn spawn(&self, task: LocalTask) {
+        self.executor.spawn(task).detach();
+    }
+
+    fn on_workspace_loading_start(&self, id: Id<WorkspaceMarker>) {
+        self.send(BrowserEvent::WorkspaceLoadingStart(id));
+    }
+
+    fn on_workspace_load(&self, id: Id<WorkspaceMarker>) {
+        self.send(BrowserEvent::WorkspaceLoad(id));
+    }
+
+    fn on_workspace_unload(&self, id: Id<WorkspaceMarker>) {
+        self.send(BrowserEvent::WorkspaceUnload(id));
+    }
+
+    fn on_workspace_create(&self, id: Id<WorkspaceMarker>) {
+        self.send(BrowserEvent::WorkspaceCreate(id));
+    }
+
+    fn on_workspace_rename(&self, id: Id<WorkspaceMarker>, name: String) {
+        self.send(BrowserEvent::WorkspaceRename { id, name });
+    }
+
+    fn on_workspace_icon_change(&self, id: Id<WorkspaceMarker>, icon: WorkspaceIconData) {
+        self.send(BrowserEvent::WorkspaceIconChange { id, icon });
+    }
+
+    fn on_workspace_delete(&self, id: Id<WorkspaceMarker>) {
+        self.send(BrowserEvent::WorkspaceDelete(id));
+    }
+
+    fn on_workspace_order_change(&self, order: Vec<Id<WorkspaceMarker>>) {
+        self.send(BrowserEvent::WorkspaceOrderChange(order));
+    }
+
+    fn on_workspace_select(&self, id: Id<WorkspaceMarker>) {
+        self.send(BrowserEvent::WorkspaceSelect(id));
+    }
+
+    fn on_tab_open(&self, workspace_id: Id<WorkspaceMarker>, id: Id<TabMarker>) {
+        self.send(BrowserEvent::TabOpen { workspace_id, id });
+    }
+
+    fn on_tab_close(&self, workspace_id: Id<WorkspaceMarker>, id: Id<TabMarker>) {
+        self.send(BrowserEvent::TabClose { workspace_id, id });
+    }
+
+    fn on_tab_move(&self, id: Id<TabMarker>, from: Id<WorkspaceMarker>, to: Id<WorkspaceMarker>) {
+        self.send(BrowserEvent::TabMove { id, from, to });
+    }
+
+    fn on_tab_order_change(&self, workspace_id: Id<WorkspaceMarker>, order: Vec<Id<TabMarker>>) {
+        self.send(BrowserEvent::TabOrderChange {
+            workspace_id,
+            order,
+        });
+    }
+
+    fn on_tab_select(&self, workspace_id: Id<WorkspaceMarker>, id: Option<Id<TabMarker>>) {
+        self.send(BrowserEvent::TabSelect { workspace_id, id });
+    }
+
+    fn on_tab_pinned_change(&self, id: Id<TabMarker>, is_pinned: bool) {
+        self.send(BrowserEvent::TabPinnedChange { id, is_pinned });
+    }
+
+    fn on_tab_group_change(&self, id: Id<TabMarker>, group_id: Option<Id<TabGroupMarker>>) {
+        self.send(BrowserEvent::TabGroupChange { id, group_id });
+    }
+
+    fn on_tab_groups_change(&self, workspace_id: Id<WorkspaceMarker>, groups: Vec<TabGroupData>) {
+        self.send(BrowserEvent::TabGroupsChange {
+            workspace_id,
+            groups,
+        });
+    }
+
+    fn on_tab_title_change(&self, id: Id<TabMarker>, title: String) {
+        self.send(BrowserEvent::TabTitleChange { id, title });
+    }
+
+    fn on_tab_navigation_commit(&self, id: Id<TabMarker>, url: String) {
+        self.send(BrowserEvent::TabNavigationCommit { id, url });
+    }
+
+    fn on_tab_favicon_request(&self, fetch: FaviconFetch) {
+        self.executor
+            .spawn(async move {
+                let tab_id = fetch.tab_id();
+                if let Err(error) = fetch.fetch().await {
+                    log::warn!("ファビコンの取得に失敗しました（{tab_id}）：{error:#}");
+                }
+            })
+            .detach();
+    }
+
+    fn on_tab_favicon_change(&self, id: Id<TabMarker>, path: Option<PathBuf>) {
+        self.send(BrowserEvent::TabFaviconChange { id, path });
+    }
+
+    fn on_site_permission_request(&self, prompt: SitePermissionPrompt) {
+        let events = self.events.clone();
+
+        self.executor
+            .spawn(async move {
+                match prompt.resolve_stored().await {
+                    Ok(Some(prompt)) => {
+                        _ = events.try_send(BrowserEvent::SitePermissionRequest(prompt));
+                    }
+                    Ok(None) => {}
+                    Err(error) => {
+                        log::error!("保存済みの権限の判断の取得に失敗しました：{error:#}");
+                    }
+                }
+            })
+            .detach();
+    }
+
+    fn on_site_permission_prompt_dismiss(&self, id: Id<TabMarker>, prompt_id: u64) {
+        self.send(BrowserEvent::SitePermissionPromptDismiss { id, prompt_id });
+    }
+
+    fn on_tab_js_dialog(&self, id: Id<TabMarker>, request: JsDialogRequest) {
+        self.send(BrowserEvent::TabJsDialog { id, request });
+    }
+
+    fn on_tab_before_unload_dialog(&self, id: Id<TabMarker>, request: BeforeUnloadRequest) {
+        self.send(BrowserEvent::TabBeforeUnloadDialog { id, request });
+    }
+
+    fn on_tab_js_dialog_reset(&self, id: Id<TabMarker>) {
+        self.send(BrowserEvent::TabJsDialogReset(id));
+    }
+
+    fn on_tab_find_result(&self, id: Id<TabMarker>, result: FindResult) {
+        self.send(BrowserEvent::TabFindResult { id, result });
+    }
+
+    fn on_tab_crash(&self, id: Id<TabMarker>, crash: TabCrash) {
+        self.send(BrowserEvent::TabCrash { id, crash });
+    }
+
+    fn on_tab_discard_change(&self, id: Id<TabMarker>, is_discarded: bool) {
+        self.send(BrowserEvent::TabDiscardChange { id, is_discarded });
+    }
+}
+
+/// ワークスペースのCSSのファイルを確かめる間隔。ファイルの変更は通知されないので、定期的に読み込み直す。
+const USER_STYLE_REFRESH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// アプリで使う、memex-coreの`Browser`。ワークスペースを読み込んだ後に`BrowserWindow`が登録する。
+/// `Browser`の非同期のメソッドは`&mut self`を取るので、待っている間も他から触られないよう`Mutex`に入れる。
+#[derive(Clone)]
+pub struct AppBrowser {
+    browser: Rc<Mutex<Browser>>,
+}
+
+impl Global for AppBrowser {}
+
+impl AppBrowser {
+    pub fn new(browser: Browser) -> Self {
+        Self {
+            browser: Rc::new(Mutex::new(browser)),
+        }
+    }
+
+    pub async fn lock(&self) -> MutexGuard<'_, Browser> {
+        self.browser.lock().await
+    }
+
+    /// ワークスペースのCSSのファイルを定期的に確かめ、変更されていれば表示中のページに適用し直す。
+    pub fn watch_user_styles(&self, cx: &App) {
+        let app_browser = self.clone();
+        let executor = cx.background_executor().clone();
+
+        cx.foreground_executor()
+            .spawn(async move {
+                loop {
+                    executor.timer(USER_STYLE_REFRESH_INTERVAL).await;
+                    app_browser.lock().await.refresh_user_styles().await;
+                }
+            })
+            .detach();
+    }
+
+    /// `Browser`を使う処理をUIスレッドで実行し、失敗したらログに残す。
+    /// `Browser`がまだ登録されていなければ何もしない。
+    pub fn update(cx: &App, f: impl AsyncFnOnce(&mut Browser) -> anyhow::Result<()> + 'static) {
+        let Some(app_browser) = cx.try_global::<Self>().cloned() else {
+            return;
+        };
+
+        cx.foreground_executor()
+            .spawn(async move {
+                let mut browser = app_browser.lock().await;
+                if let Err(error) = f(&mut browser).await {
+                    log::error!("ブラウザの操作に失敗しました：{error:#}");
+                }
+            })
+            .detach();
+    }
+}
+
+/// 選択中のワークスペースで、選択中のタブ。
+pub fn selected_tab(browser: &Browser) -> anyhow::Result<&Tab> {
+    let workspace = browser
+        .workspace(browser.selected_workspace())
+        .context("選択中のワークスペースが見つかりません。")?;
+    let id = workspace
+        .selected_tab()
+        .context("タブが選択されていません。")?;
+
+    workspace
+        .tabs()
+        .get(&id)
+        .context("選択中のタブが見つかりません。")
+}
+
+/// 選択中のワークスペースで、選択中のタブ。
+pub fn selected_tab_mut(browser: &mut Browser) -> anyhow::Result<&mut Tab> {
+    let workspace = browser.workspace_mut(browser.selected_workspace())?;
+    let id = workspace
+        .selected_tab()
+        .context("タブが選択されていません。")?;
+
+    workspace
+        .tabs_mut()
+        .get_mut(&id)
+        .context("選択中のタブが見つかりません。")
+}
@@ -0,0 +1,153 @@
+use gpui::Action;
+
+use crate::ui::{
+    COMMAND_PALETTE_CONTEXT, CloseCommandPalette, CloseFindBar, CloseTab, FIND_BAR_CONTEXT,
+    FindNext, FindPrevious, FocusUrlBar, GoBack, GoForward, NewTab, NewWorkspace, NextTab,
+    NextWorkspace, PreviousTab, PreviousWorkspace, Reload, ResetZoom, SelectNextItem,
+    SelectPreviousItem, ToggleCommandPalette, ToggleFindBar, ToggleForceDark, ZoomIn, ZoomOut,
+};
+
+/// コマンドの分類。コマンドパレットなどでの表示に使う。
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum CommandCategory {
+    General,
+    Tab,
+    Workspace,
+    Navigation,
+    Zoom,
+    Find,
+}
+
+impl CommandCategory {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::General => "全般",
+            Self::Tab => "タブ",
+            Self::Workspace => "ワークスペース",
+            Self::Navigation => "ナビゲーション",
+            Self::Zoom => "ズーム",
+            Self::Find => "ページ内検索",
+        }
+    }
+}
+
+/// キー操作やコマンドパレットから実行できる操作。
+pub struct Command {
+    pub category: CommandCategory,
+    /// 表示する名前。
+    pub title: &'static str,
+    /// 既定のキーバインディング。`cmd-shift-g`のように書き、複数のキーは空白で区切る。
+    pub default_keys: &'static [&'static str],
+    /// キーバインディングを有効にするコンテキスト。`None`ならどこでも有効。
+    pub context: Option<&'static str>,
+    action: fn() -> Box<dyn Action>,
+}
+
+impl Command {
+    /// アクションの名前。キーマップのファイルではこの名前でコマンドを指定する。
+    pub fn name(&self) -> &'static str {
+        self.action().name()
+    }
+
+    pub fn action(&self) -> Box<dyn Action> {
+        (self.action)()
+    }
+}
+
+macro_rules! command {
+    ($category:ident, $title:literal, $action:expr, [$($key:literal),*] $(, $context:expr)?) => {
+        Command {
+            category: CommandCategory::$category,
+            title: $title,
+            default_keys: &[$($key),*],
+            context: command!(@context $($context)?),
+            action: || Box::new($action),
+        }
+    };
+    (@context) => { None };
+    (@context $context:expr) => { Some($context) };
+}
+
+static COMMANDS: &[Command] = &[
+    command!(
+        General,
+        "コマンドパレット",
+        ToggleCommandPalette,
+        ["cmd-shift-p", "cmd-k"]
+    ),
+    command!(
+        General,
+        "コマンドパレットを閉じる",
+        CloseCommandPalette,
+        ["escape"],
+        COMMAND_PALETTE_CONTEXT
+    ),
+    command!(
+        General,
+        "次の項目を選択",
+        SelectNextItem,
+        ["down", "ctrl-n"],
+        COMMAND_PALETTE_CONTEXT
+    ),
+    command!(
+        General,
+        "前の項目を選択",
+        SelectPreviousItem,
+        ["up", "ctrl-p"],
+        COMMAND_PALETTE_CONTEXT
+    ),
+    command!(Tab, "新しいタブ", NewTab, ["cmd-t"]),
+    command!(Tab, "タブを閉じる", CloseTab, ["cmd-w"]),
+    command!(Tab, "次のタブ", NextTab, ["ctrl-tab", "cmd-alt-right"]),
+    command!(
+        Tab,
+        "前のタブ",
+        PreviousTab,
+        ["ctrl-shift-tab", "cmd-alt-left"]
+    ),
+    command!(
+        Workspace,
+        "新しいワークスペース",
+        NewWorkspace,
+        ["cmd-shift-n"]
+    ),
+    command!(Workspace, "次のワークスペース", NextWorkspace, ["ctrl-]"]),
+    command!(
+        Workspace,
+        "前のワークスペース",
+        PreviousWorkspace,
+        ["ctrl-["]
+    ),
+    command!(
+        Workspace,
+        "ページを強制的に暗い配色にする",
+        ToggleForceDark,
+        []
+    ),
+    command!(Navigation, "戻る", GoBack, ["cmd-["]),
+    command!(Navigation, "進む", GoForward, ["cmd-]"]),
+    command!(Navigation, "再読み込み", Reload, ["cmd-r"]),
+    command!(Navigation, "URLバーに移動", FocusUrlBar, ["cmd-l"]),
+    command!(Zoom, "拡大", ZoomIn, ["cmd-=", "cmd-+"]),
+    command!(Zoom, "縮小", ZoomOut, ["cmd--"]),
+    command!(Zoom, "実際のサイズ", ResetZoom, ["cmd-0"]),
+    command!(Find, "ページ内を検索", ToggleFindBar, ["cmd-f"]),
+    command!(Find, "次を検索", FindNext, ["cmd-g"]),
+    command!(Find, "前を検索", FindPrevious, ["cmd-shift-g"]),
+    command!(
+        Find,
+        "ページ内検索を閉じる",
+        CloseFindBar,
+        ["escape"],
+        FIND_BAR_CONTEXT
+    ),
+];
+
+/// 全てのコマンド。
+pub fn commands() -> &'static [Command] {
+    COMMANDS
+}
+
+pub fn find_command(name: &str) -> Option<&'static Command> {
+    COMMANDS.iter().find(|command| command.name() == name)
+}
@@ -0,0 +1,517 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    rc::Rc,
+    time::{Duration, SystemTime},
+};
+
+use anyhow::Context as _;
+use gpui::{App, Global, KeyBinding, KeyBindingContextPredicate, Keystroke};
+use serde::Deserialize;
+
+use crate::command::{Command, commands, find_command};
+
+/// キーマップのファイルの変更を確かめる間隔。
+const WATCH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// 読み込むキーマップのファイル。先にあるものを優先する。
+const KEYMAP_FILE_NAMES: &[&str] = &["keymap.toml", "keymap.json"];
+
+/// キーマップのファイルの内容。
+///
+/// ```toml
+/// [bindings]
+/// "tab_actions::NextTab" = ["ctrl-tab"]
+/// "find_bar::ToggleFindBar" = "cmd-shift-f"
+/// # 空にすると、既定のキーバインディングを無効にする。
+/// "navigation::Reload" = []
+/// ```
+#[derive(Default, Deserialize)]
+struct KeymapFile {
+    #[serde(default)]
+    bindings: HashMap<String, KeymapKeys>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum KeymapKeys {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl KeymapKeys {
+    fn into_vec(self) -> Vec<String> {
+        match self {
+            Self::One(keys) => vec![keys],
+            Self::Many(keys) => keys,
+        }
+    }
+}
+
+/// コマンドに割り当てたキーバインディング。
+#[derive(Clone, Debug)]
+pub struct CommandBinding {
+    pub command: &'static str,
+    pub keys: String,
+    pub context: Option<&'static str>,
+    /// キーマップのファイルで設定したものか。
+    pub is_user_defined: bool,
+}
+
+/// 同じコンテキストで、同じキー、または一方が他方の前半になっているキーを割り当てたコマンド。
+#[derive(Clone, Debug)]
+pub struct KeymapConflict {
+    pub keys: String,
+    pub other_keys: String,
+    pub commands: [&'static str; 2],
+}
+
+/// アプリのキーマップ。既定のキーバインディングを、データディレクトリのキーマップのファイルで上書きする。
+pub struct Keymap {
+    /// UIのライブラリが登録したキーバインディング。読み込み直す度に登録し直す。
+    base_bindings: Vec<KeyBinding>,
+    bindings: Vec<CommandBinding>,
+    conflicts: Vec<KeymapConflict>,
+    errors: Vec<String>,
+    path: Option<PathBuf>,
+}
+
+impl Global for Keymap {}
+
+impl Keymap {
+    pub fn global(cx: &App) -> &Self {
+        cx.global::<Self>()
+    }
+
+    /// 全てのコマンドのキーバインディング。コマンドの順に並ぶ。
+    pub fn bindings(&self) -> &[CommandBinding] {
+        &self.bindings
+    }
+
+    /// コマンドに割り当てたキー。
+    pub fn keys_for(&self, command: &str) -> impl Iterator<Item = &str> {
+        self.bindings
+            .iter()
+            .filter(move |binding| binding.command == command)
+            .map(|binding| binding.keys.as_str())
+    }
+
+    pub fn conflicts(&self) -> &[KeymapConflict] {
+        &self.conflicts
+    }
+
+    /// キーマップのファイルの読み込みで見つかった問題。問題のある設定は無視している。
+    pub fn errors(&self) -> &[String] {
+        &self.errors
+    }
+
+    /// 読み込んだキーマップのファイル。なければ`None`を返す。
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+}
+
+/// キーマップを読み込んで登録し、キーマップのファイルの変更を監視する。
+/// UIのライブラリのキーバインディングを残すため、それらの初期化より後に呼ぶこと。
+/// `dir`には、[`DataContext::keymap_dir`](memex_core::fs::DataContext::keymap_dir)を渡す。
+pub fn init(cx: &mut App, dir: PathBuf) {
+    let base_bindings = cx.key_bindings().borrow().bindings().cloned().collect();
+    cx.set_global(Keymap {
+        base_bindings,
+        bindings: Vec::new(),
+        conflicts: Vec::new(),
+        errors: Vec::new(),
+        path: None,
+    });
+
+    let source = read_keymap_source(&dir);
+    let mut signature = source.as_ref().map(|source| source.signature.clone());
+    apply(cx, source);
+
+    cx.spawn(async move |cx| {
+        loop {
+            cx.background_executor().timer(WATCH_INTERVAL).await;
+
+            let source = cx
+                .background_executor()
+                .spawn({
+                    let dir = dir.clone();
+                    async move { read_keymap_source(&dir) }
+                })
+                .await;
+            let new_signature = source.as_ref().map(|source| source.signature.clone());
+            if new_signature == signature {
+                continue;
+            }
+            signature = new_signature;
+
+            log::info!("キーマップを読み込み直します。");
+            if cx.update(|cx| apply(cx, source)).is_err() {
+                break;
+            }
+        }
+    })
+    .detach();
+}
+
+#[derive(Clone, PartialEq, Eq)]
+struct KeymapSignature {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+    len: u64,
+}
+
+struct KeymapSource {
+    signature: KeymapSignature,
+    content: std::io::Result<String>,
+}
+
+fn read_keymap_source(dir: &Path) -> Option<KeymapSource> {
+    KEYMAP_FILE_NAMES.iter().find_map(|name| {
+        let path = dir.join(name);
+        let metadata = std::fs::metadata(&path).ok()?;
+
+        Some(KeymapSource {
+            content: std::fs::read_to_string(&path),
+            signature: KeymapSignature {
+                path,
+                modified: metadata.modified().ok(),
+                len: metadata.len(),
+            },
+        })
+    })
+}
+
+fn parse_keymap_file(path: &Path, content: &str) -> anyhow::Result<KeymapFile> {
+    if path
+        .extension()
+        .is_some_and(|extension| extension == "json")
+    {
+        serde_json::from_str(content).context("JSONのキーマップを読み込めませんでした。")
+    } else {
+        toml::from_str(content).context("TOMLのキーマップを読み込めませんでした。")
+    }
+}
+
+/// キーマップのファイルを反映し、キーバインディングを全て登録し直す。
+fn apply(cx: &mut App, source: Option<KeymapSource>) {
+    let mut errors = Vec::new();
+    let path = source.as_ref().map(|source| source.signature.path.clone());
+
+    let file = match source {
+        Some(source) => source
+            .content
+            .context("キーマップのファイルの読み込みに失敗しました。")
+            .and_then(|content| parse_keymap_file(&source.signature.path, &content))
+            .unwrap_or_else(|error| {
+                errors.push(format!("{error:#}"));
+                KeymapFile::default()
+            }),
+        None => KeymapFile::default(),
+    };
+
+    let mut user_bindings = HashMap::new();
+    for (name, keys) in file.bindings {
+        let Some(command) = find_command(&name) else {
+            errors.push(format!("不明なコマンドです：{name}"));
+            continue;
+        };
+
+        let keys = keys
+            .into_vec()
+            .into_iter()
+            .filter(|keys| match validate_keys(keys) {
+                Ok(()) => true,
+                Err(error) => {
+                    errors.push(format!("{name}：{error:#}"));
+                    false
+                }
+            })
+            .collect::<Vec<_>>();
+        user_bindings.insert(command.name(), keys);
+    }
+
+    let bindings = commands()
+        .iter()
+        .flat_map(|command| {
+            let user_keys = user_bindings.get(command.name());
+            let keys = match user_keys {
+                Some(keys) => keys.iter().map(|keys| keys.trim().to_owned()).collect(),
+                None => command
+                    .default_keys
+                    .iter()
+                    .map(|keys| (*keys).to_owned())
+                    .collect::<Vec<_>>(),
+            };
+
+            keys.into_iter().map(move |keys| CommandBinding {
+                command: command.name(),
+                keys,
+                context: command.context,
+                is_user_defined: user_keys.is_some(),
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let conflicts = find_conflicts(&bindings);
+    for conflict in &conflicts {
+        log::warn!(
+            "キーバインディングが衝突しています：{}（{}）と{}（{}）",
+            conflict.keys,
+            conflict.commands[0],
+            conflict.other_keys,
+            conflict.commands[1]
+        );
+    }
+    for error in &errors {
+        log::warn!("キーマップの問題：{error}");
+    }
+
+    let key_bindings = bindings
+        .iter()
+        .filter_map(|binding| {
+            let command = find_command(binding.command)?;
+            match build_key_binding(cx, command, &binding.keys) {
+                Ok(key_binding) => Some(key_binding),
+                Err(error) => {
+                    log::warn!("キーバインディングを登録できませんでした：{error:#}");
+                    None
+                }
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let keymap = cx.global_mut::<Keymap>();
+    keymap.bindings = bindings;
+    keymap.conflicts = conflicts;
+    keymap.errors = errors;
+    keymap.path = path;
+    let base_bindings = keymap.base_bindings.clone();
+
+    cx.clear_key_bindings();
+    cx.bind_keys(base_bindings);
+    cx.bind_keys(key_bindings);
+}
+
+fn validate_keys(keys: &str) -> anyhow::Result<()> {
+    anyhow::ensure!(!keys.trim().is_empty(), "キーが空です。");
+
+    for keystroke in keys.split_whitespace() {
+        Keystroke::parse(keystroke)
+            .map_err(|error| anyhow::anyhow!("キーの書き方が正しくありません：{error}"))?;
+    }
+
+    Ok(())
+}
+
+fn build_key_binding(cx: &App, command: &Command, keys: &str) -> anyhow::Result<KeyBinding> {
+    let context = command
+        .context
+        .map(|context| KeyBindingContextPredicate::parse(context).map(Rc::new))
+        .transpose()?;
+
+    KeyBinding::load(
+        keys,
+        command.action(),
+        context,
+        false,
+        None,
+        cx.keyboard_mapper().as_ref(),
+    )
+    .map_err(|error| anyhow::anyhow!("キーの書き方が正しくありません（{keys}）：{error}"))
+}
+
+/// 同じコンテキストのキーバインディングで、キーが同じものと、一方が他方の前半になっているものを探す。
+/// 前半が一致する場合は、短い方を押した後に続きのキーを待つため、短い方が実行されにくくなる。
+fn find_conflicts(bindings: &[CommandBinding]) -> Vec<KeymapConflict> {
+    let keystrokes = bindings
+        .iter()
+        .map(|binding| {
+            binding
+                .keys
+                .split_whitespace()
+                .map(|keystroke| {
+                    Keystroke::parse(keystroke)
+                        .map(|keystroke| keystroke.unparse())
+                        .unwrap_or_else(|_| keystroke.to_owned())
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+
+    let mut conflicts = Vec::new();
+    for (i, binding) in bindings.iter().enumerate() {
+        for (j, other) in bindings.iter().enumerate().skip(i + 1) {
+            if binding.context != other.context || binding.command == other.command {
+                continue;
+            }
+
+            let (short, long) = if keystrokes[i].len() <= keystrokes[j].len() {
+                (&keystrokes[i], &keystrokes[j])
+            } else {
+                (&keystrokes[j], &keystrokes[i])
+            };
+            if long.starts_with(short) {
+                conflicts.push(KeymapConflict {
+                    keys: binding.keys.clone(),
+                    other_keys: other.keys.clone(),
+                    commands: [binding.command, other.command],
+                });
+            }
+        }
+    }
+
+    conflicts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn binding(command: &'static str, keys: &str, context: Option<&'static str>) -> CommandBinding {
+        CommandBinding {
+            command,
+            keys: keys.to_owned(),
+            context,
+            is_user_defined: false,
+        }
+    }
+
+    fn conflicting_commands(bindings: &[CommandBinding]) -> Vec<[&'static str; 2]> {
+        find_conflicts(bindings)
+            .into_iter()
+            .map(|conflict| conflict.commands)
+            .collect()
+    }
+
+    #[test]
+    fn finds_same_keys() {
+        let bindings = [
+            binding("a::First", "cmd-k", None),
+            binding("a::Second", "cmd-j", None),
+            binding("a::Third", "cmd-k", None),
+        ];
+
+        let conflicts = find_conflicts(&bindings);
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].commands, ["a::First", "a::Third"]);
+        assert_eq!(conflicts[0].keys, "cmd-k");
+        assert_eq!(conflicts[0].other_keys, "cmd-k");
+    }
+
+    #[test]
+    fn normalizes_modifier_order() {
+        let bindings = [
+            binding("a::First", "cmd-shift-k", None),
+            binding("a::Second", "shift-cmd-k", None),
+        ];
+
+        assert_eq!(conflicting_commands(&bindings), [["a::First", "a::Second"]]);
+    }
+
+    #[test]
+    fn finds_prefix_keys() {
+        let bindings = [
+            binding("a::Chord", "cmd-k cmd-s", None),
+            binding("a::Prefix", "cmd-k", None),
+            binding("a::Other", "cmd-k cmd-t", None),
+        ];
+
+        let conflicts = find_conflicts(&bindings);
+        assert_eq!(
+            conflicts
+                .iter()
+                .map(|conflict| conflict.commands)
+                .collect::<Vec<_>>(),
+            [["a::Chord", "a::Prefix"], ["a::Prefix", "a::Other"]]
+        );
+        assert_eq!(conflicts[0].keys, "cmd-k cmd-s");
+        assert_eq!(conflicts[0].other_keys, "cmd-k");
+    }
+
+    #[test]
+    fn ignores_different_contexts_and_same_command() {
+        let bindings = [
+            binding("a::First", "enter", Some("FindBar")),
+            binding("a::Second", "enter", Some("CommandPalette")),
+            binding("a::Third", "cmd-w", None),
+            binding("a::Third", "cmd-w", None),
+            binding("a::Fourth", "cmd-w cmd-w", Some("FindBar")),
+        ];
+
+        assert!(find_conflicts(&bindings).is_empty());
+    }
+
+    #[test]
+    fn parses_toml_keymap() {
+        let file = parse_keymap_file(
+            Path::new("keymap.toml"),
+            r#"
+            [bindings]
+            "tab_actions::NextTab" = ["ctrl-tab", "cmd-}"]
+            "find_bar::ToggleFindBar" = "cmd-shift-f"
+            "navigation::Reload" = []
+            "#,
+        )
+        .unwrap();
+
+        let mut bindings = file
+            .bindings
+            .into_iter()
+            .map(|(name, keys)| (name, keys.into_vec()))
+            .collect::<Vec<_>>();
+        bindings.sort();
+        assert_eq!(
+            bindings,
+            [
+                (
+                    "find_bar::ToggleFindBar".to_owned(),
+                    vec!["cmd-shift-f".to_owned()]
+                ),
+                ("navigation::Reload".to_owned(), Vec::new()),
+                (
+                    "tab_actions::NextTab".to_owned(),
+                    vec!["ctrl-tab".to_owned(), "cmd-}".to_owned()]
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_json_keymap() {
+        let mut file = parse_keymap_file(
+            Path::new("keymap.json"),
+            r#"{ "bindings": { "tab_actions::NextTab": ["ctrl-tab"], "navigation::Reload": "f5" } }"#,
+        )
+        .unwrap();
+
+        assert_eq!(file.bindings.len(), 2);
+        assert_eq!(
+            file.bindings
+                .remove("navigation::Reload")
+                .unwrap()
+                .into_vec(),
+            ["f5"]
+        );
+
+        // 拡張子で形式を決めるので、JSONの内容をTOMLとしては読み込めない。
+        assert!(parse_keymap_file(Path::new("keymap.toml"), r#"{ "bindings": {} }"#).is_err());
+        assert!(parse_keymap_file(Path::new("keymap.json"), "[bindings]").is_err());
+    }
+
+    #[test]
+    fn accepts_empty_keymap() {
+        assert!(
+            parse_keymap_file(Path::new("keymap.toml"), "")
+                .unwrap()
+                .bindings
+                .is_empty()
+        );
+        assert!(
+            parse_keymap_file(Path::new("keymap.json"), "{}")
+                .unwrap()
+                .bindings
+                .is_empty()
+        );
+    }
+}
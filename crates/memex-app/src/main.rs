@@ -1,4 +1,7 @@
+pub mod browser;
+pub mod command;
 pub mod foundation;
+pub mod keymap;
 pub mod platform_impl;
 pub mod system;
 pub mod ui;
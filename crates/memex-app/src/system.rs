@@ -1,6 +1,7 @@
 use futures_lite::future::block_on;
 use gpui::{App, Application};
 use memex_backend::init_global_state;
+use memex_core::fs::DataContext;
 
 use crate::{APP_IDENTIFIER, setup_window};
 
@@ -50,4 +51,5 @@ pub fn init_ui(cx: &mut App) {
     // UIに使う周辺の初期化。
     gpui_component::init(cx);
     crate::foundation::init_theme(cx);
+    crate::keymap::init(cx, DataContext::new(APP_IDENTIFIER).keymap_dir());
 }
@@ -1,4 +1,6 @@
+use anyhow::Context as _;
 use gpui::{App, Entity, ReadGlobal, Window, prelude::*};
+use gpui_component::{ContextModal as _, notification::Notification};
 use memex_backend::{
     LayoutState, WorkspaceListState,
     data::{
@@ -6,10 +8,19 @@ use memex_backend::{
         load_workspace,
     },
 };
+use memex_cef::{UIThreadMarker, WindowSize};
+use memex_core::{
+    Browser, BrowserContext, Id, TabCrash, TabMarker, WebViewBounds, WorkspaceMarker,
+};
+use raw_window_handle::{HasWindowHandle, RawWindowHandle};
 
-use crate::ui::{
-    EXPROLER_WIDTH, Start, TOP_TAB_BAR_HEIGHT, TitleBar, URL_BAR_HEIGHT, WORKSPACE_LIST_WIDTH,
-    Workbench, WorkspaceList,
+use crate::{
+    APP_IDENTIFIER,
+    browser::{AppBrowser, AppBrowserDelegate, BrowserEvent},
+    ui::{
+        EXPROLER_WIDTH, JsDialogHost, JsDialogQueue, PendingJsDialog, Start, TOP_TAB_BAR_HEIGHT,
+        TitleBar, URL_BAR_HEIGHT, WORKSPACE_LIST_WIDTH, Workbench, WorkspaceList,
+    },
 };
 
 #[derive(Clone)]
@@ -20,14 +31,17 @@ pub enum CurrentView {
 
 /// ブラウザのウィンドウのUIの根本。
 /// 起動時にワークスペース一覧を読み込んで、読み込めればそれをUIに反映する。
+/// その後にmemex-coreの`Browser`を用意して、その通知をUIに反映する。
 pub struct BrowserWindow {
     current: CurrentView,
+    js_dialog_queue: Entity<JsDialogQueue>,
 }
 
 impl BrowserWindow {
     pub fn new(window: &mut Window, cx: &mut App) -> Entity<Self> {
         let entity = cx.new(|cx| Self {
             current: CurrentView::Start(Start::new(cx)),
+            js_dialog_queue: JsDialogQueue::new(cx),
         });
 
         // ワークベンチの読み込みを行う。
@@ -40,6 +54,7 @@ impl BrowserWindow {
                     let workspace_list_data = get_workspace_list(&path)
                         .await
                         .expect("ワークスペースリストの状態の復元に失敗しました。");
+                    let selected_id = Id::from(workspace_list_data.selected);
                     let mut workspaces = list_workspaces(&path)
                         .await
                         .expect("ワークスペースのリストの読み込みに失敗しました。");
@@ -94,19 +109,137 @@ impl BrowserWindow {
                                 current,
                             );
 
-                            let workbench =
-                                Workbench::new(cx, layout_state, workspace_list, title_bar);
+                            let js_dialog_host = JsDialogHost::new(
+                                window,
+                                cx,
+                                browser_window.js_dialog_queue.clone(),
+                                workspace_list_state.clone(),
+                            );
+
+                            let workbench = Workbench::new(
+                                window,
+                                cx,
+                                layout_state,
+                                workspace_list,
+                                title_bar,
+                                js_dialog_host,
+                            );
                             browser_window.current = CurrentView::Workbench(workbench);
 
                             cx.notify();
                         })
                         .unwrap();
+
+                    let Ok((delegate, events, window_handle, bounds)) =
+                        entity.update_in(cx, |_, window, cx| {
+                            let (delegate, events) = AppBrowserDelegate::new(cx);
+                            (
+                                delegate,
+                                events,
+                                <Window as HasWindowHandle>::window_handle(window)
+                                    .map(|handle| handle.as_raw()),
+                                webview_bounds(window),
+                            )
+                        })
+                    else {
+                        return;
+                    };
+
+                    let browser = match window_handle {
+                        Ok(window_handle) => {
+                            setup_browser(delegate, window_handle, bounds, selected_id).await
+                        }
+                        Err(error) => Err(anyhow::anyhow!(
+                            "ウィンドウのハンドルの取得に失敗しました：{error}"
+                        )),
+                    };
+                    match browser {
+                        Ok(browser) => {
+                            let app_browser = AppBrowser::new(browser);
+                            if cx
+                                .update(|_, cx| {
+                                    app_browser.watch_user_styles(cx);
+                                    cx.set_global(app_browser);
+                                })
+                                .is_err()
+                            {
+                                return;
+                            }
+                        }
+                        Err(error) => {
+                            log::error!("ブラウザの準備に失敗しました：{error:#}");
+                            return;
+                        }
+                    }
+
+                    while let Ok(event) = events.recv().await {
+                        let result = entity.update_in(cx, |browser_window, window, cx| {
+                            browser_window.handle_browser_event(window, cx, event);
+                        });
+                        if result.is_err() {
+                            break;
+                        }
+                    }
                 }
             })
             .detach();
 
         entity
     }
+
+    /// タブから届いたJavaScriptのダイアログを溜めておく場所。
+    pub fn js_dialog_queue(&self) -> &Entity<JsDialogQueue> {
+        &self.js_dialog_queue
+    }
+
+    /// memex-coreからの通知をUIに反映する。
+    /// ワークスペースとタブの一覧はまだ`memex_backend`の状態から表示しているので、ここでは扱わない。
+    fn handle_browser_event(
+        &mut self,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+        event: BrowserEvent,
+    ) {
+        match event {
+            BrowserEvent::TabJsDialog { id, request } => {
+                self.js_dialog_queue.update(cx, |queue, cx| {
+                    queue.push(cx, *id, PendingJsDialog::Dialog(request));
+                });
+            }
+            BrowserEvent::TabBeforeUnloadDialog { id, request } => {
+                self.js_dialog_queue.update(cx, |queue, cx| {
+                    queue.push(cx, *id, PendingJsDialog::BeforeUnload(request));
+                });
+            }
+            BrowserEvent::TabJsDialogReset(id) => {
+                self.js_dialog_queue
+                    .update(cx, |queue, cx| queue.clear(cx, *id));
+            }
+            BrowserEvent::TabNavigationCommit { id, url } => {
+                if let CurrentView::Workbench(workbench) = &self.current {
+                    workbench.update(cx, |workbench, cx| {
+                        workbench.set_url(window, cx, *id, &url);
+                    });
+                }
+            }
+            BrowserEvent::TabFindResult { id, result } => {
+                if let CurrentView::Workbench(workbench) = &self.current {
+                    workbench.update(cx, |workbench, cx| {
+                        workbench.set_find_result(cx, *id, result);
+                    });
+                }
+            }
+            BrowserEvent::TabCrash { id, crash } => {
+                window.push_notification(crash_notification(id, &crash), cx);
+            }
+            BrowserEvent::SitePermissionRequest(prompt) => {
+                // 権限を尋ねるUIはまだないので、判断を保存せずに断る。
+                log::info!("権限の要求を断りました：{}", prompt.origin());
+                prompt.dismiss();
+            }
+            _ => {}
+        }
+    }
 }
 
 impl Render for BrowserWindow {
@@ -117,3 +250,68 @@ impl Render for BrowserWindow {
         }
     }
 }
+
+/// タブのクラッシュを知らせる通知。
+/// 自動で読み込み直さない場合は、通知を閉じずに残し、クリックで読み込み直せるようにする。
+/// GPUプロセスのクラッシュはCEFから通知されないので、表示が崩れた場合はユーザーに読み込み直してもらうしかない。
+fn crash_notification(id: Id<TabMarker>, crash: &TabCrash) -> Notification {
+    let Some(delay) = crash.reload_after else {
+        return Notification::error(
+            "ページがクラッシュを繰り返したため、自動では読み込み直しません。クリックすると読み込み直します。",
+        )
+        .autohide(false)
+        .on_click(move |_, _, cx| {
+            AppBrowser::update(cx, async move |browser| {
+                browser
+                    .tab(id)
+                    .context("クラッシュしたタブが見つかりません。")?
+                    .reload_after_crash()
+            });
+        });
+    };
+
+    Notification::warning(format!(
+        "ページがクラッシュしました。{}秒後に読み込み直します。",
+        delay.as_secs()
+    ))
+}
+
+/// memex-coreの`Browser`を作り、保存されているワークスペースを読み込む。
+/// `selected`がなければ、ホームを選択する。
+async fn setup_browser(
+    delegate: AppBrowserDelegate,
+    window_handle: RawWindowHandle,
+    bounds: WebViewBounds,
+    selected: Id<WorkspaceMarker>,
+) -> anyhow::Result<Browser> {
+    let (data, db) = memex_core::setup_application_data(APP_IDENTIFIER).await?;
+    let context = BrowserContext::new(data, db, window_handle, bounds, delegate)?;
+
+    let mut browser = Browser::new(context)?;
+    browser.restore_workspaces().await?;
+
+    let selected = if browser.workspace(selected).is_some() {
+        selected
+    } else {
+        Id::home()
+    };
+    let utm = UIThreadMarker::new().context("UIスレッドで呼び出す必要があります。")?;
+    browser.select(selected, utm).await?;
+
+    Ok(browser)
+}
+
+/// ウィンドウの全体を、タブの`WebView`の初期の表示領域とする。正しい位置はレイアウト後に設定する。
+fn webview_bounds(window: &Window) -> WebViewBounds {
+    let size = window.viewport_size();
+    let width = f32::from(size.width) as i32;
+    let height = f32::from(size.height) as i32;
+
+    WebViewBounds {
+        x: 0,
+        y: 0,
+        width,
+        height,
+        window_size: WindowSize { width, height },
+    }
+}
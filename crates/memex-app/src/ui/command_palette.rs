@@ -0,0 +1,342 @@
+use std::ops::Range;
+
+use gpui::{
+    App, Entity, EventEmitter, FocusHandle, FontWeight, HighlightStyle, MouseButton, StyledText,
+    Window, actions, div, prelude::*, px,
+};
+use gpui_component::{
+    ActiveTheme, h_flex,
+    input::{InputEvent, InputState, TextInput},
+    v_flex,
+};
+use memex_core::{PaletteItem, PaletteMatch, PaletteTarget, rank_palette_items};
+
+use crate::{
+    command::{commands, find_command},
+    keymap::Keymap,
+};
+
+actions!(
+    command_palette,
+    [
+        ToggleCommandPalette,
+        CloseCommandPalette,
+        SelectNextItem,
+        SelectPreviousItem
+    ]
+);
+
+/// コマンドパレットにフォーカスがある間だけ有効なキーバインディングのコンテキスト。
+pub const COMMAND_PALETTE_CONTEXT: &str = "CommandPalette";
+
+/// 一度に表示する項目の数。
+const MAX_VISIBLE_ITEMS: usize = 12;
+
+/// コマンドパレットの操作。コマンドはパレットが実行するので、それ以外の項目を受け取った側が開く。
+#[derive(Clone, Debug)]
+pub enum CommandPaletteEvent {
+    /// パレットを開いた。受け取った側は`CommandPalette::add_items`で項目を加えられる。
+    Open,
+    Confirm(PaletteTarget),
+}
+
+/// コマンド・タブ・ワークスペース・ブックマーク・ファイルをまとめて探せるパレット。
+pub struct CommandPalette {
+    input: Entity<InputState>,
+    items: Vec<PaletteItem>,
+    matches: Vec<PaletteMatch>,
+    selected: usize,
+    is_open: bool,
+    /// 開く前にフォーカスがあった場所。閉じる時に戻し、コマンドはそこから実行する。
+    previous_focus: Option<FocusHandle>,
+}
+
+impl EventEmitter<CommandPaletteEvent> for CommandPalette {}
+
+impl CommandPalette {
+    pub fn new(window: &mut Window, cx: &mut App) -> Entity<Self> {
+        cx.new(|cx| {
+            let input = cx.new(|cx| {
+                InputState::new(window, cx).placeholder("コマンド・タブ・ワークスペースを検索")
+            });
+
+            cx.subscribe_in(
+                &input,
+                window,
+                |palette, _input, event, window, cx| match event {
+                    InputEvent::Change => palette.update_matches(cx),
+                    InputEvent::PressEnter { .. } => palette.confirm(window, cx),
+                    _ => {}
+                },
+            )
+            .detach();
+
+            Self {
+                input,
+                items: Vec::new(),
+                matches: Vec::new(),
+                selected: 0,
+                is_open: false,
+                previous_focus: None,
+            }
+        })
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.is_open
+    }
+
+    /// パレットを開く。`items`にはコマンド以外の項目を渡す。コマンドはパレットが加える。
+    pub fn open(&mut self, window: &mut Window, cx: &mut Context<Self>, items: Vec<PaletteItem>) {
+        if !self.is_open {
+            self.previous_focus = window.focused(cx);
+        }
+        self.is_open = true;
+        self.items = command_items(cx).into_iter().chain(items).collect();
+
+        self.input.update(cx, |input, cx| {
+            input.set_value("", window, cx);
+            input.focus(window, cx);
+        });
+        self.update_matches(cx);
+
+        cx.emit(CommandPaletteEvent::Open);
+    }
+
+    /// 開いている間に、項目を加える。データベースから読み込む項目など、後から届くものに使う。
+    pub fn add_items(&mut self, cx: &mut Context<Self>, items: Vec<PaletteItem>) {
+        if !self.is_open {
+            return;
+        }
+
+        self.items.extend(items);
+        self.update_matches(cx);
+    }
+
+    pub fn close(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        if !self.is_open {
+            return;
+        }
+
+        self.is_open = false;
+        self.items.clear();
+        self.matches.clear();
+        if let Some(focus_handle) = self.previous_focus.take() {
+            window.focus(&focus_handle);
+        }
+
+        cx.notify();
+    }
+
+    pub fn toggle(&mut self, window: &mut Window, cx: &mut Context<Self>, items: Vec<PaletteItem>) {
+        if self.is_open {
+            self.close(window, cx);
+        } else {
+            self.open(window, cx, items);
+        }
+    }
+
+    /// 選択中の項目を`offset`だけ動かす。端を越えたら反対の端に戻る。
+    pub fn select_relative(&mut self, cx: &mut Context<Self>, offset: isize) {
+        if self.matches.is_empty() {
+            return;
+        }
+
+        self.selected =
+            (self.selected as isize + offset).rem_euclid(self.matches.len() as isize) as usize;
+        cx.notify();
+    }
+
+    fn update_matches(&mut self, cx: &mut Context<Self>) {
+        let query = self.input.read(cx).value().to_string();
+
+        self.matches = rank_palette_items(&query, self.items.clone(), MAX_VISIBLE_ITEMS);
+        self.selected = 0;
+        cx.notify();
+    }
+
+    fn confirm(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(target) = self
+            .matches
+            .get(self.selected)
+            .map(|palette_match| palette_match.item.target.clone())
+        else {
+            return;
+        };
+
+        // 元の場所にフォーカスを戻してから、コマンドを実行する。
+        self.close(window, cx);
+
+        match target {
+            PaletteTarget::Command(name) => {
+                if let Some(command) = find_command(&name) {
+                    window.dispatch_action(command.action(), cx);
+                }
+            }
+            target => cx.emit(CommandPaletteEvent::Confirm(target)),
+        }
+    }
+
+    fn confirm_at(&mut self, window: &mut Window, cx: &mut Context<Self>, index: usize) {
+        self.selected = index;
+        self.confirm(window, cx);
+    }
+}
+
+/// どこからでも実行できるコマンドを、パレットの項目にする。キーバインディングは詳細に表示する。
+fn command_items(cx: &App) -> Vec<PaletteItem> {
+    let keymap = Keymap::global(cx);
+
+    commands()
+        .iter()
+        .filter(|command| command.context.is_none())
+        .map(|command| PaletteItem {
+            target: PaletteTarget::Command(command.name().to_owned()),
+            title: format!("{}: {}", command.category.label(), command.title),
+            detail: keymap
+                .keys_for(command.name())
+                .collect::<Vec<_>>()
+                .join(" / "),
+        })
+        .collect()
+}
+
+fn kind_label(target: &PaletteTarget) -> &'static str {
+    match target {
+        PaletteTarget::Command(_) => "コマンド",
+        PaletteTarget::Tab { .. } => "タブ",
+        PaletteTarget::Workspace(_) => "ワークスペース",
+        PaletteTarget::Bookmark { .. } => "ブックマーク",
+        PaletteTarget::File(_) => "ファイル",
+    }
+}
+
+/// 一致した文字の位置（`char`単位）を、強調する範囲（バイト単位）にする。
+fn highlight_ranges(text: &str, positions: &[usize]) -> Vec<Range<usize>> {
+    text.char_indices()
+        .enumerate()
+        .filter(|(index, _)| positions.contains(index))
+        .map(|(_, (start, c))| start..start + c.len_utf8())
+        .collect()
+}
+
+impl Render for CommandPalette {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        if !self.is_open {
+            return div().into_any_element();
+        }
+
+        let highlight = HighlightStyle {
+            color: Some(cx.theme().primary),
+            font_weight: Some(FontWeight::BOLD),
+            ..Default::default()
+        };
+
+        let rows = self
+            .matches
+            .iter()
+            .enumerate()
+            .map(|(index, palette_match)| {
+                let item = &palette_match.item;
+                let title = StyledText::new(item.title.clone()).with_highlights(
+                    highlight_ranges(&item.title, &palette_match.positions)
+                        .into_iter()
+                        .map(|range| (range, highlight)),
+                );
+
+                h_flex()
+                    .id(index)
+                    .w_full()
+                    .px_3()
+                    .py_1()
+                    .gap_3()
+                    .items_center()
+                    .rounded_md()
+                    .when(index == self.selected, |this| {
+                        this.bg(cx.theme().accent)
+                            .text_color(cx.theme().accent_foreground)
+                    })
+                    .child(
+                        div()
+                            .w(px(88.))
+                            .flex_none()
+                            .text_xs()
+                            .text_color(cx.theme().muted_foreground)
+                            .child(kind_label(&item.target)),
+                    )
+                    .child(div().flex_1().overflow_hidden().child(title))
+                    .child(
+                        div()
+                            .max_w(px(240.))
+                            .overflow_hidden()
+                            .text_sm()
+                            .text_color(cx.theme().muted_foreground)
+                            .child(item.detail.clone()),
+                    )
+                    .on_mouse_down(
+                        MouseButton::Left,
+                        cx.listener(move |palette, _event, window, cx| {
+                            palette.confirm_at(window, cx, index);
+                        }),
+                    )
+            })
+            .collect::<Vec<_>>();
+
+        div()
+            .id("command-palette-overlay")
+            .absolute()
+            .inset_0()
+            .flex()
+            .justify_center()
+            .items_start()
+            .pt(px(48.))
+            .occlude()
+            .on_mouse_down(
+                MouseButton::Left,
+                cx.listener(|palette, _event, window, cx| palette.close(window, cx)),
+            )
+            .child(
+                v_flex()
+                    .id("command-palette")
+                    .key_context(COMMAND_PALETTE_CONTEXT)
+                    .on_action(cx.listener(|palette, _: &CloseCommandPalette, window, cx| {
+                        palette.close(window, cx);
+                    }))
+                    .on_action(cx.listener(|palette, _: &SelectNextItem, _window, cx| {
+                        palette.select_relative(cx, 1);
+                    }))
+                    .on_action(cx.listener(|palette, _: &SelectPreviousItem, _window, cx| {
+                        palette.select_relative(cx, -1);
+                    }))
+                    // 外側をクリックした時だけ閉じるよう、パレットの中のクリックは外側に伝えない。
+                    .on_mouse_down(MouseButton::Left, |_event, _window, cx| {
+                        cx.stop_propagation();
+                    })
+                    .w(px(600.))
+                    .gap_2()
+                    .p_2()
+                    .rounded_xl()
+                    .border_1()
+                    .border_color(cx.theme().border)
+                    .bg(cx.theme().popover)
+                    .text_color(cx.theme().popover_foreground)
+                    .shadow_lg()
+                    .child(TextInput::new(&self.input))
+                    .when_else(
+                        rows.is_empty(),
+                        |this| {
+                            this.child(
+                                div()
+                                    .px_3()
+                                    .py_2()
+                                    .text_sm()
+                                    .text_color(cx.theme().muted_foreground)
+                                    .child("一致する項目がありません"),
+                            )
+                        },
+                        |this| this.children(rows),
+                    ),
+            )
+            .into_any_element()
+    }
+}
@@ -5,3 +5,4 @@ pub const EXPROLER_WIDTH: Pixels = px(200.);
 
 pub const TOP_TAB_BAR_HEIGHT: Pixels = px(42.);
 pub const URL_BAR_HEIGHT: Pixels = px(42.);
+pub const FIND_BAR_HEIGHT: Pixels = px(38.);
@@ -0,0 +1,199 @@
+use gpui::{App, Entity, EventEmitter, Window, actions, div, prelude::*, px};
+use gpui_component::{
+    ActiveTheme, Icon, IconName, Selectable, Sizable,
+    button::{Button, ButtonVariants},
+    h_flex,
+    input::{InputEvent, InputState, TextInput},
+};
+use memex_cef::FindResult;
+
+use crate::ui::FIND_BAR_HEIGHT;
+
+actions!(
+    find_bar,
+    [ToggleFindBar, FindNext, FindPrevious, CloseFindBar]
+);
+
+/// 検索バーにフォーカスがある間だけ有効なキーバインディングのコンテキスト。
+pub const FIND_BAR_CONTEXT: &str = "FindBar";
+
+/// 検索バーの操作。受け取った側が、選択中のタブの`WebView`で検索を行う。
+#[derive(Clone, Debug)]
+pub enum FindBarEvent {
+    Find {
+        text: String,
+        forward: bool,
+        match_case: bool,
+    },
+    Stop,
+}
+
+/// URLバーの下に表示する、ページ内検索のバー。
+pub struct FindBar {
+    input: Entity<InputState>,
+    match_case: bool,
+    result: FindResult,
+    is_open: bool,
+}
+
+impl EventEmitter<FindBarEvent> for FindBar {}
+
+impl FindBar {
+    pub fn new(window: &mut Window, cx: &mut App) -> Entity<Self> {
+        cx.new(|cx| {
+            let input = cx.new(|cx| InputState::new(window, cx).placeholder("ページ内を検索"));
+
+            cx.subscribe_in(
+                &input,
+                window,
+                |find_bar, _input, event, _window, cx| match event {
+                    InputEvent::Change => find_bar.find(cx, true),
+                    InputEvent::PressEnter { secondary } => find_bar.find(cx, !secondary),
+                    _ => {}
+                },
+            )
+            .detach();
+
+            Self {
+                input,
+                match_case: false,
+                result: FindResult::default(),
+                is_open: false,
+            }
+        })
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.is_open
+    }
+
+    pub fn open(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        self.is_open = true;
+        self.input.update(cx, |input, cx| input.focus(window, cx));
+
+        if !self.input.read(cx).value().is_empty() {
+            self.find(cx, true);
+        }
+
+        cx.notify();
+    }
+
+    pub fn close(&mut self, cx: &mut Context<Self>) {
+        if !self.is_open {
+            return;
+        }
+
+        self.is_open = false;
+        self.result = FindResult::default();
+        cx.emit(FindBarEvent::Stop);
+        cx.notify();
+    }
+
+    pub fn toggle(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        if self.is_open {
+            self.close(cx);
+        } else {
+            self.open(window, cx);
+        }
+    }
+
+    /// `EventHandler::on_find_result`で届いた結果を反映する。
+    pub fn set_result(&mut self, cx: &mut Context<Self>, result: FindResult) {
+        self.result = result;
+        cx.notify();
+    }
+
+    pub fn find(&mut self, cx: &mut Context<Self>, forward: bool) {
+        if !self.is_open {
+            return;
+        }
+
+        let text = self.input.read(cx).value().to_string();
+
+        if text.is_empty() {
+            self.result = FindResult::default();
+            cx.emit(FindBarEvent::Stop);
+        } else {
+            cx.emit(FindBarEvent::Find {
+                text,
+                forward,
+                match_case: self.match_case,
+            });
+        }
+
+        cx.notify();
+    }
+
+    fn toggle_match_case(&mut self, cx: &mut Context<Self>) {
+        self.match_case = !self.match_case;
+        // 大文字と小文字の区別を変えた場合は、最初から検索し直す。
+        cx.emit(FindBarEvent::Stop);
+        self.find(cx, true);
+    }
+}
+
+impl Render for FindBar {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        if !self.is_open {
+            return div().into_any_element();
+        }
+
+        h_flex()
+            .id("find-bar")
+            .key_context(FIND_BAR_CONTEXT)
+            .on_action(cx.listener(|find_bar, _: &CloseFindBar, _window, cx| {
+                find_bar.close(cx);
+            }))
+            .h(FIND_BAR_HEIGHT)
+            .w_full()
+            .px_2()
+            .gap_2()
+            .items_center()
+            .border_t_1()
+            .border_color(cx.theme().border)
+            .text_color(cx.theme().foreground)
+            .child(div().w_64().child(TextInput::new(&self.input).small()))
+            .child(
+                div()
+                    .text_sm()
+                    .text_color(cx.theme().muted_foreground)
+                    .child(format!(
+                        "{}/{}",
+                        self.result.active_index, self.result.count
+                    )),
+            )
+            .child(
+                Button::new("find-previous")
+                    .icon(Icon::new(IconName::ChevronUp))
+                    .ghost()
+                    .with_size(px(24.))
+                    .on_click(cx.listener(|find_bar, _, _window, cx| find_bar.find(cx, false))),
+            )
+            .child(
+                Button::new("find-next")
+                    .icon(Icon::new(IconName::ChevronDown))
+                    .ghost()
+                    .with_size(px(24.))
+                    .on_click(cx.listener(|find_bar, _, _window, cx| find_bar.find(cx, true))),
+            )
+            .child(
+                Button::new("find-match-case")
+                    .label("Aa")
+                    .ghost()
+                    .selected(self.match_case)
+                    .with_size(px(24.))
+                    .on_click(
+                        cx.listener(|find_bar, _, _window, cx| find_bar.toggle_match_case(cx)),
+                    ),
+            )
+            .child(div().flex_1())
+            .child(
+                Button::new("find-close")
+                    .icon(Icon::empty().path("icons/x.svg"))
+                    .ghost()
+                    .with_size(px(24.))
+                    .on_click(cx.listener(|find_bar, _, _window, cx| find_bar.close(cx))),
+            )
+            .into_any_element()
+    }
+}
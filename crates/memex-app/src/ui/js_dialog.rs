@@ -0,0 +1,264 @@
+use std::collections::{HashMap, VecDeque};
+
+use gpui::{App, Entity, FocusHandle, Focusable, KeyDownEvent, Window, div, prelude::*, px};
+use gpui_component::{
+    ActiveTheme,
+    button::{Button, ButtonVariants},
+    h_flex,
+    input::{InputState, TextInput},
+    v_flex,
+};
+use memex_backend::WorkspaceListState;
+use memex_cef::{BeforeUnloadRequest, JsDialogKind, JsDialogRequest};
+use uuid::Uuid;
+
+pub enum PendingJsDialog {
+    Dialog(JsDialogRequest),
+    BeforeUnload(BeforeUnloadRequest),
+}
+
+/// タブ毎の、返答待ちのJavaScriptのダイアログ。
+/// ダイアログはタブが選択されている間だけ表示するので、裏のタブのものはここで待たせておく。
+#[derive(Default)]
+pub struct JsDialogQueue {
+    next_key: u64,
+    dialogs: HashMap<Uuid, VecDeque<(u64, PendingJsDialog)>>,
+}
+
+impl JsDialogQueue {
+    pub fn new(cx: &mut App) -> Entity<Self> {
+        cx.new(|_| Self::default())
+    }
+
+    pub fn push(&mut self, cx: &mut Context<Self>, tab_id: Uuid, dialog: PendingJsDialog) {
+        self.next_key += 1;
+        self.dialogs
+            .entry(tab_id)
+            .or_default()
+            .push_back((self.next_key, dialog));
+
+        cx.notify();
+    }
+
+    /// タブの表示待ちのダイアログを全て破棄する。破棄されたダイアログはキャンセル扱いとなる。
+    pub fn clear(&mut self, cx: &mut Context<Self>, tab_id: Uuid) {
+        if self.dialogs.remove(&tab_id).is_some() {
+            cx.notify();
+        }
+    }
+
+    fn front(&self, tab_id: Uuid) -> Option<&(u64, PendingJsDialog)> {
+        self.dialogs
+            .get(&tab_id)
+            .and_then(|dialogs| dialogs.front())
+    }
+
+    fn pop_front(&mut self, cx: &mut Context<Self>, tab_id: Uuid) -> Option<PendingJsDialog> {
+        let dialogs = self.dialogs.get_mut(&tab_id)?;
+        let (_, dialog) = dialogs.pop_front()?;
+
+        if dialogs.is_empty() {
+            self.dialogs.remove(&tab_id);
+        }
+
+        cx.notify();
+        Some(dialog)
+    }
+
+    /// `prompt`であれば入力欄の初期値を返す。それ以外のダイアログなら`Some(None)`を返す。
+    fn front_dialog_text(&self, key: u64) -> Option<Option<String>> {
+        self.dialogs
+            .values()
+            .filter_map(|dialogs| dialogs.front())
+            .find(|(k, _)| *k == key)
+            .map(|(_, dialog)| match dialog {
+                PendingJsDialog::Dialog(request) if request.kind() == JsDialogKind::Prompt => {
+                    Some(request.default_prompt_text().to_owned())
+                }
+                _ => None,
+            })
+    }
+}
+
+/// 選択中のタブのダイアログを、ブラウザの表示領域の上にモーダルとして表示する。
+pub struct JsDialogHost {
+    queue: Entity<JsDialogQueue>,
+    workspace_list_state: Entity<WorkspaceListState>,
+    prompt_input: Entity<InputState>,
+    focus_handle: FocusHandle,
+    /// 最後に表示したダイアログ。新しいダイアログに切り替わった時にだけフォーカスを移すのに使う。
+    shown: Option<u64>,
+}
+
+impl JsDialogHost {
+    pub fn new(
+        window: &mut Window,
+        cx: &mut App,
+        queue: Entity<JsDialogQueue>,
+        workspace_list_state: Entity<WorkspaceListState>,
+    ) -> Entity<Self> {
+        cx.new(|cx| {
+            cx.observe(&queue, |_, _, cx| cx.notify()).detach();
+            cx.observe(&workspace_list_state, |_, _, cx| cx.notify())
+                .detach();
+
+            Self {
+                queue,
+                workspace_list_state,
+                prompt_input: cx.new(|cx| InputState::new(window, cx)),
+                focus_handle: cx.focus_handle(),
+                shown: None,
+            }
+        })
+    }
+
+    fn selected_tab(&self, cx: &App) -> Option<Uuid> {
+        self.workspace_list_state
+            .read(cx)
+            .current()
+            .read(cx)
+            .selected_tab()
+    }
+
+    fn respond(&mut self, tab_id: Uuid, accepted: bool, cx: &mut Context<Self>) {
+        let user_input = self.prompt_input.read(cx).value().to_string();
+        // 返答によってCEFから新たなダイアログが届くことがあるので、取り出してから返答する。
+        let dialog = self
+            .queue
+            .update(cx, |queue, cx| queue.pop_front(cx, tab_id));
+
+        match dialog {
+            Some(PendingJsDialog::Dialog(request)) => {
+                request.respond(accepted, Some(&user_input));
+            }
+            Some(PendingJsDialog::BeforeUnload(request)) => request.respond(accepted),
+            None => {}
+        }
+    }
+
+    fn focus_new_dialog(&mut self, window: &mut Window, cx: &mut Context<Self>, key: u64) {
+        if self.shown == Some(key) {
+            return;
+        }
+        self.shown = Some(key);
+
+        let default_prompt_text = match self.queue.read(cx).front_dialog_text(key) {
+            Some(text) => text,
+            None => return,
+        };
+
+        let is_prompt = default_prompt_text.is_some();
+        self.prompt_input.update(cx, |input, cx| {
+            input.set_value(default_prompt_text.unwrap_or_default(), window, cx);
+        });
+
+        let focus_handle = if is_prompt {
+            self.prompt_input.read(cx).focus_handle(cx)
+        } else {
+            self.focus_handle.clone()
+        };
+        window.defer(cx, move |window, _cx| window.focus(&focus_handle));
+    }
+}
+
+impl Render for JsDialogHost {
+    fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        // 裏のタブのダイアログは表示しないので、フォーカスを奪うこともない。
+        let Some(tab_id) = self.selected_tab(cx) else {
+            self.shown = None;
+            return div().into_any_element();
+        };
+        let Some(key) = self.queue.read(cx).front(tab_id).map(|(key, _)| *key) else {
+            self.shown = None;
+            return div().into_any_element();
+        };
+
+        self.focus_new_dialog(window, cx, key);
+
+        let (title, message, kind) = match &self.queue.read(cx).front(tab_id).unwrap().1 {
+            PendingJsDialog::Dialog(request) => (
+                request.origin_url().to_owned(),
+                request.message().to_owned(),
+                Some(request.kind()),
+            ),
+            PendingJsDialog::BeforeUnload(request) => (
+                if request.is_reload() {
+                    "このページを再読み込みしますか？".to_owned()
+                } else {
+                    "このページを離れますか？".to_owned()
+                },
+                "行った変更が保存されない可能性があります。".to_owned(),
+                None,
+            ),
+        };
+
+        let (accept_label, cancel_label) = match kind {
+            Some(JsDialogKind::Alert) => ("OK", None),
+            Some(_) => ("OK", Some("キャンセル")),
+            None => ("離れる", Some("留まる")),
+        };
+
+        div()
+            .id("js-dialog-overlay")
+            .absolute()
+            .inset_0()
+            .flex()
+            .justify_center()
+            .items_start()
+            .pt(px(48.))
+            .bg(cx.theme().background.alpha(0.5))
+            .occlude()
+            .child(
+                v_flex()
+                    .id("js-dialog")
+                    .track_focus(&self.focus_handle)
+                    .on_key_down(cx.listener(move |this, event: &KeyDownEvent, _window, cx| {
+                        match event.keystroke.key.as_str() {
+                            "enter" => this.respond(tab_id, true, cx),
+                            "escape" => this.respond(tab_id, false, cx),
+                            _ => {}
+                        }
+                    }))
+                    .w(px(420.))
+                    .gap_3()
+                    .p_4()
+                    .rounded_xl()
+                    .border_1()
+                    .border_color(cx.theme().border)
+                    .bg(cx.theme().popover)
+                    .text_color(cx.theme().popover_foreground)
+                    .shadow_lg()
+                    .child(
+                        div()
+                            .text_sm()
+                            .text_color(cx.theme().muted_foreground)
+                            .child(title),
+                    )
+                    .child(div().child(message))
+                    .when(kind == Some(JsDialogKind::Prompt), |this| {
+                        this.child(TextInput::new(&self.prompt_input))
+                    })
+                    .child(
+                        h_flex()
+                            .justify_end()
+                            .gap_2()
+                            .when_some(cancel_label, |this, label| {
+                                this.child(Button::new("js-dialog-cancel").label(label).on_click(
+                                    cx.listener(move |this, _, _window, cx| {
+                                        this.respond(tab_id, false, cx)
+                                    }),
+                                ))
+                            })
+                            .child(
+                                Button::new("js-dialog-accept")
+                                    .label(accept_label)
+                                    .primary()
+                                    .on_click(cx.listener(move |this, _, _window, cx| {
+                                        this.respond(tab_id, true, cx)
+                                    })),
+                            ),
+                    ),
+            )
+            .into_any_element()
+    }
+}
@@ -1,6 +1,11 @@
 mod browser_window;
+mod command_palette;
 mod consts;
 mod exproler;
+mod find_bar;
+mod js_dialog;
+mod omnibox;
+mod page_zoom;
 mod start;
 mod tab_bar;
 mod title_bar;
@@ -8,8 +13,13 @@ mod workbench;
 mod workspace_list;
 
 pub use browser_window::*;
+pub use command_palette::*;
 pub use consts::*;
 pub use exproler::*;
+pub use find_bar::*;
+pub use js_dialog::*;
+pub use omnibox::*;
+pub use page_zoom::*;
 pub use start::*;
 pub use tab_bar::*;
 pub use title_bar::*;
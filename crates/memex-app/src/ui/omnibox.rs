@@ -0,0 +1,277 @@
+use gpui::{
+    App, Entity, EventEmitter, Focusable, MouseButton, Window, deferred, div, prelude::*, px,
+};
+use gpui_component::{
+    ActiveTheme, h_flex,
+    input::{InputEvent, InputState, TextInput},
+    v_flex,
+};
+use memex_core::{
+    Browser, Id, OmniboxAction, OmniboxSuggestion, OmniboxSuggestionKind, TabMarker,
+    WorkspaceMarker, db::TabLocationData,
+};
+
+use crate::browser::{AppBrowser, selected_tab_mut};
+
+/// 一度に表示する候補の数。
+const MAX_VISIBLE_SUGGESTIONS: usize = 8;
+
+/// オムニボックスの操作。URLと検索はオムニボックスが選択中のタブで開くので、それ以外を受け取った側が行う。
+#[derive(Clone, Copy, Debug)]
+pub enum OmniboxEvent {
+    SwitchToTab {
+        workspace_id: Id<WorkspaceMarker>,
+        tab_id: Id<TabMarker>,
+    },
+}
+
+/// URLバー。入力をmemex-coreのオムニボックスで解釈して候補を表示し、選んだものを選択中のタブで開く。
+pub struct Omnibox {
+    input: Entity<InputState>,
+    suggestions: Vec<OmniboxSuggestion>,
+    /// `suggestions`を求めた入力。
+    suggested_text: String,
+    /// 最後に候補を求めた入力。古い入力への候補が後から届いた場合に捨てるのに使う。
+    requested_text: String,
+}
+
+impl EventEmitter<OmniboxEvent> for Omnibox {}
+
+impl Omnibox {
+    pub fn new(window: &mut Window, cx: &mut App) -> Entity<Self> {
+        cx.new(|cx| {
+            let input =
+                cx.new(|cx| InputState::new(window, cx).placeholder("URLを入力、または検索"));
+
+            cx.subscribe_in(
+                &input,
+                window,
+                |omnibox, _input, event, window, cx| match event {
+                    InputEvent::Change => omnibox.update_suggestions(cx),
+                    InputEvent::PressEnter { .. } => omnibox.confirm(window, cx, 0),
+                    _ => {}
+                },
+            )
+            .detach();
+            // 入力欄のフォーカスが変わったら、候補を表示し直す。
+            cx.observe(&input, |_, _, cx| cx.notify()).detach();
+
+            Self {
+                input,
+                suggestions: Vec::new(),
+                suggested_text: String::new(),
+                requested_text: String::new(),
+            }
+        })
+    }
+
+    pub fn focus(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        self.input.update(cx, |input, cx| input.focus(window, cx));
+    }
+
+    /// 表示中のページのURLを表示する。入力中であれば書き換えない。
+    pub fn set_url(&mut self, window: &mut Window, cx: &mut Context<Self>, url: &str) {
+        if self.input.read(cx).focus_handle(cx).is_focused(window) {
+            return;
+        }
+
+        self.input
+            .update(cx, |input, cx| input.set_value(url.to_owned(), window, cx));
+        self.clear_suggestions(cx);
+    }
+
+    fn update_suggestions(&mut self, cx: &mut Context<Self>) {
+        let text = self.input.read(cx).value().to_string();
+        self.requested_text = text.clone();
+
+        if text.trim().is_empty() {
+            self.clear_suggestions(cx);
+            return;
+        }
+        let Some(app_browser) = cx.try_global::<AppBrowser>().cloned() else {
+            return;
+        };
+
+        cx.spawn(async move |omnibox, cx| {
+            let result = app_browser.lock().await.omnibox_suggestions(&text).await;
+
+            _ = omnibox.update(cx, |omnibox, cx| {
+                if omnibox.requested_text != text {
+                    return;
+                }
+
+                match result {
+                    Ok(result) => {
+                        omnibox.suggestions = result.suggestions;
+                        omnibox.suggestions.truncate(MAX_VISIBLE_SUGGESTIONS);
+                        omnibox.suggested_text = text;
+                    }
+                    Err(error) => log::warn!("オムニボックスの候補の取得に失敗しました：{error:#}"),
+                }
+                cx.notify();
+            });
+        })
+        .detach();
+    }
+
+    fn clear_suggestions(&mut self, cx: &mut Context<Self>) {
+        self.suggestions.clear();
+        self.suggested_text.clear();
+        cx.notify();
+    }
+
+    /// `index`番目の候補を開く。入力に対する候補がまだ届いていなければ、求め直して最も関連するものを開く。
+    fn confirm(&mut self, window: &mut Window, cx: &mut Context<Self>, index: usize) {
+        let text = self.input.read(cx).value().to_string();
+        if text.trim().is_empty() {
+            return;
+        }
+        let Some(app_browser) = cx.try_global::<AppBrowser>().cloned() else {
+            return;
+        };
+
+        let suggestion = if self.suggested_text == text {
+            self.suggestions.get(index).cloned()
+        } else {
+            None
+        };
+        self.requested_text.clear();
+        self.clear_suggestions(cx);
+        window.blur();
+
+        cx.spawn(async move |omnibox, cx| {
+            let action = match suggestion {
+                Some(suggestion) => suggestion.action,
+                None => match app_browser.lock().await.omnibox_suggestions(&text).await {
+                    Ok(result) => match result.suggestions.into_iter().next() {
+                        Some(suggestion) => suggestion.action,
+                        None => return,
+                    },
+                    Err(error) => {
+                        log::error!("オムニボックスの候補の取得に失敗しました：{error:#}");
+                        return;
+                    }
+                },
+            };
+
+            match action {
+                OmniboxAction::Navigate(location) => {
+                    let mut browser = app_browser.lock().await;
+                    if let Err(error) = navigate_selected_tab(&mut browser, location).await {
+                        log::error!("ページを開けませんでした：{error:#}");
+                    }
+                }
+                // タブの切り替えは、UIの状態を通して行う。
+                OmniboxAction::SwitchToTab {
+                    workspace_id,
+                    tab_id,
+                } => {
+                    _ = omnibox.update(cx, |_, cx| {
+                        cx.emit(OmniboxEvent::SwitchToTab {
+                            workspace_id,
+                            tab_id,
+                        });
+                    });
+                }
+            }
+        })
+        .detach();
+    }
+}
+
+async fn navigate_selected_tab(
+    browser: &mut Browser,
+    location: TabLocationData,
+) -> anyhow::Result<()> {
+    selected_tab_mut(browser)?.navigate(location).await
+}
+
+fn kind_label(kind: OmniboxSuggestionKind) -> &'static str {
+    match kind {
+        OmniboxSuggestionKind::Url => "URL",
+        OmniboxSuggestionKind::Search => "検索",
+        OmniboxSuggestionKind::History => "履歴",
+        OmniboxSuggestionKind::Bookmark => "ブックマーク",
+        OmniboxSuggestionKind::OpenTab => "タブ",
+        OmniboxSuggestionKind::File => "ファイル",
+    }
+}
+
+impl Render for Omnibox {
+    fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        // 入力中だけ候補を表示する。
+        let is_focused = self.input.read(cx).focus_handle(cx).is_focused(window);
+        let rows = self
+            .suggestions
+            .iter()
+            .enumerate()
+            .map(|(index, suggestion)| {
+                h_flex()
+                    .id(index)
+                    .w_full()
+                    .px_3()
+                    .py_1()
+                    .gap_3()
+                    .items_center()
+                    .rounded_md()
+                    .when(index == 0, |this| {
+                        this.bg(cx.theme().accent)
+                            .text_color(cx.theme().accent_foreground)
+                    })
+                    .child(
+                        div()
+                            .w(px(72.))
+                            .flex_none()
+                            .text_xs()
+                            .text_color(cx.theme().muted_foreground)
+                            .child(kind_label(suggestion.kind)),
+                    )
+                    .child(
+                        div()
+                            .flex_1()
+                            .overflow_hidden()
+                            .child(suggestion.title.clone()),
+                    )
+                    .child(
+                        div()
+                            .max_w(px(320.))
+                            .overflow_hidden()
+                            .text_sm()
+                            .text_color(cx.theme().muted_foreground)
+                            .child(suggestion.description.clone()),
+                    )
+                    .on_mouse_down(
+                        MouseButton::Left,
+                        cx.listener(move |omnibox, _event, window, cx| {
+                            omnibox.confirm(window, cx, index);
+                        }),
+                    )
+            })
+            .collect::<Vec<_>>();
+
+        div()
+            .relative()
+            .flex_1()
+            .child(TextInput::new(&self.input))
+            .when(is_focused && !rows.is_empty(), |this| {
+                this.child(deferred(
+                    v_flex()
+                        .id("omnibox-suggestions")
+                        .absolute()
+                        .top_full()
+                        .left_0()
+                        .w_full()
+                        .mt_1()
+                        .p_1()
+                        .rounded_lg()
+                        .border_1()
+                        .border_color(cx.theme().border)
+                        .bg(cx.theme().popover)
+                        .text_color(cx.theme().popover_foreground)
+                        .shadow_lg()
+                        .occlude()
+                        .children(rows),
+                ))
+            })
+    }
+}
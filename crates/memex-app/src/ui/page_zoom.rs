@@ -0,0 +1,11 @@
+use gpui::actions;
+
+actions!(page_zoom, [ZoomIn, ZoomOut, ResetZoom]);
+
+/// ズームの操作。受け取った側が、選択中のタブのズームレベルを変えて保存する。
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PageZoomEvent {
+    ZoomIn,
+    ZoomOut,
+    Reset,
+}
@@ -2,7 +2,7 @@ use gpui::{App, Div, Entity, MouseButton, Stateful, Window, actions, prelude::*,
 use gpui_component::*;
 use memex_backend::{LayoutState, WorkspaceState};
 
-actions!(tab_actions, [NextTab, PreviousTab]);
+actions!(tab_actions, [NewTab, CloseTab, NextTab, PreviousTab]);
 
 pub struct TabBar {
     layout_state: Entity<LayoutState>,
@@ -21,6 +21,37 @@ impl TabBar {
         })
     }
 
+    /// 新しいタブを開いて選択する。
+    pub fn create_tab(&self, window: &mut Window, cx: &mut App) {
+        self.workspace.update(cx, |workspace, cx| {
+            workspace
+                .create_tab(window, cx, true)
+                .expect("新しいタブを開くのに失敗しました。");
+
+            cx.notify();
+        });
+    }
+
+    /// 選択中のタブから`offset`だけ離れたタブを選択する。端を越えたら反対の端に戻る。
+    pub fn select_relative(&self, cx: &mut App, offset: isize) {
+        let workspace = self.workspace.read(cx);
+        let tab_order = workspace.tab_order();
+        if tab_order.is_empty() {
+            return;
+        }
+
+        let current = workspace
+            .selected_tab()
+            .and_then(|selected| tab_order.iter().position(|id| *id == selected))
+            .unwrap_or(0);
+        let index = (current as isize + offset).rem_euclid(tab_order.len() as isize) as usize;
+        let id = tab_order[index];
+
+        self.workspace.update(cx, |workspace, cx| {
+            workspace.select(cx, id);
+        });
+    }
+
     fn render_tabs(&self, cx: &mut App) -> impl Iterator<Item = Stateful<Div>> {
         let tab_order = self.workspace.read(cx).tab_order();
 
@@ -47,7 +78,14 @@ impl TabBar {
                         })
                     },
                 )
-                .child(h_flex().justify_start().items_center().child(tab.title()))
+                .child(
+                    h_flex()
+                        .justify_start()
+                        .items_center()
+                        .gap_2()
+                        .child(letter_avatar(&tab.title(), cx))
+                        .child(tab.title()),
+                )
                 .child(Icon::empty().path("icons/x.svg"))
                 .on_mouse_up(MouseButton::Left, {
                     let workspace = self.workspace.clone();
@@ -79,19 +117,32 @@ impl Render for TabBar {
                     .justify_center()
                     .items_center()
                     .child(Icon::new(IconName::Plus).size_6())
-                    .on_mouse_up(MouseButton::Left, {
-                        let workspace = self.workspace.clone();
-
-                        move |_event, window, cx| {
-                            workspace.update(cx, |workspace, cx| {
-                                workspace
-                                    .create_tab(window, cx, true)
-                                    .expect("新しいタブを開くのに失敗しました。");
-
-                                cx.notify();
-                            });
-                        }
-                    }),
+                    .on_mouse_up(
+                        MouseButton::Left,
+                        cx.listener(|tab_bar, _event, window, cx| {
+                            tab_bar.create_tab(window, cx);
+                        }),
+                    ),
             )
     }
 }
+
+/// ファビコンの代わりに表示する、タイトルの頭文字。
+fn letter_avatar(title: &str, cx: &App) -> Div {
+    let letter = title
+        .chars()
+        .find(|c| c.is_alphanumeric())
+        .map(|c| c.to_uppercase().to_string())
+        .unwrap_or_else(|| "?".to_owned());
+
+    v_flex()
+        .flex_none()
+        .size_4()
+        .justify_center()
+        .items_center()
+        .rounded_sm()
+        .bg(cx.theme().primary)
+        .text_color(cx.theme().primary_foreground)
+        .text_xs()
+        .child(letter)
+}
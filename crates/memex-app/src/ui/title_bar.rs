@@ -1,23 +1,39 @@
-use gpui::{App, Entity, div, prelude::*, px};
+use gpui::{App, Entity, EventEmitter, actions, div, prelude::*, px};
 use gpui_component::{
     ActiveTheme, Icon, IconName, Sizable,
     button::{Button, ButtonVariants},
-    h_flex,
-    input::{InputState, TextInput},
-    v_flex,
+    h_flex, v_flex,
 };
 use memex_backend::{LayoutState, WorkspaceListState, WorkspaceState};
 
 use crate::ui::{
+    CloseTab, FindBar, FindNext, FindPrevious, NewTab, NextTab, Omnibox, PageZoomEvent,
+    PreviousTab, ResetZoom, ToggleFindBar, ZoomIn, ZoomOut,
     consts::{TOP_TAB_BAR_HEIGHT, URL_BAR_HEIGHT},
     tab_bar::TabBar,
 };
 
+actions!(navigation, [GoBack, GoForward, Reload, FocusUrlBar]);
+
+/// 選択中のタブへの操作。受け取った側が、選択中のタブで行う。
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TabCommandEvent {
+    GoBack,
+    GoForward,
+    Reload,
+    Close,
+}
+
 pub struct TitleBar {
     tabs: Entity<TabBar>,
-    url: Entity<InputState>,
+    omnibox: Entity<Omnibox>,
+    find_bar: Entity<FindBar>,
 }
 
+impl EventEmitter<PageZoomEvent> for TitleBar {}
+
+impl EventEmitter<TabCommandEvent> for TitleBar {}
+
 impl TitleBar {
     pub fn new(
         window: &mut gpui::Window,
@@ -34,22 +50,93 @@ impl TitleBar {
                 move |title_bar: &mut TitleBar, entity, cx: &mut Context<'_, _>| {
                     let workspace = entity.read(cx).current().clone();
                     title_bar.tabs = TabBar::new(cx, layout_state.clone(), workspace);
+
+                    // 検索結果は前のタブのものなので閉じる。
+                    title_bar
+                        .find_bar
+                        .update(cx, |find_bar, cx| find_bar.close(cx));
                 }
             })
             .detach();
 
             Self {
                 tabs: TabBar::new(cx, layout_state, workspace_state),
-                url: cx
-                    .new(|cx| InputState::new(window, cx).default_value("https://www.google.com/")),
+                omnibox: Omnibox::new(window, cx),
+                find_bar: FindBar::new(window, cx),
             }
         })
     }
+
+    /// URLバー。`OmniboxEvent`を購読して、開いているタブへの切り替えを行うこと。
+    pub fn omnibox(&self) -> &Entity<Omnibox> {
+        &self.omnibox
+    }
+
+    /// ページ内検索のバー。`FindBarEvent`を購読して、選択中のタブで検索を行うこと。
+    pub fn find_bar(&self) -> &Entity<FindBar> {
+        &self.find_bar
+    }
 }
 
 impl Render for TitleBar {
     fn render(&mut self, _window: &mut gpui::Window, cx: &mut Context<Self>) -> impl IntoElement {
         v_flex()
+            .on_action(cx.listener(|title_bar, _: &ToggleFindBar, window, cx| {
+                title_bar
+                    .find_bar
+                    .update(cx, |find_bar, cx| find_bar.toggle(window, cx));
+            }))
+            .on_action(cx.listener(|title_bar, _: &FindNext, _window, cx| {
+                title_bar
+                    .find_bar
+                    .update(cx, |find_bar, cx| find_bar.find(cx, true));
+            }))
+            .on_action(cx.listener(|title_bar, _: &FindPrevious, _window, cx| {
+                title_bar
+                    .find_bar
+                    .update(cx, |find_bar, cx| find_bar.find(cx, false));
+            }))
+            .on_action(cx.listener(|_, _: &ZoomIn, _window, cx| {
+                cx.emit(PageZoomEvent::ZoomIn);
+            }))
+            .on_action(cx.listener(|_, _: &ZoomOut, _window, cx| {
+                cx.emit(PageZoomEvent::ZoomOut);
+            }))
+            .on_action(cx.listener(|_, _: &ResetZoom, _window, cx| {
+                cx.emit(PageZoomEvent::Reset);
+            }))
+            .on_action(cx.listener(|title_bar, _: &NewTab, window, cx| {
+                title_bar
+                    .tabs
+                    .update(cx, |tabs, cx| tabs.create_tab(window, cx));
+            }))
+            .on_action(cx.listener(|_, _: &CloseTab, _window, cx| {
+                cx.emit(TabCommandEvent::Close);
+            }))
+            .on_action(cx.listener(|title_bar, _: &NextTab, _window, cx| {
+                title_bar
+                    .tabs
+                    .update(cx, |tabs, cx| tabs.select_relative(cx, 1));
+            }))
+            .on_action(cx.listener(|title_bar, _: &PreviousTab, _window, cx| {
+                title_bar
+                    .tabs
+                    .update(cx, |tabs, cx| tabs.select_relative(cx, -1));
+            }))
+            .on_action(cx.listener(|_, _: &GoBack, _window, cx| {
+                cx.emit(TabCommandEvent::GoBack);
+            }))
+            .on_action(cx.listener(|_, _: &GoForward, _window, cx| {
+                cx.emit(TabCommandEvent::GoForward);
+            }))
+            .on_action(cx.listener(|_, _: &Reload, _window, cx| {
+                cx.emit(TabCommandEvent::Reload);
+            }))
+            .on_action(cx.listener(|title_bar, _: &FocusUrlBar, window, cx| {
+                title_bar
+                    .omnibox
+                    .update(cx, |omnibox, cx| omnibox.focus(window, cx));
+            }))
             .child(
                 div()
                     .w_full()
@@ -71,24 +158,33 @@ impl Render for TitleBar {
                                     .icon(Icon::new(IconName::ChevronLeft))
                                     .ghost()
                                     .size_10()
-                                    .with_size(px(28.)),
+                                    .with_size(px(28.))
+                                    .on_click(cx.listener(|_, _, _window, cx| {
+                                        cx.emit(TabCommandEvent::GoBack);
+                                    })),
                             )
                             .child(
                                 Button::new("forward")
                                     .icon(Icon::new(IconName::ChevronRight))
                                     .ghost()
                                     .size_10()
-                                    .with_size(px(28.)),
+                                    .with_size(px(28.))
+                                    .on_click(cx.listener(|_, _, _window, cx| {
+                                        cx.emit(TabCommandEvent::GoForward);
+                                    })),
                             )
                             .child(
                                 Button::new("reload")
                                     .icon(Icon::empty().path("icons/rotate-cw.svg"))
                                     .ghost()
                                     .size_10()
-                                    .with_size(px(28.)),
+                                    .with_size(px(28.))
+                                    .on_click(cx.listener(|_, _, _window, cx| {
+                                        cx.emit(TabCommandEvent::Reload);
+                                    })),
                             ),
                     )
-                    .child(TextInput::new(&self.url))
+                    .child(self.omnibox.clone())
                     .child(
                         Button::new("menu")
                             .icon(Icon::new(IconName::EllipsisVertical))
@@ -97,5 +193,6 @@ impl Render for TitleBar {
                             .with_size(px(28.)),
                     ),
             )
+            .child(self.find_bar.clone())
     }
 }
@@ -1,12 +1,23 @@
+use anyhow::Context as _;
 use gpui::{App, Entity, Window, div, prelude::*};
 use gpui_component::{
     ActiveTheme, h_flex,
     resizable::{ResizableState, h_resizable, resizable_panel},
     v_flex,
 };
-use memex_backend::LayoutState;
+use memex_backend::{LayoutState, data::WorkspaceIconData};
+use memex_cef::FindResult;
+use memex_core::{Id, PaletteItem, PaletteTarget, ZoomScope};
+use uuid::Uuid;
 
-use crate::ui::{Exproler, TitleBar, WorkspaceList};
+use crate::{
+    browser::{AppBrowser, selected_tab},
+    ui::{
+        CommandPalette, CommandPaletteEvent, Exproler, FindBarEvent, JsDialogHost, NewWorkspace,
+        NextWorkspace, OmniboxEvent, PageZoomEvent, PreviousWorkspace, TitleBar,
+        ToggleCommandPalette, ToggleForceDark, WorkspaceList,
+    },
+};
 
 /// ワークスペースを開いている前提のView。
 /// ワークスペース一覧を読み込み終わった後にしか作れない。
@@ -16,26 +27,239 @@ pub struct Workbench {
     workspace_list: Entity<WorkspaceList>,
     title_bar: Entity<TitleBar>,
     exproler: Entity<Exproler>,
+    js_dialog_host: Entity<JsDialogHost>,
+    command_palette: Entity<CommandPalette>,
 
     workspace_box_state: Entity<ResizableState>,
 }
 
 impl Workbench {
     pub fn new(
+        window: &mut Window,
         cx: &mut App,
         layout_state: Entity<LayoutState>,
         workspace_list: Entity<WorkspaceList>,
         title_bar: Entity<TitleBar>,
+        js_dialog_host: Entity<JsDialogHost>,
     ) -> Entity<Self> {
-        cx.new(move |cx| Self {
-            layout_state,
-            workspace_list,
-            title_bar,
-            exproler: Exproler::new(cx),
+        cx.new(move |cx| {
+            let command_palette = CommandPalette::new(window, cx);
+
+            cx.subscribe_in(
+                &command_palette,
+                window,
+                |workbench, _palette, event, window, cx| {
+                    if let CommandPaletteEvent::Confirm(target) = event {
+                        workbench.open_palette_target(window, cx, target);
+                    }
+                },
+            )
+            .detach();
+
+            // 検索バーの操作を、選択中のタブで行う。
+            let find_bar = title_bar.read(cx).find_bar().clone();
+            cx.subscribe(
+                &find_bar,
+                |_workbench, _find_bar, event: &FindBarEvent, cx| {
+                    let event = event.clone();
+
+                    AppBrowser::update(cx, async move |browser| {
+                        let webview = selected_tab(browser)?
+                            .webview()
+                            .context("タブが読み込まれていません。")?;
+
+                        match event {
+                            FindBarEvent::Find {
+                                text,
+                                forward,
+                                match_case,
+                            } => webview.find(&text, forward, match_case),
+                            FindBarEvent::Stop => webview.stop_finding(),
+                        }
+                    });
+                },
+            )
+            .detach();
+
+            // オムニボックスで選んだ、開いているタブへ切り替える。
+            let omnibox = title_bar.read(cx).omnibox().clone();
+            cx.subscribe_in(
+                &omnibox,
+                window,
+                |workbench, _omnibox, event, window, cx| {
+                    let OmniboxEvent::SwitchToTab {
+                        workspace_id,
+                        tab_id,
+                    } = *event;
+
+                    workbench.switch_to_tab(window, cx, *workspace_id, *tab_id);
+                },
+            )
+            .detach();
+
+            // ズームの操作を、選択中のタブで行う。倍率はオリジン毎に、全てのワークスペースで記憶する。
+            cx.subscribe(
+                &title_bar,
+                |_workbench, _title_bar, event: &PageZoomEvent, cx| {
+                    let event = *event;
 
-            workspace_box_state: ResizableState::new(cx),
+                    AppBrowser::update(cx, async move |browser| {
+                        let tab = selected_tab(browser)?;
+
+                        match event {
+                            PageZoomEvent::ZoomIn => tab.zoom_in(ZoomScope::Global).await,
+                            PageZoomEvent::ZoomOut => tab.zoom_out(ZoomScope::Global).await,
+                            PageZoomEvent::Reset => tab.reset_zoom(ZoomScope::Global).await,
+                        }
+                    });
+                },
+            )
+            .detach();
+
+            Self {
+                layout_state,
+                workspace_list,
+                title_bar,
+                exproler: Exproler::new(cx),
+                js_dialog_host,
+                command_palette,
+
+                workspace_box_state: ResizableState::new(cx),
+            }
         })
     }
+
+    /// コマンドパレット。ブックマークとファイルはここでは開かないので、
+    /// `CommandPaletteEvent`を購読して開くこと。`Open`を受け取ったら、`add_items`で項目を加えられる。
+    pub fn command_palette(&self) -> &Entity<CommandPalette> {
+        &self.command_palette
+    }
+
+    /// タブの検索結果を検索バーに反映する。選択中のタブのものでなければ無視する。
+    pub fn set_find_result(&self, cx: &mut App, tab_id: Uuid, result: FindResult) {
+        if !self.is_selected_tab(cx, tab_id) {
+            return;
+        }
+
+        self.title_bar
+            .read(cx)
+            .find_bar()
+            .clone()
+            .update(cx, |find_bar, cx| find_bar.set_result(cx, result));
+    }
+
+    /// タブのナビゲーションが確定したら、URLバーに表示する。選択中のタブのものでなければ無視する。
+    pub fn set_url(&self, window: &mut Window, cx: &mut App, tab_id: Uuid, url: &str) {
+        if !self.is_selected_tab(cx, tab_id) {
+            return;
+        }
+
+        self.title_bar
+            .read(cx)
+            .omnibox()
+            .clone()
+            .update(cx, |omnibox, cx| omnibox.set_url(window, cx, url));
+    }
+
+    fn is_selected_tab(&self, cx: &App, tab_id: Uuid) -> bool {
+        self.workspace_list
+            .read(cx)
+            .state()
+            .read(cx)
+            .current()
+            .read(cx)
+            .selected_tab()
+            == Some(tab_id)
+    }
+
+    /// 選択中のワークスペースのタブと、全てのワークスペースを、パレットの項目にする。
+    fn palette_items(&self, cx: &App) -> Vec<PaletteItem> {
+        let list = self.workspace_list.read(cx).state().read(cx);
+        let workspace_id = Id::from(list.selected());
+        let workspace = list.current().read(cx);
+
+        let tabs = workspace.tab_order().iter().filter_map(|tab_id| {
+            let tab = workspace.get_tab(*tab_id)?.read(cx);
+
+            Some(PaletteItem {
+                target: PaletteTarget::Tab {
+                    workspace_id,
+                    tab_id: Id::from(*tab_id),
+                },
+                title: tab.title().to_string(),
+                detail: String::new(),
+            })
+        });
+
+        let home = PaletteItem {
+            target: PaletteTarget::Workspace(Id::from(list.home())),
+            title: "ホーム".to_owned(),
+            detail: String::new(),
+        };
+        let workspaces = list.order().iter().enumerate().map(|(ix, id)| {
+            // ワークスペースの一覧と同じく、アイコンの文字で見分けられるようにする。
+            let detail = match list.list_metadata().get(id).map(|metadata| metadata.icon()) {
+                Some(WorkspaceIconData::Emoji(text) | WorkspaceIconData::Text(text)) => {
+                    text.to_string()
+                }
+                _ => String::new(),
+            };
+
+            PaletteItem {
+                target: PaletteTarget::Workspace(Id::from(*id)),
+                title: format!("ワークスペース {}", ix + 1),
+                detail,
+            }
+        });
+
+        tabs.chain(std::iter::once(home))
+            .chain(workspaces)
+            .collect()
+    }
+
+    fn open_palette_target(
+        &mut self,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+        target: &PaletteTarget,
+    ) {
+        let list = self.workspace_list.read(cx).state().clone();
+
+        match target {
+            PaletteTarget::Workspace(id) => WorkspaceList::open(list, window, cx, **id),
+            PaletteTarget::Tab {
+                workspace_id,
+                tab_id,
+            } => self.switch_to_tab(window, cx, **workspace_id, **tab_id),
+            _ => {}
+        }
+    }
+
+    /// ワークスペースを開き、そのタブを選択する。
+    fn switch_to_tab(
+        &mut self,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+        workspace_id: Uuid,
+        tab_id: Uuid,
+    ) {
+        let list = self.workspace_list.read(cx).state().clone();
+
+        if list.read(cx).selected() != workspace_id {
+            // 読み込み済みでなければ選択が後で終わるので、タブは選べない。
+            let is_loaded = list.read(cx).is_loaded(workspace_id);
+            WorkspaceList::open(list.clone(), window, cx, workspace_id);
+            if !is_loaded {
+                return;
+            }
+        }
+
+        let workspace = list.read(cx).current().clone();
+        workspace.update(cx, |workspace, cx| {
+            workspace.select(cx, tab_id);
+            cx.notify();
+        });
+    }
 }
 
 impl Render for Workbench {
@@ -44,7 +268,37 @@ impl Render for Workbench {
 
         h_flex()
             .id("window-body")
+            .relative()
             .size_full()
+            .on_action(
+                cx.listener(|workbench, _: &ToggleCommandPalette, window, cx| {
+                    let items = workbench.palette_items(cx);
+                    workbench
+                        .command_palette
+                        .update(cx, |palette, cx| palette.toggle(window, cx, items));
+                }),
+            )
+            .on_action(cx.listener(|workbench, _: &NewWorkspace, window, cx| {
+                let list = workbench.workspace_list.read(cx).state().clone();
+                WorkspaceList::create(list, window, cx);
+            }))
+            .on_action(cx.listener(|workbench, _: &NextWorkspace, window, cx| {
+                let list = workbench.workspace_list.read(cx).state().clone();
+                WorkspaceList::open_relative(list, window, cx, 1);
+            }))
+            .on_action(cx.listener(|workbench, _: &PreviousWorkspace, window, cx| {
+                let list = workbench.workspace_list.read(cx).state().clone();
+                WorkspaceList::open_relative(list, window, cx, -1);
+            }))
+            .on_action(cx.listener(|_workbench, _: &ToggleForceDark, _window, cx| {
+                AppBrowser::update(cx, async |browser| {
+                    let workspace = browser
+                        .workspace(browser.selected_workspace())
+                        .context("選択中のワークスペースが見つかりません。")?;
+
+                    workspace.set_force_dark(!workspace.is_force_dark()).await
+                });
+            }))
             .child(
                 v_flex()
                     .id("workspace-list")
@@ -88,8 +342,16 @@ impl Render for Workbench {
                                     )
                                 },
                             )
-                            .child(h_flex().size_full().p_4().into_any_element()),
+                            .child(
+                                h_flex()
+                                    .relative()
+                                    .size_full()
+                                    .p_4()
+                                    .child(self.js_dialog_host.clone())
+                                    .into_any_element(),
+                            ),
                     ),
             )
+            .child(self.command_palette.clone())
     }
 }
@@ -1,4 +1,6 @@
-use gpui::{AnyElement, App, Entity, MouseButton, Pixels, ReadGlobal, Window, div, prelude::*};
+use gpui::{
+    AnyElement, App, Entity, MouseButton, Pixels, ReadGlobal, Window, actions, div, img, prelude::*,
+};
 use gpui_component::{ActiveTheme, Icon, IconName, Sizable, v_flex};
 use memex_backend::{
     LayoutState, WorkspaceListState, WorkspaceState,
@@ -9,6 +11,16 @@ use memex_backend::{
 };
 use uuid::Uuid;
 
+actions!(
+    workspace_actions,
+    [
+        NewWorkspace,
+        NextWorkspace,
+        PreviousWorkspace,
+        ToggleForceDark
+    ]
+);
+
 pub struct WorkspaceList {
     state: Entity<WorkspaceListState>,
     layout_state: Entity<LayoutState>,
@@ -30,7 +42,7 @@ impl WorkspaceList {
         &self.state
     }
 
-    fn open(list: Entity<WorkspaceListState>, window: &mut Window, cx: &mut App, id: Uuid) {
+    pub fn open(list: Entity<WorkspaceListState>, window: &mut Window, cx: &mut App, id: Uuid) {
         if list.read(cx).is_loaded(id) {
             list.update(cx, |list, cx| {
                 list.select(cx, id).unwrap();
@@ -64,6 +76,59 @@ impl WorkspaceList {
             .detach();
     }
 
+    /// ホームから順に並べて、選択中のワークスペースから`offset`だけ離れたものを開く。
+    /// 端を越えたら反対の端に戻る。
+    pub fn open_relative(
+        list: Entity<WorkspaceListState>,
+        window: &mut Window,
+        cx: &mut App,
+        offset: isize,
+    ) {
+        let state = list.read(cx);
+        let order = std::iter::once(state.home())
+            .chain(state.order().iter().cloned())
+            .collect::<Vec<_>>();
+        let current = order
+            .iter()
+            .position(|id| *id == state.selected())
+            .unwrap_or(0);
+        let index = (current as isize + offset).rem_euclid(order.len() as isize) as usize;
+
+        Self::open(list, window, cx, order[index]);
+    }
+
+    /// 新しいワークスペースを作って選択する。
+    pub fn create(list: Entity<WorkspaceListState>, window: &mut Window, cx: &mut App) {
+        let path = AppPath::global(cx).clone();
+
+        window
+            .spawn(cx, async move |cx| {
+                let data = create_workspace(&path, Uuid::new_v4(), "New workspace".to_owned())
+                    .await
+                    .expect("ワークスペースのデータの作成に失敗しました。");
+
+                list.update_in(cx, move |list, window, cx| {
+                    let rect = list.layout_state.read(cx).view_rect(window);
+                    let id = data.id;
+                    let workspace = WorkspaceState::new(window, cx, rect, data, Vec::new())
+                        .expect("ワークスペースの作成に失敗しました。");
+
+                    list.add(cx, workspace).unwrap();
+                    list.select(cx, id).unwrap();
+
+                    let data = WorkspaceListData::from_state(list);
+                    cx.background_spawn(async move {
+                        save_workspace_list(&path, &data).await.unwrap();
+                    })
+                    .detach();
+
+                    cx.notify();
+                })
+                .unwrap();
+            })
+            .detach();
+    }
+
     pub fn render_user_workspaces(&self, cx: &mut App) -> Vec<AnyElement> {
         let mut children = Vec::new();
 
@@ -174,36 +239,7 @@ impl RenderOnce for WorkspaceAddButton {
             .items_center()
             .child(Icon::new(IconName::Plus).size_8())
             .on_mouse_down(MouseButton::Left, move |_event, window, cx| {
-                let path = AppPath::global(cx).clone();
-                let list = list.clone();
-
-                window
-                    .spawn(cx, async move |cx| {
-                        let data =
-                            create_workspace(&path, Uuid::new_v4(), "New workspace".to_owned())
-                                .await
-                                .expect("ワークスペースのデータの作成に失敗しました。");
-
-                        list.update_in(cx, move |list, window, cx| {
-                            let rect = list.layout_state.read(cx).view_rect(window);
-                            let id = data.id;
-                            let workspace = WorkspaceState::new(window, cx, rect, data, Vec::new())
-                                .expect("ワークスペースの作成に失敗しました。");
-
-                            list.add(cx, workspace).unwrap();
-                            list.select(cx, id).unwrap();
-
-                            let data = WorkspaceListData::from_state(list);
-                            cx.background_spawn(async move {
-                                save_workspace_list(&path, &data).await.unwrap();
-                            })
-                            .detach();
-
-                            cx.notify();
-                        })
-                        .unwrap();
-                    })
-                    .detach();
+                WorkspaceList::create(list.clone(), window, cx);
             })
     }
 }
@@ -238,6 +274,10 @@ impl RenderOnce for WorkspaceIcon {
                 .justify_center()
                 .items_center()
                 .child(text.to_owned()),
+            WorkspaceIconData::Image(path) => v_flex()
+                .justify_center()
+                .items_center()
+                .child(img(path.clone()).size_6().rounded_sm()),
             _ => v_flex().child("😳"),
         }
     }
@@ -13,9 +13,12 @@ fn main() {
         loader
     };
 
+    // レンダラーで`window.memex`を使えるようにするため、`App`を渡す。
+    let mut app = memex_cef::create_subprocess_app();
+
     execute_process(
         Some(args.as_main_args()),
-        None::<&mut App>,
+        Some(&mut app),
         std::ptr::null_mut(),
     );
 }
@@ -0,0 +1,158 @@
+//! ウィンドウを持たない`WebView`で、ローカルのHTTPサーバーのページを操作する。ネットワークは使わない。
+//! `cargo run -p memex-cef --example headless`で実行し、失敗すればエラーで終了する。
+//!
+//! CEFはこの実行ファイル自体をサブプロセスとしても起動するので、`boot`が`None`を返したら何もせずに終わる。
+//! macOSではヘルパーのアプリのバンドルが必要なため、LinuxかWindowsで実行する。
+
+use std::{
+    io::{Read, Write},
+    net::TcpListener,
+    path::Path,
+    pin::pin,
+    thread,
+};
+
+use futures::future::{Either, select};
+use memex_cef::{
+    CefContext, DevToolsEvent, EventHandler, PdfPrintSettings, Profile, WebView, WebViewContext,
+    WindowSize,
+};
+
+const PAGE: &str = r#"<!doctype html>
+<html>
+  <head><meta charset="utf-8"><title>memex headless</title></head>
+  <body><h1 id="heading">Hello, memex</h1><p class="note">ヘッドレスでの確認</p></body>
+</html>"#;
+
+struct Handler;
+
+impl EventHandler for Handler {
+    fn on_title_change(&self, _title: String) {}
+}
+
+fn main() -> anyhow::Result<()> {
+    let root = std::env::temp_dir().join("memex-cef-headless");
+    std::fs::create_dir_all(&root)?;
+
+    let Some(mut event_loop) = memex_cef::boot(&root, &root, "memex-headless", "ja", true)? else {
+        return Ok(());
+    };
+    let _cef_context = CefContext::default();
+
+    let url = serve_page()?;
+
+    let result = async_io::block_on(async {
+        let checks = pin!(run_checks(&root, &url));
+        let event_loop = pin!(event_loop.start(|task| task()));
+
+        match select(checks, event_loop).await {
+            Either::Left((result, _)) => result,
+            Either::Right(_) => Err(anyhow::anyhow!("イベントループが先に終了しました。")),
+        }
+    });
+
+    memex_cef::teardown();
+    result
+}
+
+/// `PAGE`を返し続けるHTTPサーバーを`127.0.0.1`で起動し、そのURLを返す。
+fn serve_page() -> anyhow::Result<String> {
+    let listener = TcpListener::bind("127.0.0.1:0")?;
+    let address = listener.local_addr()?;
+
+    thread::spawn(move || {
+        for mut stream in listener.incoming().flatten() {
+            let mut request = [0; 4096];
+            _ = stream.read(&mut request);
+
+            let response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: text/html; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{PAGE}",
+                PAGE.len()
+            );
+            _ = stream.write_all(response.as_bytes());
+        }
+    });
+
+    Ok(format!("http://{address}/"))
+}
+
+async fn run_checks(root: &Path, url: &str) -> anyhow::Result<()> {
+    let mut profile = Profile::new(&root.join("profile"))?;
+    let webview = WebView::new_windowless(
+        &mut profile,
+        WebViewContext::new(Handler),
+        "about:blank",
+        WindowSize {
+            width: 1024,
+            height: 768,
+        },
+        &[],
+    )?;
+
+    let dev_tools = webview.dev_tools();
+    let events = dev_tools.events()?;
+    dev_tools.call("Page.enable", serde_json::json!({})).await?;
+    dev_tools
+        .call("Runtime.enable", serde_json::json!({}))
+        .await?;
+    dev_tools.navigate(url).await?;
+    wait_for_event(&events, "Page.loadEventFired").await?;
+    println!("読み込み：{url}");
+
+    let title = dev_tools.evaluate("document.title").await?;
+    anyhow::ensure!(title == "memex headless", "タイトルが違います：{title}");
+    let heading = dev_tools
+        .evaluate("document.querySelector('#heading').textContent")
+        .await?;
+    anyhow::ensure!(heading == "Hello, memex", "見出しが違います：{heading}");
+    let sum = dev_tools
+        .evaluate("new Promise((resolve) => setTimeout(() => resolve(1 + 2), 10))")
+        .await?;
+    anyhow::ensure!(sum == 3, "Promiseの解決を待っていません：{sum}");
+    anyhow::ensure!(
+        dev_tools.evaluate("undefinedFunction()").await.is_err(),
+        "例外がエラーになっていません。"
+    );
+    println!("式の評価：{title}、{heading}");
+
+    anyhow::ensure!(
+        dev_tools.query_selector("p.note").await?.is_some(),
+        "`p.note`が見つかりません。"
+    );
+    anyhow::ensure!(
+        dev_tools.query_selector("#missing").await?.is_none(),
+        "存在しない要素が見つかりました。"
+    );
+    println!("要素の検索：p.note");
+
+    dev_tools.evaluate("console.log('memex-event')").await?;
+    let event = wait_for_event(&events, "Runtime.consoleAPICalled").await?;
+    let message = &event.params["args"][0]["value"];
+    anyhow::ensure!(
+        message == "memex-event",
+        "コンソールの出力が違います：{message}"
+    );
+    println!("イベントの購読：{}", event.method);
+
+    let pdf_path = root.join("page.pdf");
+    webview
+        .print_to_pdf(&pdf_path, &PdfPrintSettings::default())
+        .await?;
+    let pdf = std::fs::read(&pdf_path)?;
+    anyhow::ensure!(pdf.starts_with(b"%PDF"), "PDFとして保存されていません。");
+    println!("PDFへの印刷：{}（{}バイト）", pdf_path.display(), pdf.len());
+
+    Ok(())
+}
+
+async fn wait_for_event(
+    events: &async_channel::Receiver<DevToolsEvent>,
+    method: &str,
+) -> anyhow::Result<DevToolsEvent> {
+    loop {
+        let event = events.recv().await?;
+        if event.method == method {
+            return Ok(event);
+        }
+    }
+}
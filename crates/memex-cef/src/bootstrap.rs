@@ -1,18 +1,27 @@
-use std::path::Path;
+use std::{
+    path::Path,
+    sync::atomic::{AtomicBool, Ordering},
+};
 
 use anyhow::Context;
 use cef::{args::Args, *};
 
 use crate::{cef_impl::AppService, event_loop::EventLoopHandle};
 
+static WINDOWLESS_RENDERING: AtomicBool = AtomicBool::new(false);
+
 /// CEFをセットアップする。最初に呼ばれるべき。
 /// ブラウザプロセスとしての起動であれば、ブラウザのイベントループの操作用ハンドルを返す。
 /// そうでなければ`None`を返す。`None`の場合、それ以上やることはないので終了すべき。
+///
+/// `windowless_rendering`は、`WebView::new_windowless`を使う場合だけ有効にする。
+/// 全てのブラウザの描画に関わる設定なので、ウィンドウに表示するだけなら無効にしておく。
 pub fn boot(
     root_cache_path: &Path,
     cache_path: &Path,
     product_name: &str,
     locale: &str,
+    windowless_rendering: bool,
 ) -> anyhow::Result<Option<EventLoopHandle>> {
     anyhow::ensure!(
         cache_path == root_cache_path || cache_path.parent() == Some(root_cache_path),
@@ -57,6 +66,7 @@ pub fn boot(
     // TODO: 設定をプロファイル毎に分けるべきなのかを確認する。
     let settings = Settings {
         external_message_pump: true.into(),
+        windowless_rendering_enabled: windowless_rendering.into(),
         root_cache_path: root_cache_path
             .to_str()
             .context("`root_cache_path`の文字列化に失敗")?
@@ -79,9 +89,22 @@ pub fn boot(
         1
     );
 
+    WINDOWLESS_RENDERING.store(windowless_rendering, Ordering::Relaxed);
+
     Ok(Some(event_loop))
 }
 
+/// `boot`でウィンドウを持たない描画を有効にしたか。
+pub fn is_windowless_rendering_enabled() -> bool {
+    WINDOWLESS_RENDERING.load(Ordering::Relaxed)
+}
+
+/// ヘルパーのプロセスで`execute_process`に渡す`App`を作る。
+/// レンダラーで`window.memex`を使えるようにするのに必要。
+pub fn create_subprocess_app() -> App {
+    AppService::create_for_subprocess()
+}
+
 /// CEFを終了する。
 pub fn teardown() {
     shutdown();
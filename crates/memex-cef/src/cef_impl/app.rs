@@ -1,22 +1,40 @@
 use cef::*;
 
 use crate::{
-    cef_impl::BrowserProcessHandlerService, event_loop::PumpTx, helper::define_cef_service,
+    cef_impl::{BrowserProcessHandlerService, RenderProcessHandlerService},
+    event_loop::PumpTx,
+    helper::define_cef_service,
+    scheme::INTERNAL_SCHEME,
 };
 
 define_cef_service! {
     #[derive_cef(cef::WrapApp)]
     pub struct AppService {
         sys: *mut cef::rc::RcImpl<sys::cef_app_t, Self>,
-        browser_process_handler: BrowserProcessHandler,
+        browser_process_handler: Option<BrowserProcessHandler>,
+        render_process_handler: RenderProcessHandler,
     }
 }
 
 impl AppService {
     pub fn create(pump_tx: PumpTx) -> App {
+        let (render_process_handler, _) = RenderProcessHandlerService::create();
+
+        App::new(Self {
+            sys: Default::default(),
+            browser_process_handler: Some(BrowserProcessHandlerService::create(pump_tx)),
+            render_process_handler,
+        })
+    }
+
+    /// ヘルパーのプロセス用。ブラウザプロセスのハンドラを持たない。
+    pub fn create_for_subprocess() -> App {
+        let (render_process_handler, _) = RenderProcessHandlerService::create();
+
         App::new(Self {
             sys: Default::default(),
-            browser_process_handler: BrowserProcessHandlerService::create(pump_tx),
+            browser_process_handler: None,
+            render_process_handler,
         })
     }
 }
@@ -39,7 +57,24 @@ impl ImplApp for AppService {
         }
     }
 
+    fn on_register_custom_schemes(&self, registrar: Option<&mut SchemeRegistrar>) {
+        let Some(registrar) = registrar else {
+            return;
+        };
+
+        // 全てのプロセスで同じ設定で登録しないと、レンダラーでオリジンが正しく扱われない。
+        let options = sys::cef_scheme_options_t::CEF_SCHEME_OPTION_STANDARD as i32
+            | sys::cef_scheme_options_t::CEF_SCHEME_OPTION_SECURE as i32
+            | sys::cef_scheme_options_t::CEF_SCHEME_OPTION_CORS_ENABLED as i32
+            | sys::cef_scheme_options_t::CEF_SCHEME_OPTION_FETCH_ENABLED as i32;
+        registrar.add_custom_scheme(Some(&INTERNAL_SCHEME.into()), options);
+    }
+
     fn browser_process_handler(&self) -> Option<cef::BrowserProcessHandler> {
-        Some(self.browser_process_handler.clone())
+        self.browser_process_handler.clone()
+    }
+
+    fn render_process_handler(&self) -> Option<cef::RenderProcessHandler> {
+        Some(self.render_process_handler.clone())
     }
 }
@@ -2,8 +2,13 @@ use cef::*;
 
 use crate::{
     WebViewContext,
-    cef_impl::{DisplayHandlerService, LifeSpanHandlerService},
+    cef_impl::{
+        DisplayHandlerService, FindHandlerService, JsDialogHandlerService, LifeSpanHandlerService,
+        LoadHandlerService, PermissionHandlerService, RequestHandlerService,
+    },
     helper::define_cef_service,
+    injected_script::INJECTED_SCRIPT_ERROR_MESSAGE,
+    ipc::{IPC_REQUEST_MESSAGE, IpcRequest},
 };
 
 define_cef_service! {
@@ -13,16 +18,29 @@ define_cef_service! {
         context: WebViewContext,
         life_span_handler: LifeSpanHandler,
         display_handler: DisplayHandler,
+        permission_handler: PermissionHandler,
+        js_dialog_handler: JsdialogHandler,
+        find_handler: FindHandler,
+        load_handler: LoadHandler,
+        request_handler: RequestHandler,
+        render_handler: Option<RenderHandler>,
     }
 }
 
 impl ClientService {
-    pub fn create(context: WebViewContext) -> Client {
+    /// `render_handler`は、ウィンドウを持たない`WebView`の場合にのみ渡す。
+    pub fn create(context: WebViewContext, render_handler: Option<RenderHandler>) -> Client {
         Client::new(Self {
             sys: Default::default(),
             context: context.clone(),
             life_span_handler: LifeSpanHandlerService::create(),
-            display_handler: DisplayHandlerService::create(context),
+            display_handler: DisplayHandlerService::create(context.clone()),
+            permission_handler: PermissionHandlerService::create(context.clone()),
+            js_dialog_handler: JsDialogHandlerService::create(context.clone()),
+            find_handler: FindHandlerService::create(context.clone()),
+            load_handler: LoadHandlerService::create(context.clone()),
+            request_handler: RequestHandlerService::create(context),
+            render_handler,
         })
     }
 }
@@ -35,4 +53,80 @@ impl ImplClient for ClientService {
     fn life_span_handler(&self) -> Option<LifeSpanHandler> {
         Some(self.life_span_handler.clone())
     }
+
+    fn display_handler(&self) -> Option<DisplayHandler> {
+        Some(self.display_handler.clone())
+    }
+
+    fn permission_handler(&self) -> Option<PermissionHandler> {
+        Some(self.permission_handler.clone())
+    }
+
+    fn jsdialog_handler(&self) -> Option<JsdialogHandler> {
+        Some(self.js_dialog_handler.clone())
+    }
+
+    fn find_handler(&self) -> Option<FindHandler> {
+        Some(self.find_handler.clone())
+    }
+
+    fn load_handler(&self) -> Option<LoadHandler> {
+        Some(self.load_handler.clone())
+    }
+
+    fn request_handler(&self) -> Option<RequestHandler> {
+        Some(self.request_handler.clone())
+    }
+
+    fn render_handler(&self) -> Option<RenderHandler> {
+        self.render_handler.clone()
+    }
+
+    fn on_process_message_received(
+        &self,
+        _browser: Option<&mut Browser>,
+        frame: Option<&mut Frame>,
+        _source_process: ProcessId,
+        message: Option<&mut ProcessMessage>,
+    ) -> ::std::os::raw::c_int {
+        let (Some(frame), Some(message)) = (frame, message) else {
+            return 0;
+        };
+        let Some(arguments) = message.argument_list() else {
+            return 0;
+        };
+
+        let name = CefString::from(&message.name()).to_string();
+
+        if name == INJECTED_SCRIPT_ERROR_MESSAGE {
+            let script_name = CefString::from(&arguments.string(0)).to_string();
+            let error = CefString::from(&arguments.string(1)).to_string();
+            self.context
+                .event_handler()
+                .on_injected_script_error(script_name, error);
+
+            return 1;
+        }
+        if name != IPC_REQUEST_MESSAGE {
+            return 0;
+        }
+
+        let request_id = arguments.int(0);
+        let channel = CefString::from(&arguments.string(1)).to_string();
+        let payload = CefString::from(&arguments.string(2)).to_string();
+
+        let request = match serde_json::from_str(&payload) {
+            Ok(payload) => IpcRequest::new(frame.clone(), request_id, channel, payload),
+            Err(error) => {
+                let request =
+                    IpcRequest::new(frame.clone(), request_id, channel, serde_json::Value::Null);
+                request.respond(Err(format!("要求のJSONの解析に失敗しました：{error}")));
+                return 1;
+            }
+        };
+
+        self.context.event_handler().on_ipc_request(request);
+
+        1
+    }
 }
@@ -0,0 +1,60 @@
+use std::rc::Weak;
+
+use cef::*;
+
+use crate::{define_cef_service, devtools::DevToolsState};
+
+define_cef_service! {
+    #[derive_cef(WrapDevToolsMessageObserver)]
+    pub struct DevToolsMessageObserverService {
+        sys: *mut cef::rc::RcImpl<sys::cef_dev_tools_message_observer_t, Self>,
+        // 登録を`DevToolsState`が保持するので、循環参照にならないよう弱参照で持つ。
+        state: Weak<DevToolsState>,
+    }
+}
+
+impl DevToolsMessageObserverService {
+    pub fn create(state: Weak<DevToolsState>) -> DevToolsMessageObserver {
+        DevToolsMessageObserver::new(Self {
+            sys: Default::default(),
+            state,
+        })
+    }
+}
+
+impl ImplDevToolsMessageObserver for DevToolsMessageObserverService {
+    fn get_raw(&self) -> *mut sys::_cef_dev_tools_message_observer_t {
+        self.sys.cast()
+    }
+
+    fn on_dev_tools_method_result(
+        &self,
+        _browser: Option<&mut Browser>,
+        message_id: ::std::os::raw::c_int,
+        success: ::std::os::raw::c_int,
+        result: Option<&[u8]>,
+    ) {
+        if let Some(state) = self.state.upgrade() {
+            state.resolve(message_id, success == 1, result.unwrap_or_default());
+        }
+    }
+
+    fn on_dev_tools_event(
+        &self,
+        _browser: Option<&mut Browser>,
+        method: Option<&CefString>,
+        params: Option<&[u8]>,
+    ) {
+        let (Some(state), Some(method)) = (self.state.upgrade(), method) else {
+            return;
+        };
+
+        state.dispatch_event(method.to_string(), params.unwrap_or_default());
+    }
+
+    fn on_dev_tools_agent_detached(&self, _browser: Option<&mut Browser>) {
+        if let Some(state) = self.state.upgrade() {
+            state.detach();
+        }
+    }
+}
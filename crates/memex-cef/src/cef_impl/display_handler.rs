@@ -31,4 +31,16 @@ impl ImplDisplayHandler for DisplayHandlerService {
                 .on_title_change(title.to_string());
         }
     }
+
+    fn on_favicon_urlchange(
+        &self,
+        _browser: Option<&mut Browser>,
+        icon_urls: Option<&mut CefStringList>,
+    ) {
+        let urls = icon_urls
+            .map(|icon_urls| icon_urls.clone().into_iter().collect())
+            .unwrap_or_default();
+
+        self.context.event_handler().on_favicon_urls_change(urls);
+    }
 }
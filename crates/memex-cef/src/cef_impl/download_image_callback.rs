@@ -0,0 +1,49 @@
+use cef::*;
+
+use crate::define_cef_service;
+
+/// ダウンロードした画像のPNGのバイト列。失敗した場合は`None`。
+pub type DownloadImageResultTx = async_channel::Sender<Option<Vec<u8>>>;
+
+define_cef_service! {
+    #[derive_cef(WrapDownloadImageCallback)]
+    pub struct DownloadImageCallbackService {
+        sys: *mut cef::rc::RcImpl<sys::cef_download_image_callback_t, Self>,
+        result_tx: DownloadImageResultTx,
+    }
+}
+
+impl DownloadImageCallbackService {
+    pub fn create(result_tx: DownloadImageResultTx) -> DownloadImageCallback {
+        DownloadImageCallback::new(Self {
+            sys: Default::default(),
+            result_tx,
+        })
+    }
+}
+
+impl ImplDownloadImageCallback for DownloadImageCallbackService {
+    fn get_raw(&self) -> *mut sys::_cef_download_image_callback_t {
+        self.sys.cast()
+    }
+
+    fn on_download_image_finished(
+        &self,
+        _image_url: Option<&CefString>,
+        _http_status_code: ::std::os::raw::c_int,
+        image: Option<&mut Image>,
+    ) {
+        let png = image.and_then(|image| {
+            let png = image.as_png(1.0, true as _, None, None)?;
+            let data = png.raw_data().cast::<u8>();
+            if data.is_null() {
+                return None;
+            }
+
+            // SAFETY: `raw_data`は`size`バイトの読み取り専用のバッファを指し、`png`が生きている間は有効。
+            Some(unsafe { std::slice::from_raw_parts(data, png.size()) }.to_vec())
+        });
+
+        _ = self.result_tx.try_send(png);
+    }
+}
@@ -0,0 +1,42 @@
+use cef::*;
+
+use crate::{FindResult, WebViewContext, define_cef_service};
+
+define_cef_service! {
+    #[derive_cef(WrapFindHandler)]
+    pub struct FindHandlerService {
+        sys: *mut cef::rc::RcImpl<sys::cef_find_handler_t, Self>,
+        context: WebViewContext,
+    }
+}
+
+impl FindHandlerService {
+    pub fn create(context: WebViewContext) -> FindHandler {
+        FindHandler::new(Self {
+            sys: Default::default(),
+            context,
+        })
+    }
+}
+
+impl ImplFindHandler for FindHandlerService {
+    fn get_raw(&self) -> *mut sys::_cef_find_handler_t {
+        self.sys.cast()
+    }
+
+    fn on_find_result(
+        &self,
+        _browser: Option<&mut Browser>,
+        _identifier: ::std::os::raw::c_int,
+        count: ::std::os::raw::c_int,
+        _selection_rect: Option<&Rect>,
+        active_match_ordinal: ::std::os::raw::c_int,
+        final_update: ::std::os::raw::c_int,
+    ) {
+        self.context.event_handler().on_find_result(FindResult {
+            count: count.max(0) as _,
+            active_index: active_match_ordinal.max(0) as _,
+            is_final: final_update == 1,
+        });
+    }
+}
@@ -0,0 +1,64 @@
+use cef::*;
+
+use crate::{define_cef_service, injected_script::INJECTED_SCRIPT_ERROR_MESSAGE};
+
+define_cef_service! {
+    #[derive_cef(WrapV8Handler)]
+    /// レンダラーで、注入したスクリプトの例外をブラウザプロセスへ報告する。
+    pub struct InjectedScriptV8HandlerService {
+        sys: *mut cef::rc::RcImpl<sys::cef_v8_handler_t, Self>,
+        frame: Frame,
+    }
+}
+
+impl InjectedScriptV8HandlerService {
+    pub fn create(frame: Frame) -> V8Handler {
+        V8Handler::new(Self {
+            sys: Default::default(),
+            frame,
+        })
+    }
+}
+
+/// 注入したスクリプトのエラーを、ブラウザプロセスへ送る。
+pub(crate) fn send_injected_script_error(frame: &Frame, name: &str, error: &str) {
+    let Some(mut message) = process_message_create(Some(&INJECTED_SCRIPT_ERROR_MESSAGE.into()))
+    else {
+        return;
+    };
+
+    if let Some(arguments) = message.argument_list() {
+        arguments.set_string(0, Some(&name.into()));
+        arguments.set_string(1, Some(&error.into()));
+    }
+
+    frame.send_process_message(
+        ProcessId::from(sys::cef_process_id_t::PID_BROWSER),
+        Some(&mut message),
+    );
+}
+
+impl ImplV8Handler for InjectedScriptV8HandlerService {
+    fn get_raw(&self) -> *mut sys::_cef_v8_handler_t {
+        self.sys.cast()
+    }
+
+    fn execute(
+        &self,
+        _name: Option<&CefString>,
+        _object: Option<&mut V8Value>,
+        arguments: Option<&[Option<V8Value>]>,
+        _retval: Option<&mut Option<V8Value>>,
+        _exception: Option<&mut CefString>,
+    ) -> ::std::os::raw::c_int {
+        if let Some([Some(name), Some(error)]) = arguments {
+            send_injected_script_error(
+                &self.frame,
+                &CefString::from(&name.string_value()).to_string(),
+                &CefString::from(&error.string_value()).to_string(),
+            );
+        }
+
+        1
+    }
+}
@@ -0,0 +1,104 @@
+use std::cell::Cell;
+
+use cef::*;
+
+use crate::{DownloadedResource, define_cef_service};
+
+define_cef_service! {
+    #[derive_cef(WrapResourceHandler)]
+    pub struct InternalResourceHandlerService {
+        sys: *mut cef::rc::RcImpl<sys::cef_resource_handler_t, Self>,
+        /// `None`であれば404を返す。
+        resource: Option<DownloadedResource>,
+        /// 読み出し済みのバイト数。
+        offset: Cell<usize>,
+    }
+}
+
+impl InternalResourceHandlerService {
+    pub fn create(resource: Option<DownloadedResource>) -> ResourceHandler {
+        ResourceHandler::new(Self {
+            sys: Default::default(),
+            resource,
+            offset: Cell::new(0),
+        })
+    }
+
+    fn data(&self) -> &[u8] {
+        self.resource
+            .as_ref()
+            .map(|resource| resource.data.as_slice())
+            .unwrap_or_default()
+    }
+}
+
+impl ImplResourceHandler for InternalResourceHandlerService {
+    fn get_raw(&self) -> *mut sys::_cef_resource_handler_t {
+        self.sys.cast()
+    }
+
+    fn open(
+        &self,
+        _request: Option<&mut Request>,
+        handle_request: Option<&mut ::std::os::raw::c_int>,
+        _callback: Option<&mut Callback>,
+    ) -> ::std::os::raw::c_int {
+        // リソースは用意できているので、その場で処理する。
+        if let Some(handle_request) = handle_request {
+            *handle_request = 1;
+        }
+        1
+    }
+
+    fn response_headers(
+        &self,
+        response: Option<&mut Response>,
+        response_length: Option<&mut i64>,
+        _redirect_url: Option<&mut CefString>,
+    ) {
+        if let Some(response) = response {
+            match &self.resource {
+                Some(resource) => {
+                    response.set_status(200);
+                    response.set_status_text(Some(&"OK".into()));
+                    response.set_mime_type(Some(&resource.mime_type.as_str().into()));
+                }
+                None => {
+                    response.set_status(404);
+                    response.set_status_text(Some(&"Not Found".into()));
+                    response.set_mime_type(Some(&"text/plain".into()));
+                }
+            }
+        }
+
+        if let Some(response_length) = response_length {
+            *response_length = self.data().len() as i64;
+        }
+    }
+
+    fn read(
+        &self,
+        data_out: *mut u8,
+        bytes_to_read: ::std::os::raw::c_int,
+        bytes_read: Option<&mut ::std::os::raw::c_int>,
+        _callback: Option<&mut ResourceReadCallback>,
+    ) -> ::std::os::raw::c_int {
+        let data = self.data();
+        let offset = self.offset.get();
+        let length = (data.len() - offset).min(bytes_to_read.max(0) as usize);
+
+        if length > 0 && !data_out.is_null() {
+            unsafe { std::ptr::copy_nonoverlapping(data[offset..].as_ptr(), data_out, length) };
+        }
+        self.offset.set(offset + length);
+
+        if let Some(bytes_read) = bytes_read {
+            *bytes_read = length as _;
+        }
+
+        // 読み終えたら`0`を返して、応答の終わりを伝える。
+        (length > 0) as _
+    }
+
+    fn cancel(&self) {}
+}
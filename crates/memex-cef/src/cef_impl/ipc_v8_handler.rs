@@ -0,0 +1,63 @@
+use cef::*;
+
+use crate::{define_cef_service, ipc::IPC_REQUEST_MESSAGE};
+
+define_cef_service! {
+    #[derive_cef(WrapV8Handler)]
+    /// レンダラーで`window.memex`から呼ばれ、要求をブラウザプロセスへ送る。
+    pub struct IpcV8HandlerService {
+        sys: *mut cef::rc::RcImpl<sys::cef_v8_handler_t, Self>,
+        frame: Frame,
+    }
+}
+
+impl IpcV8HandlerService {
+    pub fn create(frame: Frame) -> V8Handler {
+        V8Handler::new(Self {
+            sys: Default::default(),
+            frame,
+        })
+    }
+}
+
+impl ImplV8Handler for IpcV8HandlerService {
+    fn get_raw(&self) -> *mut sys::_cef_v8_handler_t {
+        self.sys.cast()
+    }
+
+    fn execute(
+        &self,
+        _name: Option<&CefString>,
+        _object: Option<&mut V8Value>,
+        arguments: Option<&[Option<V8Value>]>,
+        _retval: Option<&mut Option<V8Value>>,
+        exception: Option<&mut CefString>,
+    ) -> ::std::os::raw::c_int {
+        let Some([Some(request_id), Some(channel), Some(payload)]) = arguments else {
+            if let Some(exception) = exception {
+                *exception = "引数が不正です。".into();
+            }
+            return 1;
+        };
+
+        let Some(mut message) = process_message_create(Some(&IPC_REQUEST_MESSAGE.into())) else {
+            if let Some(exception) = exception {
+                *exception = "メッセージの作成に失敗しました。".into();
+            }
+            return 1;
+        };
+
+        if let Some(arguments) = message.argument_list() {
+            arguments.set_int(0, request_id.int_value());
+            arguments.set_string(1, Some(&CefString::from(&channel.string_value())));
+            arguments.set_string(2, Some(&CefString::from(&payload.string_value())));
+        }
+
+        self.frame.send_process_message(
+            ProcessId::from(sys::cef_process_id_t::PID_BROWSER),
+            Some(&mut message),
+        );
+
+        1
+    }
+}
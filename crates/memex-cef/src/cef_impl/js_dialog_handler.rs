@@ -0,0 +1,90 @@
+use cef::*;
+
+use crate::{
+    BeforeUnloadRequest, JsDialogKind, JsDialogRequest, WebViewContext, define_cef_service,
+};
+
+define_cef_service! {
+    #[derive_cef(WrapJsdialogHandler)]
+    pub struct JsDialogHandlerService {
+        sys: *mut cef::rc::RcImpl<sys::cef_jsdialog_handler_t, Self>,
+        context: WebViewContext,
+    }
+}
+
+impl JsDialogHandlerService {
+    pub fn create(context: WebViewContext) -> JsdialogHandler {
+        JsdialogHandler::new(Self {
+            sys: Default::default(),
+            context,
+        })
+    }
+}
+
+impl ImplJsdialogHandler for JsDialogHandlerService {
+    fn get_raw(&self) -> *mut sys::_cef_jsdialog_handler_t {
+        self.sys.cast()
+    }
+
+    fn on_jsdialog(
+        &self,
+        _browser: Option<&mut Browser>,
+        origin_url: Option<&CefString>,
+        dialog_type: JsdialogType,
+        message_text: Option<&CefString>,
+        default_prompt_text: Option<&CefString>,
+        callback: Option<&mut JsdialogCallback>,
+        _suppress_message: Option<&mut ::std::os::raw::c_int>,
+    ) -> ::std::os::raw::c_int {
+        let Some(callback) = callback else {
+            return false as _;
+        };
+
+        let kind = match dialog_type.as_ref() {
+            sys::cef_jsdialog_type_t::JSDIALOGTYPE_CONFIRM => JsDialogKind::Confirm,
+            sys::cef_jsdialog_type_t::JSDIALOGTYPE_PROMPT => JsDialogKind::Prompt,
+            _ => JsDialogKind::Alert,
+        };
+
+        let request = JsDialogRequest::new(
+            kind,
+            origin_url.map(CefString::to_string).unwrap_or_default(),
+            message_text.map(CefString::to_string).unwrap_or_default(),
+            default_prompt_text
+                .map(CefString::to_string)
+                .unwrap_or_default(),
+            callback.clone(),
+        );
+        self.context.event_handler().on_js_dialog(request);
+
+        // 返答は`JsDialogRequest`を通して非同期に行う。
+        true as _
+    }
+
+    fn on_before_unload_dialog(
+        &self,
+        _browser: Option<&mut Browser>,
+        message_text: Option<&CefString>,
+        is_reload: ::std::os::raw::c_int,
+        callback: Option<&mut JsdialogCallback>,
+    ) -> ::std::os::raw::c_int {
+        let Some(callback) = callback else {
+            return false as _;
+        };
+
+        let request = BeforeUnloadRequest::new(
+            message_text.map(CefString::to_string).unwrap_or_default(),
+            is_reload == 1,
+            callback.clone(),
+        );
+        self.context
+            .event_handler()
+            .on_before_unload_dialog(request);
+
+        true as _
+    }
+
+    fn on_reset_dialog_state(&self, _browser: Option<&mut Browser>) {
+        self.context.event_handler().on_js_dialog_reset();
+    }
+}
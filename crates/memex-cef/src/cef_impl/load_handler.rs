@@ -0,0 +1,60 @@
+use cef::*;
+
+use crate::{
+    WebViewContext, define_cef_service, highlight::apply_highlights,
+    style_sheet::apply_style_sheets,
+};
+
+define_cef_service! {
+    #[derive_cef(WrapLoadHandler)]
+    pub struct LoadHandlerService {
+        sys: *mut cef::rc::RcImpl<sys::cef_load_handler_t, Self>,
+        context: WebViewContext,
+    }
+}
+
+impl LoadHandlerService {
+    pub fn create(context: WebViewContext) -> LoadHandler {
+        LoadHandler::new(Self {
+            sys: Default::default(),
+            context,
+        })
+    }
+}
+
+impl ImplLoadHandler for LoadHandlerService {
+    fn get_raw(&self) -> *mut sys::_cef_load_handler_t {
+        self.sys.cast()
+    }
+
+    fn on_load_start(
+        &self,
+        browser: Option<&mut Browser>,
+        frame: Option<&mut Frame>,
+        _transition_type: TransitionType,
+    ) {
+        // `on_load_start`はナビゲーションが確定した後に呼ばれる。
+        let Some(frame) = frame.filter(|frame| frame.is_main() == 1) else {
+            return;
+        };
+        let url = CefStringUtf16::from(&frame.url()).to_string();
+        let event_handler = self.context.event_handler();
+
+        if let Some(host) = browser.and_then(|browser| browser.host()) {
+            let zoom_level = event_handler.zoom_level_for(&url).unwrap_or_default();
+            host.set_zoom_level(zoom_level);
+        }
+
+        let style_sheets = event_handler.style_sheets_for(&url);
+        if !style_sheets.is_empty() {
+            apply_style_sheets(frame, &style_sheets);
+        }
+
+        let highlights = event_handler.highlights_for(&url);
+        if !highlights.is_empty() {
+            apply_highlights(frame, &highlights);
+        }
+
+        event_handler.on_navigation_commit(url);
+    }
+}
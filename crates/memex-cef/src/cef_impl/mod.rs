@@ -1,15 +1,43 @@
 pub use app::*;
 pub use browser_process_handler::*;
 pub use client::*;
+pub use dev_tools_message_observer::*;
 pub use display_handler::*;
+pub use download_image_callback::*;
+pub use find_handler::*;
+pub use injected_script_v8_handler::*;
+pub use internal_resource_handler::*;
+pub use ipc_v8_handler::*;
+pub use js_dialog_handler::*;
 pub use life_span_handler::*;
+pub use load_handler::*;
+pub use pdf_print_callback::*;
+pub use permission_handler::*;
+pub use render_handler::*;
 pub use render_process_handler::*;
+pub use request_handler::*;
 pub use request_context_handler::*;
+pub use scheme_handler_factory::*;
+pub use url_request_client::*;
 
 mod app;
 mod browser_process_handler;
 mod client;
+mod dev_tools_message_observer;
 mod display_handler;
+mod download_image_callback;
+mod find_handler;
+mod injected_script_v8_handler;
+mod internal_resource_handler;
+mod ipc_v8_handler;
+mod js_dialog_handler;
 mod life_span_handler;
+mod load_handler;
+mod pdf_print_callback;
+mod permission_handler;
+mod render_handler;
 mod render_process_handler;
+mod request_handler;
 mod request_context_handler;
+mod scheme_handler_factory;
+mod url_request_client;
@@ -0,0 +1,32 @@
+use cef::*;
+
+use crate::define_cef_service;
+
+pub type PdfPrintResultTx = async_channel::Sender<bool>;
+
+define_cef_service! {
+    #[derive_cef(WrapPdfPrintCallback)]
+    pub struct PdfPrintCallbackService {
+        sys: *mut cef::rc::RcImpl<sys::cef_pdf_print_callback_t, Self>,
+        result_tx: PdfPrintResultTx,
+    }
+}
+
+impl PdfPrintCallbackService {
+    pub fn create(result_tx: PdfPrintResultTx) -> PdfPrintCallback {
+        PdfPrintCallback::new(Self {
+            sys: Default::default(),
+            result_tx,
+        })
+    }
+}
+
+impl ImplPdfPrintCallback for PdfPrintCallbackService {
+    fn get_raw(&self) -> *mut sys::_cef_pdf_print_callback_t {
+        self.sys.cast()
+    }
+
+    fn on_pdf_print_finished(&self, _path: Option<&CefString>, ok: ::std::os::raw::c_int) {
+        _ = self.result_tx.try_send(ok == 1);
+    }
+}
@@ -0,0 +1,83 @@
+use cef::*;
+
+use crate::{PermissionRequest, WebViewContext, define_cef_service};
+
+define_cef_service! {
+    #[derive_cef(WrapPermissionHandler)]
+    pub struct PermissionHandlerService {
+        sys: *mut cef::rc::RcImpl<sys::cef_permission_handler_t, Self>,
+        context: WebViewContext,
+    }
+}
+
+impl PermissionHandlerService {
+    pub fn create(context: WebViewContext) -> PermissionHandler {
+        PermissionHandler::new(Self {
+            sys: Default::default(),
+            context,
+        })
+    }
+}
+
+impl ImplPermissionHandler for PermissionHandlerService {
+    fn get_raw(&self) -> *mut sys::_cef_permission_handler_t {
+        self.sys.cast()
+    }
+
+    fn on_request_media_access_permission(
+        &self,
+        _browser: Option<&mut Browser>,
+        _frame: Option<&mut Frame>,
+        requesting_origin: Option<&CefString>,
+        requested_permissions: u32,
+        callback: Option<&mut MediaAccessCallback>,
+    ) -> ::std::os::raw::c_int {
+        let (Some(origin), Some(callback)) = (requesting_origin, callback) else {
+            return false as _;
+        };
+
+        let request = PermissionRequest::media_access(
+            origin.to_string(),
+            requested_permissions,
+            callback.clone(),
+        );
+        self.context.event_handler().on_permission_request(request);
+
+        // 返答は`PermissionRequest`を通して非同期に行う。
+        true as _
+    }
+
+    fn on_show_permission_prompt(
+        &self,
+        _browser: Option<&mut Browser>,
+        prompt_id: u64,
+        requesting_origin: Option<&CefString>,
+        requested_permissions: u32,
+        callback: Option<&mut PermissionPromptCallback>,
+    ) -> ::std::os::raw::c_int {
+        let (Some(origin), Some(callback)) = (requesting_origin, callback) else {
+            return false as _;
+        };
+
+        let request = PermissionRequest::prompt(
+            prompt_id,
+            origin.to_string(),
+            requested_permissions,
+            callback.clone(),
+        );
+        self.context.event_handler().on_permission_request(request);
+
+        true as _
+    }
+
+    fn on_dismiss_permission_prompt(
+        &self,
+        _browser: Option<&mut Browser>,
+        prompt_id: u64,
+        _result: PermissionRequestResult,
+    ) {
+        self.context
+            .event_handler()
+            .on_permission_prompt_dismiss(prompt_id);
+    }
+}
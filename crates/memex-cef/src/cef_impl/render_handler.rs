@@ -0,0 +1,54 @@
+use std::{cell::Cell, rc::Rc};
+
+use cef::*;
+
+use crate::{WindowSize, define_cef_service};
+
+define_cef_service! {
+    #[derive_cef(WrapRenderHandler)]
+    /// ウィンドウを持たない（オフスクリーンの）`WebView`のためのハンドラ。
+    pub struct RenderHandlerService {
+        sys: *mut cef::rc::RcImpl<sys::cef_render_handler_t, Self>,
+        view_size: Rc<Cell<WindowSize>>,
+    }
+}
+
+impl RenderHandlerService {
+    pub fn create(view_size: Rc<Cell<WindowSize>>) -> RenderHandler {
+        RenderHandler::new(Self {
+            sys: Default::default(),
+            view_size,
+        })
+    }
+}
+
+impl ImplRenderHandler for RenderHandlerService {
+    fn get_raw(&self) -> *mut sys::_cef_render_handler_t {
+        self.sys.cast()
+    }
+
+    fn view_rect(&self, _browser: Option<&mut Browser>, rect: Option<&mut Rect>) {
+        if let Some(rect) = rect {
+            let size = self.view_size.get();
+
+            *rect = Rect {
+                x: 0,
+                y: 0,
+                width: size.width.max(1),
+                height: size.height.max(1),
+            };
+        }
+    }
+
+    fn on_paint(
+        &self,
+        _browser: Option<&mut Browser>,
+        _type_: PaintElementType,
+        _dirty_rects_count: usize,
+        _dirty_rects: Option<&Rect>,
+        _buffer: *const u8,
+        _width: ::std::os::raw::c_int,
+        _height: ::std::os::raw::c_int,
+    ) {
+    }
+}
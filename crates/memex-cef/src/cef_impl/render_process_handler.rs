@@ -1,6 +1,15 @@
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
+
 use cef::*;
 
-use crate::helper::define_cef_service;
+use crate::{
+    InjectedScript,
+    cef_impl::{InjectedScriptV8HandlerService, IpcV8HandlerService, send_injected_script_error},
+    helper::define_cef_service,
+    injected_script::{INJECTED_SCRIPT_ERROR_FUNCTION, decode_injected_scripts},
+    ipc::{IPC_BRIDGE_SCRIPT, IPC_RESPONSE_MESSAGE, IPC_SEND_FUNCTION},
+    scheme::is_internal_url,
+};
 
 pub type BrowserCreateNotifier = async_channel::Sender<Browser>;
 pub type BrowserCreateListener = async_channel::Receiver<Browser>;
@@ -10,6 +19,8 @@ define_cef_service! {
     pub struct RenderProcessHandlerService {
         sys: *mut cef::rc::RcImpl<sys::cef_render_process_handler_t, Self>,
         notifier: BrowserCreateNotifier,
+        /// ブラウザのID毎の、読み込みの度に評価するスクリプト。
+        injected_scripts: Rc<RefCell<HashMap<i32, Vec<InjectedScript>>>>,
     }
 }
 
@@ -21,6 +32,7 @@ impl RenderProcessHandlerService {
             RenderProcessHandler::new(Self {
                 sys: Default::default(),
                 notifier,
+                injected_scripts: Rc::default(),
             }),
             listener,
         )
@@ -35,12 +47,130 @@ impl ImplRenderProcessHandler for RenderProcessHandlerService {
     fn on_browser_created(
         &self,
         browser: Option<&mut Browser>,
-        _extra_info: Option<&mut DictionaryValue>,
+        extra_info: Option<&mut DictionaryValue>,
     ) {
+        if let (Some(browser), Some(extra_info)) = (browser.as_deref(), extra_info) {
+            let scripts = decode_injected_scripts(extra_info);
+
+            if !scripts.is_empty() {
+                self.injected_scripts
+                    .borrow_mut()
+                    .insert(browser.identifier(), scripts);
+            }
+        }
+
         if let Some(browser) = browser.cloned() {
             let _ = self.notifier.send_blocking(browser);
         } else {
             log::warn!("Some browser is created but I received `None`.");
         }
     }
+
+    fn on_browser_destroyed(&self, browser: Option<&mut Browser>) {
+        if let Some(browser) = browser {
+            self.injected_scripts
+                .borrow_mut()
+                .remove(&browser.identifier());
+        }
+    }
+
+    fn on_context_created(
+        &self,
+        browser: Option<&mut Browser>,
+        frame: Option<&mut Frame>,
+        context: Option<&mut V8Context>,
+    ) {
+        let (Some(frame), Some(context)) = (frame, context) else {
+            return;
+        };
+        let Some(global) = context.global() else {
+            return;
+        };
+
+        let injected_scripts = self.injected_scripts.borrow();
+        let scripts = browser.and_then(|browser| injected_scripts.get(&browser.identifier()));
+
+        // ページのグローバルを汚さないよう、`window.memex`は内部ページとユーザースクリプトのあるページにだけ置く。
+        let url = CefStringUtf16::from(&frame.url()).to_string();
+        if is_internal_url(&url) || scripts.is_some() {
+            // ネイティブ関数を一時的に置き、`window.memex`の定義に閉じ込めてから取り除く。
+            let mut handler = IpcV8HandlerService::create(frame.clone());
+            let mut function =
+                v8_value_create_function(Some(&IPC_SEND_FUNCTION.into()), Some(&mut handler));
+            global.set_value_bykey(
+                Some(&IPC_SEND_FUNCTION.into()),
+                function.as_mut(),
+                V8Propertyattribute::default(),
+            );
+
+            if context.eval(Some(&IPC_BRIDGE_SCRIPT.into()), None, 0, None, None) != 1 {
+                log::warn!("`window.memex`の定義に失敗しました。");
+            }
+        }
+
+        let Some(scripts) = scripts else {
+            return;
+        };
+
+        // 後から起きた例外を報告できるよう、スクリプトにはネイティブ関数を渡しておく。
+        let mut handler = InjectedScriptV8HandlerService::create(frame.clone());
+        let mut function = v8_value_create_function(
+            Some(&INJECTED_SCRIPT_ERROR_FUNCTION.into()),
+            Some(&mut handler),
+        );
+        global.set_value_bykey(
+            Some(&INJECTED_SCRIPT_ERROR_FUNCTION.into()),
+            function.as_mut(),
+            V8Propertyattribute::default(),
+        );
+
+        for script in scripts {
+            let mut exception = None;
+
+            if context.eval(
+                Some(&script.source.as_str().into()),
+                None,
+                0,
+                None,
+                Some(&mut exception),
+            ) != 1
+            {
+                let error = exception
+                    .map(|exception| CefString::from(&exception.message()).to_string())
+                    .unwrap_or_default();
+                send_injected_script_error(frame, &script.name, &error);
+            }
+        }
+
+        global.delete_value_bykey(Some(&INJECTED_SCRIPT_ERROR_FUNCTION.into()));
+    }
+
+    fn on_process_message_received(
+        &self,
+        _browser: Option<&mut Browser>,
+        frame: Option<&mut Frame>,
+        _source_process: ProcessId,
+        message: Option<&mut ProcessMessage>,
+    ) -> ::std::os::raw::c_int {
+        let (Some(frame), Some(message)) = (frame, message) else {
+            return 0;
+        };
+        if CefString::from(&message.name()).to_string() != IPC_RESPONSE_MESSAGE {
+            return 0;
+        }
+        let Some(arguments) = message.argument_list() else {
+            return 0;
+        };
+
+        let request_id = arguments.int(0);
+        let ok = arguments.bool(1) == 1;
+        let payload = CefString::from(&arguments.string(2)).to_string();
+        // 文字列のJSONは、そのままJavaScriptの文字列リテラルとして使える。
+        let payload = serde_json::Value::String(payload).to_string();
+
+        let code = format!("globalThis.__memexIpcResolve?.({request_id}, {ok}, {payload});");
+        frame.execute_java_script(Some(&code.as_str().into()), None, 0);
+
+        1
+    }
 }
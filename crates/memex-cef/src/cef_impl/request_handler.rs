@@ -0,0 +1,46 @@
+use cef::*;
+
+use crate::{RenderProcessTermination, WebViewContext, define_cef_service};
+
+define_cef_service! {
+    #[derive_cef(WrapRequestHandler)]
+    pub struct RequestHandlerService {
+        sys: *mut cef::rc::RcImpl<sys::cef_request_handler_t, Self>,
+        context: WebViewContext,
+    }
+}
+
+impl RequestHandlerService {
+    pub fn create(context: WebViewContext) -> RequestHandler {
+        RequestHandler::new(Self {
+            sys: Default::default(),
+            context,
+        })
+    }
+}
+
+impl ImplRequestHandler for RequestHandlerService {
+    fn get_raw(&self) -> *mut sys::_cef_request_handler_t {
+        self.sys.cast()
+    }
+
+    // CEFが通知するのはレンダラープロセスの終了だけで、GPUプロセスのクラッシュを知る方法はない。
+    // GPUプロセスはChromiumが自動で起動し直し、繰り返しクラッシュすればソフトウェアでの描画に切り替える。
+    fn on_render_process_terminated(
+        &self,
+        _browser: Option<&mut Browser>,
+        status: TerminationStatus,
+        error_code: ::std::os::raw::c_int,
+        error_string: Option<&CefString>,
+    ) {
+        let termination = RenderProcessTermination {
+            kind: status.into(),
+            error_code,
+            error_message: error_string.map(ToString::to_string).unwrap_or_default(),
+        };
+
+        self.context
+            .event_handler()
+            .on_render_process_terminated(termination);
+    }
+}
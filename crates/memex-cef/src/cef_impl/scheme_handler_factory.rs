@@ -0,0 +1,45 @@
+use cef::*;
+
+use crate::{
+    cef_impl::InternalResourceHandlerService, define_cef_service, scheme::InternalSchemeHandler,
+};
+
+define_cef_service! {
+    #[derive_cef(WrapSchemeHandlerFactory)]
+    pub struct SchemeHandlerFactoryService {
+        sys: *mut cef::rc::RcImpl<sys::cef_scheme_handler_factory_t, Self>,
+        handler: InternalSchemeHandler,
+    }
+}
+
+impl SchemeHandlerFactoryService {
+    pub fn create(handler: InternalSchemeHandler) -> SchemeHandlerFactory {
+        SchemeHandlerFactory::new(Self {
+            sys: Default::default(),
+            handler,
+        })
+    }
+}
+
+impl ImplSchemeHandlerFactory for SchemeHandlerFactoryService {
+    fn get_raw(&self) -> *mut sys::_cef_scheme_handler_factory_t {
+        self.sys.cast()
+    }
+
+    fn create(
+        &self,
+        _browser: Option<&mut Browser>,
+        _frame: Option<&mut Frame>,
+        _scheme_name: Option<&CefString>,
+        request: Option<&mut Request>,
+    ) -> Option<ResourceHandler> {
+        let url = CefStringUtf16::from(&request?.url()).to_string();
+        let resource = (self.handler)(&url);
+        if resource.is_none() {
+            log::warn!("内部ページが見つかりません：{url}");
+        }
+
+        // 見つからない場合も、CEFの既定の処理に任せずに404を返す。
+        Some(InternalResourceHandlerService::create(resource))
+    }
+}
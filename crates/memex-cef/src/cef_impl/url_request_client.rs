@@ -0,0 +1,91 @@
+use std::{
+    cell::{Cell, RefCell},
+    rc::Rc,
+};
+
+use cef::*;
+
+use crate::{DownloadedResource, define_cef_service};
+
+pub type UrlRequestResultTx = async_channel::Sender<anyhow::Result<DownloadedResource>>;
+
+define_cef_service! {
+    #[derive_cef(WrapUrlrequestClient)]
+    pub struct UrlRequestClientService {
+        sys: *mut cef::rc::RcImpl<sys::cef_urlrequest_client_t, Self>,
+        data: Rc<RefCell<Vec<u8>>>,
+        max_size: usize,
+        /// レスポンスが`max_size`を超えて、取り消したか。
+        is_too_large: Rc<Cell<bool>>,
+        result_tx: UrlRequestResultTx,
+    }
+}
+
+impl UrlRequestClientService {
+    pub fn create(result_tx: UrlRequestResultTx, max_size: usize) -> UrlrequestClient {
+        UrlrequestClient::new(Self {
+            sys: Default::default(),
+            data: Rc::default(),
+            max_size,
+            is_too_large: Rc::default(),
+            result_tx,
+        })
+    }
+}
+
+impl ImplUrlrequestClient for UrlRequestClientService {
+    fn get_raw(&self) -> *mut sys::_cef_urlrequest_client_t {
+        self.sys.cast()
+    }
+
+    fn on_download_data(&self, request: Option<&mut Urlrequest>, data: Option<&[u8]>) {
+        let Some(data) = data else {
+            return;
+        };
+        if self.is_too_large.get() {
+            return;
+        }
+
+        let mut buffer = self.data.borrow_mut();
+        if buffer.len() + data.len() > self.max_size {
+            // 残りを受け取らないよう取り消す。結果は`on_request_complete`で送る。
+            self.is_too_large.set(true);
+            buffer.clear();
+            drop(buffer);
+
+            if let Some(request) = request {
+                request.cancel();
+            }
+            return;
+        }
+
+        buffer.extend_from_slice(data);
+    }
+
+    fn on_request_complete(&self, request: Option<&mut Urlrequest>) {
+        let response = request.and_then(|request| request.response());
+
+        let result = match response {
+            _ if self.is_too_large.get() => Err(anyhow::anyhow!(
+                "レスポンスが{}バイトを超えました。",
+                self.max_size
+            )),
+            Some(response) => {
+                let status = response.status();
+                let mime_type = CefStringUtf16::from(&response.mime_type()).to_string();
+
+                if (200..300).contains(&status) {
+                    Ok(DownloadedResource {
+                        mime_type,
+                        data: self.data.take(),
+                    })
+                } else {
+                    Err(anyhow::anyhow!("HTTPのステータスが{status}でした。"))
+                }
+            }
+            None => Err(anyhow::anyhow!("レスポンスを受け取れませんでした。")),
+        };
+
+        _ = self.result_tx.try_send(result);
+    }
+}
@@ -0,0 +1,40 @@
+use cef::{TerminationStatus, sys};
+
+/// レンダラープロセスが終了した理由。
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TerminationKind {
+    /// 0以外の終了コードで終了した。
+    Abnormal,
+    /// シグナルなどで強制終了された。
+    Killed,
+    /// クラッシュした。
+    Crashed,
+    /// メモリが足りなくなった。
+    OutOfMemory,
+    /// プロセスの起動に失敗した。
+    LaunchFailed,
+    /// コードの整合性の確認に失敗した。
+    IntegrityFailure,
+}
+
+impl From<TerminationStatus> for TerminationKind {
+    fn from(status: TerminationStatus) -> Self {
+        match status.as_ref() {
+            sys::cef_termination_status_t::TS_PROCESS_WAS_KILLED => Self::Killed,
+            sys::cef_termination_status_t::TS_PROCESS_CRASHED => Self::Crashed,
+            sys::cef_termination_status_t::TS_PROCESS_OOM => Self::OutOfMemory,
+            sys::cef_termination_status_t::TS_LAUNCH_FAILED => Self::LaunchFailed,
+            sys::cef_termination_status_t::TS_INTEGRITY_FAILURE => Self::IntegrityFailure,
+            _ => Self::Abnormal,
+        }
+    }
+}
+
+/// レンダラープロセスの終了の情報。
+#[derive(Clone, Debug)]
+pub struct RenderProcessTermination {
+    pub kind: TerminationKind,
+    /// プラットフォームの終了コード。
+    pub error_code: i32,
+    pub error_message: String,
+}
@@ -0,0 +1,228 @@
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashMap,
+    rc::Rc,
+    time::Duration,
+};
+
+use anyhow::Context as _;
+use cef::{Browser, BrowserHost, ImplBrowser, ImplBrowserHost, Registration};
+use serde_json::Value;
+
+use crate::{cef_impl::DevToolsMessageObserverService, timeout::timeout};
+
+type MethodResultTx = async_channel::Sender<anyhow::Result<Value>>;
+
+/// DevToolsのメソッドの結果を待つ時間の上限。
+pub const DEV_TOOLS_CALL_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// DevToolsプロトコルのイベント。
+#[derive(Clone, Debug)]
+pub struct DevToolsEvent {
+    /// `Page.loadEventFired`のような、ドメインを含むイベント名。
+    pub method: String,
+    pub params: Value,
+}
+
+/// `WebView`毎の、DevToolsプロトコルの呼び出しの状態。
+#[derive(Default)]
+pub(crate) struct DevToolsState {
+    next_message_id: Cell<i32>,
+    pending: RefCell<HashMap<i32, MethodResultTx>>,
+    subscribers: RefCell<Vec<async_channel::Sender<DevToolsEvent>>>,
+    /// 監視の登録。ドロップすると監視が解除される。
+    registration: RefCell<Option<Registration>>,
+}
+
+impl DevToolsState {
+    pub(crate) fn resolve(&self, message_id: i32, success: bool, result: &[u8]) {
+        let Some(result_tx) = self.pending.borrow_mut().remove(&message_id) else {
+            return;
+        };
+
+        let result = serde_json::from_slice::<Value>(result)
+            .context("DevToolsのメソッドの結果の解析に失敗しました。")
+            .and_then(|value| {
+                if success {
+                    Ok(value)
+                } else {
+                    let message = value["message"].as_str().unwrap_or_default();
+                    Err(anyhow::anyhow!(
+                        "DevToolsのメソッドが失敗しました：{message}"
+                    ))
+                }
+            });
+
+        _ = result_tx.try_send(result);
+    }
+
+    pub(crate) fn dispatch_event(&self, method: String, params: &[u8]) {
+        let params = match serde_json::from_slice::<Value>(params) {
+            Ok(params) => params,
+            Err(error) => {
+                log::warn!("DevToolsのイベントの解析に失敗しました：{method}：{error}");
+                return;
+            }
+        };
+
+        let event = DevToolsEvent { method, params };
+        // 受信側がドロップされた購読はここで取り除く。
+        self.subscribers
+            .borrow_mut()
+            .retain(|subscriber| subscriber.try_send(event.clone()).is_ok());
+    }
+
+    /// 監視を解除し、結果を待っている呼び出しを全てエラーにする。
+    /// ブラウザが閉じられたり、DevToolsのエージェントが切り離されたりすると、結果は届かなくなる。
+    pub(crate) fn detach(&self) {
+        self.registration.borrow_mut().take();
+
+        let pending = std::mem::take(&mut *self.pending.borrow_mut());
+        for result_tx in pending.into_values() {
+            _ = result_tx.try_send(Err(anyhow::anyhow!(
+                "DevToolsのメソッドの結果を受け取る前に、監視が解除されました。"
+            )));
+        }
+    }
+
+    fn observe(self: &Rc<Self>, host: &BrowserHost) -> anyhow::Result<()> {
+        if self.registration.borrow().is_some() {
+            return Ok(());
+        }
+
+        let mut observer = DevToolsMessageObserverService::create(Rc::downgrade(self));
+        let registration = host
+            .add_dev_tools_message_observer(Some(&mut observer))
+            .context("DevToolsの監視の登録に失敗しました。")?;
+        *self.registration.borrow_mut() = Some(registration);
+
+        Ok(())
+    }
+}
+
+/// `WebView`をDevToolsプロトコルで操作するためのハンドル。
+/// [`WebView::dev_tools`](crate::WebView::dev_tools)で取得する。
+#[derive(Clone)]
+pub struct DevTools {
+    browser: Browser,
+    state: Rc<DevToolsState>,
+}
+
+impl DevTools {
+    pub(crate) fn new(browser: Browser, state: Rc<DevToolsState>) -> Self {
+        Self { browser, state }
+    }
+
+    /// メソッドを呼び出し、結果を待つ。`params`と結果はプロトコルのJSONそのまま。
+    /// [`DEV_TOOLS_CALL_TIMEOUT`]を過ぎても結果が届かなければ、エラーを返す。
+    pub async fn call(&self, method: &str, params: Value) -> anyhow::Result<Value> {
+        let host = self.host()?;
+        self.state.observe(&host)?;
+
+        let message_id = self.state.next_message_id.get() + 1;
+        self.state.next_message_id.set(message_id);
+
+        let (result_tx, result_rx) = async_channel::bounded(1);
+        self.state
+            .pending
+            .borrow_mut()
+            .insert(message_id, result_tx);
+
+        let message = serde_json::json!({
+            "id": message_id,
+            "method": method,
+            "params": params,
+        })
+        .to_string();
+
+        if host.send_dev_tools_message(Some(message.as_bytes())) != 1 {
+            self.state.pending.borrow_mut().remove(&message_id);
+            anyhow::bail!("DevToolsのメッセージの送信に失敗しました：{method}");
+        }
+
+        let Some(result) = timeout(DEV_TOOLS_CALL_TIMEOUT, result_rx.recv()).await else {
+            self.state.pending.borrow_mut().remove(&message_id);
+            anyhow::bail!("DevToolsのメソッドの結果が時間内に届きませんでした：{method}");
+        };
+
+        result
+            .with_context(|| format!("DevToolsのメソッドの結果を受け取れませんでした：{method}"))?
+    }
+
+    /// イベントを購読する。受信側をドロップすると購読は解除される。
+    /// 多くのドメインは、`Page.enable`などで有効にしないとイベントを送らない。
+    pub fn events(&self) -> anyhow::Result<async_channel::Receiver<DevToolsEvent>> {
+        self.state.observe(&self.host()?)?;
+
+        let (event_tx, event_rx) = async_channel::unbounded();
+        self.state.subscribers.borrow_mut().push(event_tx);
+
+        Ok(event_rx)
+    }
+
+    /// `Page.navigate`を呼び出し、フレームのIDを返す。
+    pub async fn navigate(&self, url: &str) -> anyhow::Result<String> {
+        let result = self
+            .call("Page.navigate", serde_json::json!({ "url": url }))
+            .await?;
+
+        if let Some(error_text) = result["errorText"].as_str() {
+            anyhow::bail!("ページの移動に失敗しました：{error_text}");
+        }
+
+        result["frameId"]
+            .as_str()
+            .map(ToOwned::to_owned)
+            .context("`Page.navigate`の結果にフレームのIDがありません。")
+    }
+
+    /// `Runtime.evaluate`で式を評価し、その値を返す。Promiseであれば解決を待つ。
+    pub async fn evaluate(&self, expression: &str) -> anyhow::Result<Value> {
+        let mut result = self
+            .call(
+                "Runtime.evaluate",
+                serde_json::json!({
+                    "expression": expression,
+                    "returnByValue": true,
+                    "awaitPromise": true,
+                }),
+            )
+            .await?;
+
+        if let Some(exception) = result.get("exceptionDetails") {
+            let description = exception["exception"]["description"]
+                .as_str()
+                .or(exception["text"].as_str())
+                .unwrap_or_default();
+            anyhow::bail!("式の評価中に例外が発生しました：{description}");
+        }
+
+        Ok(result["result"]["value"].take())
+    }
+
+    /// `DOM.querySelector`でドキュメントから要素を探し、ノードのIDを返す。
+    pub async fn query_selector(&self, selector: &str) -> anyhow::Result<Option<i64>> {
+        let document = self
+            .call("DOM.getDocument", serde_json::json!({ "depth": 0 }))
+            .await?;
+        let root_node_id = document["root"]["nodeId"]
+            .as_i64()
+            .context("`DOM.getDocument`の結果にノードのIDがありません。")?;
+
+        let result = self
+            .call(
+                "DOM.querySelector",
+                serde_json::json!({ "nodeId": root_node_id, "selector": selector }),
+            )
+            .await?;
+
+        // 見つからなかった場合は`0`が返される。
+        Ok(result["nodeId"].as_i64().filter(|node_id| *node_id != 0))
+    }
+
+    fn host(&self) -> anyhow::Result<BrowserHost> {
+        self.browser
+            .host()
+            .context("The browser host is not available yet.")
+    }
+}
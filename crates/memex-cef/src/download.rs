@@ -0,0 +1,49 @@
+use std::time::Duration;
+
+use anyhow::Context as _;
+use cef::{ImplRequest, ImplUrlrequest};
+
+use crate::{Profile, cef_impl::UrlRequestClientService, timeout::timeout};
+
+/// ダウンロードを待つ時間の上限。
+pub const DOWNLOAD_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// プロファイルのリクエストコンテキストでダウンロードしたリソース。
+#[derive(Clone, Debug)]
+pub struct DownloadedResource {
+    pub mime_type: String,
+    pub data: Vec<u8>,
+}
+
+impl Profile {
+    /// プロファイルのキャッシュやプロキシの設定を使って、`url`をGETでダウンロードする。
+    /// Cookieなどの認証情報は送らない。UIスレッドで呼ぶこと。
+    /// [`DOWNLOAD_TIMEOUT`]を過ぎるか、レスポンスが`max_size`バイトを超えれば、取り消してエラーを返す。
+    pub async fn download(&self, url: &str, max_size: usize) -> anyhow::Result<DownloadedResource> {
+        let mut request = cef::request_create().context("リクエストの作成に失敗")?;
+        request.set_url(Some(&url.into()));
+        request.set_method(Some(&"GET".into()));
+
+        let (result_tx, result_rx) = async_channel::bounded(1);
+        let mut client = UrlRequestClientService::create(result_tx, max_size);
+        let mut request_context = self.request_context.clone();
+
+        // 完了するまで`url_request`を持っておかないと、リクエストが取り消されることがある。
+        let url_request = cef::urlrequest_create(
+            Some(&mut request),
+            Some(&mut client),
+            Some(&mut request_context),
+        )
+        .context("URLリクエストの作成に失敗")?;
+
+        let Some(result) = timeout(DOWNLOAD_TIMEOUT, result_rx.recv()).await else {
+            url_request.cancel();
+            anyhow::bail!("ダウンロードが時間内に終わりませんでした：{url}");
+        };
+        drop(url_request);
+
+        result
+            .context("ダウンロードの完了を受け取れませんでした。")?
+            .with_context(|| format!("ダウンロードに失敗しました：{url}"))
+    }
+}
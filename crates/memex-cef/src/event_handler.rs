@@ -1,3 +1,84 @@
+use crate::{
+    BeforeUnloadRequest, FindResult, Highlight, IpcRequest, JsDialogRequest, PermissionRequest,
+    RenderProcessTermination,
+};
+
 pub trait EventHandler {
     fn on_title_change(&self, title: String);
+
+    /// サイトから権限を要求された。
+    /// 返答しないまま`request`をドロップすると、要求は却下される。
+    fn on_permission_request(&self, request: PermissionRequest) {
+        let _ = request;
+    }
+
+    /// 表示中の権限のプロンプトが、返答を待たずに閉じられた。
+    fn on_permission_prompt_dismiss(&self, prompt_id: u64) {
+        let _ = prompt_id;
+    }
+
+    /// `alert`・`confirm`・`prompt`が呼ばれた。
+    /// 返答しないまま`request`をドロップすると、キャンセル扱いとなる。
+    fn on_js_dialog(&self, request: JsDialogRequest) {
+        let _ = request;
+    }
+
+    /// `beforeunload`によって、ページを離れるかの確認を求められた。
+    fn on_before_unload_dialog(&self, request: BeforeUnloadRequest) {
+        let _ = request;
+    }
+
+    /// ページの移動などにより、表示待ちのダイアログが全て無効になった。
+    fn on_js_dialog_reset(&self) {}
+
+    /// ページ内検索の結果が更新された。
+    fn on_find_result(&self, result: FindResult) {
+        let _ = result;
+    }
+
+    /// メインフレームのナビゲーションが確定した。
+    fn on_navigation_commit(&self, url: String) {
+        let _ = url;
+    }
+
+    /// ページのファビコンのURLが変わった。`<link rel="icon">`で指定された順に並ぶ。
+    fn on_favicon_urls_change(&self, urls: Vec<String>) {
+        let _ = urls;
+    }
+
+    /// ナビゲーションが確定したページに適用するズームレベルを返す。
+    /// `None`なら既定の倍率に戻す。
+    fn zoom_level_for(&self, url: &str) -> Option<f64> {
+        let _ = url;
+        None
+    }
+
+    /// ナビゲーションが確定したページに適用するスタイルシートを返す。
+    fn style_sheets_for(&self, url: &str) -> Vec<String> {
+        let _ = url;
+        Vec::new()
+    }
+
+    /// ナビゲーションが確定したページに表示するハイライトを返す。
+    fn highlights_for(&self, url: &str) -> Vec<Highlight> {
+        let _ = url;
+        Vec::new()
+    }
+
+    /// ページの`window.memex.invoke`による要求が届いた。
+    /// 返答しないまま`request`をドロップすると、ページ側ではエラーとなる。
+    fn on_ipc_request(&self, request: IpcRequest) {
+        let _ = request;
+    }
+
+    /// レンダラープロセスが終了し、ページが表示できなくなった。
+    /// `WebView::reload`で読み込み直すと、新しいプロセスで表示される。
+    fn on_render_process_terminated(&self, termination: RenderProcessTermination) {
+        log::warn!("レンダラープロセスが終了しました：{termination:?}");
+    }
+
+    /// `WebView`の作成時に渡したスクリプトで、エラーが発生した。
+    fn on_injected_script_error(&self, name: String, error: String) {
+        log::warn!("スクリプト{name}でエラーが発生しました：{error}");
+    }
 }
@@ -0,0 +1,10 @@
+/// ページ内検索の結果。
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FindResult {
+    /// 一致した箇所の数。
+    pub count: u32,
+    /// 選択中の一致箇所の、1から始まる番号。一致がなければ0。
+    pub active_index: u32,
+    /// 検索が終わり、これ以上結果が更新されないかどうか。
+    pub is_final: bool,
+}
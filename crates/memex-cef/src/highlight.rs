@@ -0,0 +1,44 @@
+use cef::{Frame, ImplFrame};
+use serde::{Deserialize, Serialize};
+
+const APPLY_HIGHLIGHTS_SCRIPT: &str = include_str!("scripts/apply_highlights.js");
+const SELECTION_ANCHOR_SCRIPT: &str = include_str!("scripts/selection_anchor.js");
+
+/// ページ内の文章の位置。
+/// DOMが多少変わっても見つけ直せるよう、位置だけでなく、その文章と前後の文字列も持つ。
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TextAnchor {
+    pub quote: String,
+    pub prefix: String,
+    pub suffix: String,
+    /// `body`のテキストを連結した文字列での、開始位置（UTF-16）。
+    pub start: u32,
+    /// 同じく終了位置。
+    pub end: u32,
+}
+
+/// ページに表示するハイライト。
+#[derive(Clone, Debug, Serialize)]
+pub struct Highlight {
+    /// ページ内でハイライトを識別するID。
+    pub id: String,
+    pub anchor: TextAnchor,
+    /// ハイライトにマウスを重ねた時に表示する文字列。
+    pub note: String,
+}
+
+/// フレームに表示しているハイライトを、`highlights`で置き換える。
+/// 文章が見つからなかったハイライトは表示しない。
+pub(crate) fn apply_highlights(frame: &Frame, highlights: &[Highlight]) {
+    let Ok(highlights) = serde_json::to_string(highlights) else {
+        return;
+    };
+    let code = format!("{APPLY_HIGHLIGHTS_SCRIPT}({highlights});");
+
+    frame.execute_java_script(Some(&code.as_str().into()), None, 0);
+}
+
+/// 選択中の文章の位置を求める式。選択されていなければ`null`となる。
+pub(crate) fn selection_anchor_expression() -> String {
+    format!("{SELECTION_ANCHOR_SCRIPT}()")
+}
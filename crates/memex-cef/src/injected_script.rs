@@ -0,0 +1,61 @@
+use cef::{
+    CefString, DictionaryValue, ImplDictionaryValue, ImplListValue, dictionary_value_create,
+    list_value_create,
+};
+
+/// レンダラーからブラウザへ送る、スクリプトのエラーのメッセージ名。
+/// 引数は`[スクリプトの名前, エラーの内容]`。
+pub(crate) const INJECTED_SCRIPT_ERROR_MESSAGE: &str = "memex.injected_script.error";
+/// スクリプトが例外を報告するための、ネイティブ関数の名前。`(name, error)`を受け取る。
+/// 全てのスクリプトを評価した後に取り除かれるので、評価時に参照を取っておく必要がある。
+pub const INJECTED_SCRIPT_ERROR_FUNCTION: &str = "__memexInjectedScriptError";
+
+const EXTRA_INFO_KEY: &str = "injected_scripts";
+const NAME_KEY: &str = "name";
+const SOURCE_KEY: &str = "source";
+
+/// ページの読み込み毎に、ページのスクリプトより先にレンダラーで評価するスクリプト。
+/// `WebView`の作成時に渡す。
+#[derive(Clone, Debug)]
+pub struct InjectedScript {
+    /// エラーの報告に使う名前。
+    pub name: String,
+    pub source: String,
+}
+
+/// `InjectedScript`をレンダラーへ渡すため、ブラウザの`extra_info`に変換する。
+pub(crate) fn encode_injected_scripts(scripts: &[InjectedScript]) -> Option<DictionaryValue> {
+    let extra_info = dictionary_value_create()?;
+    let mut list = list_value_create()?;
+
+    for (index, script) in scripts.iter().enumerate() {
+        let Some(mut dictionary) = dictionary_value_create() else {
+            continue;
+        };
+        dictionary.set_string(Some(&NAME_KEY.into()), Some(&script.name.as_str().into()));
+        dictionary.set_string(
+            Some(&SOURCE_KEY.into()),
+            Some(&script.source.as_str().into()),
+        );
+        list.set_dictionary(index, Some(&mut dictionary));
+    }
+
+    extra_info.set_list(Some(&EXTRA_INFO_KEY.into()), Some(&mut list));
+
+    Some(extra_info)
+}
+
+/// レンダラーで、`on_browser_created`の`extra_info`から`InjectedScript`を取り出す。
+pub(crate) fn decode_injected_scripts(extra_info: &DictionaryValue) -> Vec<InjectedScript> {
+    let Some(list) = extra_info.list(Some(&EXTRA_INFO_KEY.into())) else {
+        return Vec::new();
+    };
+
+    (0..list.size())
+        .filter_map(|index| list.dictionary(index))
+        .map(|dictionary| InjectedScript {
+            name: CefString::from(&dictionary.string(Some(&NAME_KEY.into()))).to_string(),
+            source: CefString::from(&dictionary.string(Some(&SOURCE_KEY.into()))).to_string(),
+        })
+        .collect()
+}
@@ -0,0 +1,94 @@
+use cef::{
+    CefStringUtf16, Frame, ImplFrame, ImplListValue, ImplProcessMessage, ProcessId,
+    sys::cef_process_id_t,
+};
+use serde_json::Value;
+
+/// レンダラーからブラウザへ送る、要求のメッセージ名。
+/// 引数は`[要求のID, チャンネル, JSONの文字列]`。
+pub(crate) const IPC_REQUEST_MESSAGE: &str = "memex.ipc.request";
+/// ブラウザからレンダラーへ送る、返答のメッセージ名。
+/// 引数は`[要求のID, 成功したかどうか, JSONまたはエラーの文字列]`。
+pub(crate) const IPC_RESPONSE_MESSAGE: &str = "memex.ipc.response";
+/// レンダラーで`window.memex`が使う、要求を送るためのネイティブ関数の名前。
+pub(crate) const IPC_SEND_FUNCTION: &str = "__memexIpcSend";
+/// `window.memex`を定義するスクリプト。
+pub(crate) const IPC_BRIDGE_SCRIPT: &str = include_str!("scripts/ipc_bridge.js");
+
+/// ページの`window.memex.invoke(channel, payload)`による要求。
+/// 返答せずにドロップした場合は、ページ側ではエラーとなる。
+pub struct IpcRequest {
+    url: String,
+    channel: String,
+    payload: Value,
+    responder: Option<(Frame, i32)>,
+}
+
+impl IpcRequest {
+    pub(crate) fn new(frame: Frame, request_id: i32, channel: String, payload: Value) -> Self {
+        Self {
+            url: CefStringUtf16::from(&frame.url()).to_string(),
+            channel,
+            payload,
+            responder: Some((frame, request_id)),
+        }
+    }
+
+    /// 要求元のフレームのURL。オリジンの確認に使う。
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    pub fn channel(&self) -> &str {
+        &self.channel
+    }
+
+    pub fn payload(&self) -> &Value {
+        &self.payload
+    }
+
+    /// 要求に返答する。`Err`はページ側で`Error`として投げられる。
+    pub fn respond(mut self, result: Result<Value, String>) {
+        self.resolve(result);
+    }
+
+    fn resolve(&mut self, result: Result<Value, String>) {
+        let Some((frame, request_id)) = self.responder.take() else {
+            return;
+        };
+
+        let (ok, payload) = match result {
+            Ok(value) => (true, value.to_string()),
+            Err(message) => (false, message),
+        };
+
+        let Some(mut message) = cef::process_message_create(Some(&IPC_RESPONSE_MESSAGE.into()))
+        else {
+            log::error!("IPCの返答のメッセージの作成に失敗しました。");
+            return;
+        };
+
+        if let Some(arguments) = message.argument_list() {
+            arguments.set_int(0, request_id);
+            arguments.set_bool(1, ok as _);
+            arguments.set_string(2, Some(&payload.as_str().into()));
+        }
+
+        frame.send_process_message(
+            ProcessId::from(cef_process_id_t::PID_RENDERER),
+            Some(&mut message),
+        );
+    }
+}
+
+impl Drop for IpcRequest {
+    fn drop(&mut self) {
+        if self.responder.is_some() {
+            log::debug!("IPCの要求が返答されずに破棄されました：{}", self.channel);
+            self.resolve(Err(format!(
+                "チャンネル`{}`の要求は処理されませんでした。",
+                self.channel
+            )));
+        }
+    }
+}
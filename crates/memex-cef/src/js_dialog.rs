@@ -0,0 +1,133 @@
+use cef::{CefString, ImplJsdialogCallback, JsdialogCallback};
+
+/// JavaScriptのダイアログの種類。
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum JsDialogKind {
+    Alert,
+    Confirm,
+    Prompt,
+}
+
+/// `alert`・`confirm`・`prompt`によるダイアログの表示の要求。
+/// [`JsDialogRequest::respond`]はいつ呼んでも良いが、それまでページの処理は止まったままになる。
+/// 返答せずにドロップした場合は、キャンセル扱いとなる。
+pub struct JsDialogRequest {
+    kind: JsDialogKind,
+    origin_url: String,
+    message: String,
+    default_prompt_text: String,
+    callback: Option<JsdialogCallback>,
+}
+
+impl JsDialogRequest {
+    pub(crate) fn new(
+        kind: JsDialogKind,
+        origin_url: String,
+        message: String,
+        default_prompt_text: String,
+        callback: JsdialogCallback,
+    ) -> Self {
+        Self {
+            kind,
+            origin_url,
+            message,
+            default_prompt_text,
+            callback: Some(callback),
+        }
+    }
+
+    pub fn kind(&self) -> JsDialogKind {
+        self.kind
+    }
+
+    pub fn origin_url(&self) -> &str {
+        &self.origin_url
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// `prompt`の入力欄の初期値。
+    pub fn default_prompt_text(&self) -> &str {
+        &self.default_prompt_text
+    }
+
+    /// ダイアログに返答する。
+    /// `user_input`は`prompt`の場合のみ使われる。
+    pub fn respond(mut self, accepted: bool, user_input: Option<&str>) {
+        self.resolve(accepted, user_input);
+    }
+
+    pub fn accept(self) {
+        self.respond(true, None);
+    }
+
+    pub fn cancel(self) {
+        self.respond(false, None);
+    }
+
+    fn resolve(&mut self, accepted: bool, user_input: Option<&str>) {
+        if let Some(callback) = self.callback.take() {
+            let user_input = CefString::from(user_input.unwrap_or_default());
+            callback.cont(accepted as _, Some(&user_input));
+        }
+    }
+}
+
+impl Drop for JsDialogRequest {
+    fn drop(&mut self) {
+        if self.callback.is_some() {
+            log::debug!("JavaScriptのダイアログが返答されずに破棄されたため、キャンセルします。");
+            self.resolve(false, None);
+        }
+    }
+}
+
+/// `beforeunload`による、ページを離れるかどうかの確認の要求。
+/// 返答せずにドロップした場合は、ページを離れることを許可したものとして扱う。
+pub struct BeforeUnloadRequest {
+    message: String,
+    is_reload: bool,
+    callback: Option<JsdialogCallback>,
+}
+
+impl BeforeUnloadRequest {
+    pub(crate) fn new(message: String, is_reload: bool, callback: JsdialogCallback) -> Self {
+        Self {
+            message,
+            is_reload,
+            callback: Some(callback),
+        }
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// 再読み込みによる確認かどうか。
+    pub fn is_reload(&self) -> bool {
+        self.is_reload
+    }
+
+    /// `leave`が`true`ならページを離れ、`false`ならページに留まる。
+    pub fn respond(mut self, leave: bool) {
+        self.resolve(leave);
+    }
+
+    fn resolve(&mut self, leave: bool) {
+        if let Some(callback) = self.callback.take() {
+            callback.cont(leave as _, None);
+        }
+    }
+}
+
+impl Drop for BeforeUnloadRequest {
+    fn drop(&mut self) {
+        if self.callback.is_some() {
+            // 返答されないままだと、タブを閉じられなくなってしまうため離れる方に倒す。
+            log::debug!("`beforeunload`の確認が返答されずに破棄されたため、ページを離れます。");
+            self.resolve(true);
+        }
+    }
+}
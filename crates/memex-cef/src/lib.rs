@@ -1,20 +1,48 @@
 pub use bootstrap::*;
 pub use webview::*;
+pub use zoom::*;
 pub use browser_context::*;
 pub use cef_context::*;
+pub use crash::*;
+pub use devtools::*;
+pub use download::*;
 pub use event_handler::*;
 pub use event_loop::*;
+pub use find::*;
 pub use helper::*;
+pub use highlight::*;
+pub use injected_script::*;
+pub use ipc::*;
+pub use js_dialog::*;
+pub use pdf::*;
+pub use permission::*;
 pub use profile::*;
 pub use rect::*;
+pub use scheme::*;
+pub use screenshot::*;
 
 mod bootstrap;
 mod webview;
+mod zoom;
 mod browser_context;
 mod cef_context;
 mod cef_impl;
+mod crash;
+mod devtools;
+mod download;
 mod event_handler;
 mod event_loop;
+mod find;
 mod helper;
+mod highlight;
+mod injected_script;
+mod ipc;
+mod js_dialog;
+mod pdf;
+mod permission;
 mod profile;
 mod rect;
+mod scheme;
+mod style_sheet;
+mod screenshot;
+mod timeout;
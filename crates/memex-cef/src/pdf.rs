@@ -0,0 +1,43 @@
+/// PDFとして印刷する時の設定。
+#[derive(Clone, Debug)]
+pub struct PdfPrintSettings {
+    pub landscape: bool,
+    pub print_background: bool,
+    /// 拡大率。`1.0`が等倍。
+    pub scale: f64,
+    /// 用紙の幅と高さ（インチ）。`None`ならUSレターになる。
+    pub paper_size: Option<(f64, f64)>,
+    /// ページに指定されたCSSの`@page`の大きさを優先するかどうか。
+    pub prefer_css_page_size: bool,
+    pub display_header_footer: bool,
+}
+
+impl Default for PdfPrintSettings {
+    fn default() -> Self {
+        Self {
+            landscape: false,
+            print_background: true,
+            scale: 1.0,
+            paper_size: None,
+            prefer_css_page_size: true,
+            display_header_footer: false,
+        }
+    }
+}
+
+impl From<&PdfPrintSettings> for cef::PdfPrintSettings {
+    fn from(value: &PdfPrintSettings) -> Self {
+        let (paper_width, paper_height) = value.paper_size.unwrap_or_default();
+
+        Self {
+            landscape: value.landscape as _,
+            print_background: value.print_background as _,
+            scale: value.scale,
+            paper_width,
+            paper_height,
+            prefer_css_page_size: value.prefer_css_page_size as _,
+            display_header_footer: value.display_header_footer as _,
+            ..Default::default()
+        }
+    }
+}
@@ -0,0 +1,258 @@
+use std::str::FromStr;
+
+use cef::{
+    ImplMediaAccessCallback, ImplPermissionPromptCallback, MediaAccessCallback,
+    PermissionPromptCallback, PermissionRequestResult,
+    sys::{
+        cef_media_access_permission_types_t as MediaPermissionType,
+        cef_permission_request_result_t, cef_permission_request_types_t as PromptPermissionType,
+    },
+};
+
+/// サイトが要求する権限の種類。
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PermissionKind {
+    Camera,
+    Microphone,
+    Geolocation,
+    Notifications,
+    Clipboard,
+    ScreenCapture,
+    Other,
+}
+
+impl PermissionKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Camera => "Camera",
+            Self::Microphone => "Microphone",
+            Self::Geolocation => "Geolocation",
+            Self::Notifications => "Notifications",
+            Self::Clipboard => "Clipboard",
+            Self::ScreenCapture => "ScreenCapture",
+            Self::Other => "Other",
+        }
+    }
+
+    /// `on_request_media_access_permission`で渡されるビットフラグを権限の種類に変換する。
+    pub(crate) fn from_media_bits(bits: u32) -> Vec<Self> {
+        let mut kinds = Vec::new();
+
+        if bits & MediaPermissionType::CEF_MEDIA_PERMISSION_DEVICE_VIDEO_CAPTURE as u32 != 0 {
+            kinds.push(Self::Camera);
+        }
+        if bits & MediaPermissionType::CEF_MEDIA_PERMISSION_DEVICE_AUDIO_CAPTURE as u32 != 0 {
+            kinds.push(Self::Microphone);
+        }
+        if bits
+            & (MediaPermissionType::CEF_MEDIA_PERMISSION_DESKTOP_VIDEO_CAPTURE as u32
+                | MediaPermissionType::CEF_MEDIA_PERMISSION_DESKTOP_AUDIO_CAPTURE as u32)
+            != 0
+        {
+            kinds.push(Self::ScreenCapture);
+        }
+
+        kinds
+    }
+
+    /// `on_show_permission_prompt`で渡されるビットフラグを権限の種類に変換する。
+    pub(crate) fn from_prompt_bits(bits: u32) -> Vec<Self> {
+        const TABLE: &[(PromptPermissionType, PermissionKind)] = &[
+            (
+                PromptPermissionType::CEF_PERMISSION_TYPE_CAMERA_STREAM,
+                PermissionKind::Camera,
+            ),
+            (
+                PromptPermissionType::CEF_PERMISSION_TYPE_MIC_STREAM,
+                PermissionKind::Microphone,
+            ),
+            (
+                PromptPermissionType::CEF_PERMISSION_TYPE_GEOLOCATION,
+                PermissionKind::Geolocation,
+            ),
+            (
+                PromptPermissionType::CEF_PERMISSION_TYPE_NOTIFICATIONS,
+                PermissionKind::Notifications,
+            ),
+            (
+                PromptPermissionType::CEF_PERMISSION_TYPE_CLIPBOARD,
+                PermissionKind::Clipboard,
+            ),
+        ];
+
+        let mut kinds = Vec::new();
+        let mut known = 0;
+
+        for (flag, kind) in TABLE {
+            let flag = *flag as u32;
+            known |= flag;
+
+            if bits & flag != 0 {
+                kinds.push(*kind);
+            }
+        }
+
+        if bits & !known != 0 {
+            kinds.push(Self::Other);
+        }
+
+        kinds
+    }
+}
+
+impl FromStr for PermissionKind {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "Camera" => Self::Camera,
+            "Microphone" => Self::Microphone,
+            "Geolocation" => Self::Geolocation,
+            "Notifications" => Self::Notifications,
+            "Clipboard" => Self::Clipboard,
+            "ScreenCapture" => Self::ScreenCapture,
+            "Other" => Self::Other,
+            _ => anyhow::bail!("不明な権限の種類です：{s}"),
+        })
+    }
+}
+
+/// 権限の要求に対する返答。
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PermissionDecision {
+    Allow,
+    Deny,
+}
+
+impl PermissionDecision {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Allow => "Allow",
+            Self::Deny => "Deny",
+        }
+    }
+}
+
+impl FromStr for PermissionDecision {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "Allow" => Self::Allow,
+            "Deny" => Self::Deny,
+            _ => anyhow::bail!("不明な権限の判断です：{s}"),
+        })
+    }
+}
+
+enum PermissionResponder {
+    MediaAccess {
+        callback: MediaAccessCallback,
+        requested_permissions: u32,
+    },
+    Prompt(PermissionPromptCallback),
+}
+
+/// サイトからの権限の要求。
+/// CEFには非同期で返答することになるので、[`PermissionRequest::respond`]はいつ呼んでも良い。
+/// 返答せずにドロップした場合は、要求は却下扱いとなる。
+pub struct PermissionRequest {
+    prompt_id: Option<u64>,
+    origin: String,
+    kinds: Vec<PermissionKind>,
+    responder: Option<PermissionResponder>,
+}
+
+impl PermissionRequest {
+    pub(crate) fn media_access(
+        origin: String,
+        requested_permissions: u32,
+        callback: MediaAccessCallback,
+    ) -> Self {
+        Self {
+            prompt_id: None,
+            origin,
+            kinds: PermissionKind::from_media_bits(requested_permissions),
+            responder: Some(PermissionResponder::MediaAccess {
+                callback,
+                requested_permissions,
+            }),
+        }
+    }
+
+    pub(crate) fn prompt(
+        prompt_id: u64,
+        origin: String,
+        requested_permissions: u32,
+        callback: PermissionPromptCallback,
+    ) -> Self {
+        Self {
+            prompt_id: Some(prompt_id),
+            origin,
+            kinds: PermissionKind::from_prompt_bits(requested_permissions),
+            responder: Some(PermissionResponder::Prompt(callback)),
+        }
+    }
+
+    /// プロンプトとしての要求であれば、そのID。
+    /// [`EventHandler::on_permission_prompt_dismiss`](crate::EventHandler::on_permission_prompt_dismiss)
+    /// で渡されるIDと対応する。
+    pub fn prompt_id(&self) -> Option<u64> {
+        self.prompt_id
+    }
+
+    pub fn origin(&self) -> &str {
+        &self.origin
+    }
+
+    pub fn kinds(&self) -> &[PermissionKind] {
+        &self.kinds
+    }
+
+    pub fn respond(mut self, decision: PermissionDecision) {
+        self.resolve(Some(decision));
+    }
+
+    /// 許可も却下もせずにプロンプトを閉じる。
+    pub fn dismiss(mut self) {
+        self.resolve(None);
+    }
+
+    fn resolve(&mut self, decision: Option<PermissionDecision>) {
+        let Some(responder) = self.responder.take() else {
+            return;
+        };
+
+        match responder {
+            PermissionResponder::MediaAccess {
+                callback,
+                requested_permissions,
+            } => match decision {
+                Some(PermissionDecision::Allow) => callback.cont(requested_permissions),
+                _ => callback.cancel(),
+            },
+            PermissionResponder::Prompt(callback) => {
+                let result = match decision {
+                    Some(PermissionDecision::Allow) => {
+                        cef_permission_request_result_t::CEF_PERMISSION_RESULT_ACCEPT
+                    }
+                    Some(PermissionDecision::Deny) => {
+                        cef_permission_request_result_t::CEF_PERMISSION_RESULT_DENY
+                    }
+                    None => cef_permission_request_result_t::CEF_PERMISSION_RESULT_DISMISS,
+                };
+
+                callback.cont(PermissionRequestResult::from(result));
+            }
+        }
+    }
+}
+
+impl Drop for PermissionRequest {
+    fn drop(&mut self) {
+        if self.responder.is_some() {
+            log::debug!("権限の要求が返答されずに破棄されたため、却下します。");
+            self.resolve(Some(PermissionDecision::Deny));
+        }
+    }
+}
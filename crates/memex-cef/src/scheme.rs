@@ -0,0 +1,35 @@
+use std::sync::Arc;
+
+use anyhow::Context as _;
+
+use crate::{DownloadedResource, cef_impl::SchemeHandlerFactoryService};
+
+/// アプリの内部ページのURLのスキーム。全てのプロセスで、標準のスキームとして登録する。
+pub const INTERNAL_SCHEME: &str = "memex";
+
+/// `memex://`のURLかどうか。
+pub(crate) fn is_internal_url(url: &str) -> bool {
+    url.split_once(':')
+        .is_some_and(|(scheme, _)| scheme.eq_ignore_ascii_case(INTERNAL_SCHEME))
+}
+
+/// `memex://`のURLに対するリソースを返す。見つからなければ`None`を返す。
+/// CEFのIOスレッドから呼ばれるので、UIスレッドの状態には触れないこと。
+pub type InternalSchemeHandler = Arc<dyn Fn(&str) -> Option<DownloadedResource> + Send + Sync>;
+
+/// `memex://`のURLを読み込んだ時に、`handler`が返すリソースを表示する。`boot`の後に呼ぶこと。
+pub fn register_internal_scheme_handler(
+    handler: impl Fn(&str) -> Option<DownloadedResource> + Send + Sync + 'static,
+) -> anyhow::Result<()> {
+    let mut factory = SchemeHandlerFactoryService::create(Arc::new(handler));
+
+    let ok = cef::register_scheme_handler_factory(
+        Some(&INTERNAL_SCHEME.into()),
+        None,
+        Some(&mut factory),
+    );
+
+    (ok == 1)
+        .then_some(())
+        .context("`memex://`のハンドラの登録に失敗しました。")
+}
@@ -0,0 +1,8 @@
+/// スクリーンショットを撮る範囲。
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ScreenshotArea {
+    /// 現在表示されている範囲。
+    Viewport,
+    /// スクロールしないと見えない部分も含めた、ページ全体。
+    FullPage,
+}
@@ -0,0 +1,88 @@
+((highlights) => {
+  const IGNORED_TAGS = new Set(["SCRIPT", "STYLE", "NOSCRIPT", "TEMPLATE"]);
+
+  // `body`のテキストノードと、それらを連結した文字列での開始位置。
+  const collectTextNodes = () => {
+    const walker = document.createTreeWalker(document.body, NodeFilter.SHOW_TEXT, {
+      acceptNode: (node) =>
+        IGNORED_TAGS.has(node.parentElement?.tagName)
+          ? NodeFilter.FILTER_REJECT
+          : NodeFilter.FILTER_ACCEPT,
+    });
+    const nodes = [];
+    let text = "";
+    while (walker.nextNode()) {
+      nodes.push({ node: walker.currentNode, start: text.length });
+      text += walker.currentNode.data;
+    }
+    return { nodes, text };
+  };
+
+  // 前後の文字列がより一致し、元の位置により近いものを選ぶ。
+  const findQuote = (text, anchor) => {
+    let best = null;
+    let bestScore = -Infinity;
+    for (let index = text.indexOf(anchor.quote); index !== -1; index = text.indexOf(anchor.quote, index + 1)) {
+      const prefix = text.slice(Math.max(0, index - anchor.prefix.length), index);
+      const suffix = text.slice(index + anchor.quote.length, index + anchor.quote.length + anchor.suffix.length);
+      const score =
+        (prefix === anchor.prefix ? 2 : 0) +
+        (suffix === anchor.suffix ? 2 : 0) -
+        Math.abs(index - anchor.start) / Math.max(text.length, 1);
+      if (score > bestScore) {
+        best = index;
+        bestScore = score;
+      }
+    }
+    return best;
+  };
+
+  const highlight = ({ id, anchor, note }) => {
+    if (!anchor.quote) {
+      return;
+    }
+    const { nodes, text } = collectTextNodes();
+    const start = findQuote(text, anchor);
+    if (start === null) {
+      return;
+    }
+    const end = start + anchor.quote.length;
+
+    for (const { node, start: nodeStart } of nodes) {
+      const nodeEnd = nodeStart + node.data.length;
+      if (nodeEnd <= start || end <= nodeStart) {
+        continue;
+      }
+      const range = document.createRange();
+      range.setStart(node, Math.max(start - nodeStart, 0));
+      range.setEnd(node, Math.min(end, nodeEnd) - nodeStart);
+      if (range.collapsed || !range.toString().trim()) {
+        continue;
+      }
+      const mark = document.createElement("mark");
+      mark.dataset.memexHighlight = id;
+      if (note) {
+        mark.title = note;
+      }
+      range.surroundContents(mark);
+    }
+  };
+
+  const apply = () => {
+    for (const mark of document.querySelectorAll("mark[data-memex-highlight]")) {
+      mark.replaceWith(...mark.childNodes);
+    }
+    document.body.normalize();
+
+    for (const item of highlights) {
+      highlight(item);
+    }
+  };
+
+  // 読み込みの開始直後は、まだ本文がない。
+  if (document.readyState === "loading") {
+    document.addEventListener("DOMContentLoaded", apply, { once: true });
+  } else {
+    apply();
+  }
+})
@@ -0,0 +1,26 @@
+((styleSheets) => {
+  const apply = () => {
+    for (const element of document.querySelectorAll("style[data-memex-style-sheet]")) {
+      element.remove();
+    }
+
+    for (const css of styleSheets) {
+      const element = document.createElement("style");
+      element.dataset.memexStyleSheet = "";
+      element.textContent = css;
+      (document.head ?? document.documentElement).append(element);
+    }
+  };
+
+  // 読み込みの開始直後は、まだルート要素がないことがある。
+  if (document.documentElement) {
+    apply();
+  } else {
+    new MutationObserver((_, observer) => {
+      if (document.documentElement) {
+        observer.disconnect();
+        apply();
+      }
+    }).observe(document, { childList: true });
+  }
+})
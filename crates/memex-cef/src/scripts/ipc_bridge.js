@@ -0,0 +1,45 @@
+(() => {
+  const send = globalThis.__memexIpcSend;
+  delete globalThis.__memexIpcSend;
+
+  if (typeof send !== "function" || "memex" in globalThis) {
+    return;
+  }
+
+  let nextId = 0;
+  const pending = new Map();
+
+  // ページが同じ名前を宣言しても壊れないよう、書き換えも再定義もできるようにしておく。
+  Object.defineProperty(globalThis, "__memexIpcResolve", {
+    configurable: true,
+    writable: true,
+    value: (id, ok, payload) => {
+      const callbacks = pending.get(id);
+      if (!callbacks) {
+        return;
+      }
+      pending.delete(id);
+
+      if (ok) {
+        callbacks.resolve(JSON.parse(payload));
+      } else {
+        callbacks.reject(new Error(payload));
+      }
+    },
+  });
+
+  Object.defineProperty(globalThis, "memex", {
+    configurable: true,
+    writable: true,
+    value: Object.freeze({
+      invoke(channel, payload) {
+        const id = ++nextId;
+
+        return new Promise((resolve, reject) => {
+          pending.set(id, { resolve, reject });
+          send(id, String(channel), JSON.stringify(payload ?? null));
+        });
+      },
+    }),
+  });
+})();
@@ -0,0 +1,42 @@
+(() => {
+  const IGNORED_TAGS = new Set(["SCRIPT", "STYLE", "NOSCRIPT", "TEMPLATE"]);
+  const CONTEXT_LENGTH = 32;
+
+  const selection = document.getSelection();
+  if (!selection || selection.isCollapsed || selection.rangeCount === 0) {
+    return null;
+  }
+  const range = selection.getRangeAt(0);
+
+  // `apply_highlights.js`と同じ方法で、`body`のテキストを連結する。
+  const walker = document.createTreeWalker(document.body, NodeFilter.SHOW_TEXT, {
+    acceptNode: (node) =>
+      IGNORED_TAGS.has(node.parentElement?.tagName)
+        ? NodeFilter.FILTER_REJECT
+        : NodeFilter.FILTER_ACCEPT,
+  });
+  let text = "";
+  let start = null;
+  let end = null;
+  while (walker.nextNode()) {
+    const node = walker.currentNode;
+    if (range.intersectsNode(node)) {
+      const nodeStart = node === range.startContainer ? range.startOffset : 0;
+      const nodeEnd = node === range.endContainer ? range.endOffset : node.data.length;
+      start ??= text.length + nodeStart;
+      end = text.length + nodeEnd;
+    }
+    text += node.data;
+  }
+  if (start === null || end <= start) {
+    return null;
+  }
+
+  return {
+    quote: text.slice(start, end),
+    prefix: text.slice(Math.max(0, start - CONTEXT_LENGTH), start),
+    suffix: text.slice(end, end + CONTEXT_LENGTH),
+    start,
+    end,
+  };
+})
@@ -0,0 +1,12 @@
+use cef::{Frame, ImplFrame};
+
+const APPLY_STYLE_SHEETS_SCRIPT: &str = include_str!("scripts/apply_style_sheets.js");
+
+/// フレームに適用しているスタイルシートを、`style_sheets`で置き換える。
+/// 空であれば、適用済みのものを取り除くだけになる。
+pub(crate) fn apply_style_sheets(frame: &Frame, style_sheets: &[String]) {
+    let style_sheets = serde_json::Value::from(style_sheets).to_string();
+    let code = format!("{APPLY_STYLE_SHEETS_SCRIPT}({style_sheets});");
+
+    frame.execute_java_script(Some(&code.as_str().into()), None, 0);
+}
@@ -0,0 +1,12 @@
+use std::{pin::pin, time::Duration};
+
+use futures::future::{Either, select};
+
+/// `future`が`duration`以内に終われば結果を、終わらなければ`None`を返す。
+/// CEFのコールバックが呼ばれないまま待ち続けないようにするのに使う。
+pub(crate) async fn timeout<T>(duration: Duration, future: impl Future<Output = T>) -> Option<T> {
+    match select(pin!(future), pin!(async_io::Timer::after(duration))).await {
+        Either::Left((output, _)) => Some(output),
+        Either::Right(_) => None,
+    }
+}
@@ -1,17 +1,60 @@
-use std::ffi::c_void;
+use std::{
+    cell::{Cell, RefCell},
+    ffi::c_void,
+    path::Path,
+    rc::{Rc, Weak},
+    time::Duration,
+};
 
 use anyhow::Context;
+use base64::Engine as _;
 use cef::{CefStringUtf16, Client, ImplBrowser, ImplBrowserHost, ImplFrame};
 use raw_window_handle::RawWindowHandle;
 
 use crate::{
-    UIThreadMarker, WebViewBounds, WebViewContext, cef_impl::ClientService, profile::Profile,
+    Highlight, InjectedScript, PdfPrintSettings, ScreenshotArea, TextAnchor, UIThreadMarker,
+    WebViewBounds, WebViewContext, WindowSize,
+    cef_impl::{
+        ClientService, DownloadImageCallbackService, PdfPrintCallbackService, RenderHandlerService,
+    },
+    devtools::{DevTools, DevToolsState},
+    highlight::{apply_highlights, selection_anchor_expression},
+    injected_script::encode_injected_scripts,
+    is_windowless_rendering_enabled,
+    profile::Profile,
+    style_sheet::apply_style_sheets,
+    timeout::timeout,
 };
 
+/// PDFへの印刷を待つ時間の上限。
+pub const PDF_PRINT_TIMEOUT: Duration = Duration::from_secs(60);
+
 #[derive(Clone)]
 pub struct WebView {
+    inner: Rc<WebViewInner>,
+}
+
+/// `WebView`への弱い参照。`WebView`が持つクライアントから参照される場所で、循環参照を避けるのに使う。
+#[derive(Clone)]
+pub struct WeakWebView {
+    inner: Weak<WebViewInner>,
+}
+
+impl WeakWebView {
+    /// 全ての`WebView`が破棄されていれば`None`を返す。
+    pub fn upgrade(&self) -> Option<WebView> {
+        self.inner.upgrade().map(|inner| WebView { inner })
+    }
+}
+
+struct WebViewInner {
     browser: cef::Browser,
     _client: Client,
+    /// 直前のページ内検索の文字列。同じ文字列での検索なら、次の一致箇所へ移動させるのに使う。
+    find_text: RefCell<Option<String>>,
+    /// ウィンドウを持たない`WebView`の場合の、描画する領域の大きさ。
+    view_size: Option<Rc<Cell<WindowSize>>>,
+    dev_tools: Rc<DevToolsState>,
 }
 
 impl WebView {
@@ -21,6 +64,7 @@ impl WebView {
         parent_window: RawWindowHandle,
         initial_url: &str,
         rect: WebViewBounds,
+        injected_scripts: &[InjectedScript],
     ) -> anyhow::Result<Self> {
         let view = match parent_window {
             RawWindowHandle::AppKit(handle) => handle.ns_view.as_ptr(),
@@ -35,14 +79,70 @@ impl WebView {
             bounds: rect.into(),
             ..Default::default()
         };
-        let mut client = ClientService::create(context);
+        let client = ClientService::create(context, None);
+
+        Self::create(
+            profile,
+            window_info,
+            client,
+            initial_url,
+            injected_scripts,
+            None,
+        )
+    }
+
+    /// ウィンドウを持たない`WebView`を作る。
+    /// 画面には表示されないので、PDFへの印刷などをヘッドレスで行うのに使える。
+    /// `boot`で`windowless_rendering`を有効にしている必要がある。
+    pub fn new_windowless(
+        profile: &mut Profile,
+        context: WebViewContext,
+        initial_url: &str,
+        size: WindowSize,
+        injected_scripts: &[InjectedScript],
+    ) -> anyhow::Result<Self> {
+        anyhow::ensure!(
+            is_windowless_rendering_enabled(),
+            "ウィンドウを持たない描画が有効になっていません。`boot`で有効にしてください。"
+        );
+
+        let window_info = cef::WindowInfo {
+            windowless_rendering_enabled: true as _,
+            ..Default::default()
+        };
+        let view_size = Rc::new(Cell::new(size));
+        let client = ClientService::create(
+            context,
+            Some(RenderHandlerService::create(view_size.clone())),
+        );
+
+        Self::create(
+            profile,
+            window_info,
+            client,
+            initial_url,
+            injected_scripts,
+            Some(view_size),
+        )
+    }
+
+    fn create(
+        profile: &mut Profile,
+        window_info: cef::WindowInfo,
+        mut client: Client,
+        initial_url: &str,
+        injected_scripts: &[InjectedScript],
+        view_size: Option<Rc<Cell<WindowSize>>>,
+    ) -> anyhow::Result<Self> {
+        // スクリプトはレンダラーの`on_browser_created`に`extra_info`として届く。
+        let mut extra_info = encode_injected_scripts(injected_scripts);
 
         let browser = cef::browser_host_create_browser_sync(
             Some(&window_info),
             Some(&mut client),
             Some(&initial_url.into()),
             Some(&profile.browser_settings),
-            None,
+            extra_info.as_mut(),
             Some(&mut profile.request_context),
         );
         // anyhow::ensure!(result == 1, "Failed to create browser.");
@@ -53,19 +153,43 @@ impl WebView {
         //     .context("Failed to retrieve browser from CEF.")?;
 
         Ok(Self {
-            browser: browser.context("ブラウザの作成に失敗しました。")?,
-            _client: client,
+            inner: Rc::new(WebViewInner {
+                browser: browser.context("ブラウザの作成に失敗しました。")?,
+                _client: client,
+                find_text: RefCell::default(),
+                view_size,
+                dev_tools: Rc::default(),
+            }),
         })
     }
 
-    pub fn resize(&self, _rect: WebViewBounds) {
-        log::info!("TODO: on_resize");
+    pub fn downgrade(&self) -> WeakWebView {
+        WeakWebView {
+            inner: Rc::downgrade(&self.inner),
+        }
+    }
+
+    pub fn resize(&self, rect: WebViewBounds) {
+        let Some(view_size) = &self.inner.view_size else {
+            log::info!("TODO: on_resize");
+            return;
+        };
+
+        view_size.set(WindowSize {
+            width: rect.width,
+            height: rect.height,
+        });
+
+        if let Some(host) = self.inner.browser.host() {
+            host.was_resized();
+        }
     }
 
     pub fn view_handle(&self, utm: UIThreadMarker) -> Option<*mut c_void> {
         let _ = utm;
 
-        self.browser
+        self.inner
+            .browser
             .host()
             .map(|browser_host| browser_host.window_handle())
     }
@@ -92,37 +216,40 @@ impl WebView {
 
     pub fn go_back(&self) {
         log::debug!("go_back");
-        self.browser.go_back();
+        self.inner.browser.go_back();
     }
 
     pub fn go_forward(&self) {
         log::debug!("go_forward");
-        self.browser.go_forward();
+        self.inner.browser.go_forward();
     }
 
     pub fn can_go_back(&self) -> bool {
-        self.browser.can_go_back() == 1
+        self.inner.browser.can_go_back() == 1
     }
 
     pub fn can_go_forward(&self) -> bool {
-        self.browser.can_go_forward() == 1
+        self.inner.browser.can_go_forward() == 1
     }
 
     pub fn reload(&self) {
         log::debug!("reload");
-        self.browser.reload();
+        self.inner.browser.reload();
     }
 
     pub fn hard_reload(&self) {
         log::debug!("hard reload");
-        self.browser.reload_ignore_cache();
+        self.inner.browser.reload_ignore_cache();
     }
 
     pub fn close(&self, utm: UIThreadMarker) -> anyhow::Result<()> {
         log::debug!("close");
         let _ = utm;
 
-        self.browser
+        self.inner.dev_tools.detach();
+
+        self.inner
+            .browser
             .host()
             .context("The browser host is not available yet.")?
             .close_browser(0);
@@ -130,6 +257,254 @@ impl WebView {
         Ok(())
     }
 
+    pub fn zoom_level(&self) -> anyhow::Result<f64> {
+        Ok(self
+            .browser
+            .host()
+            .context("The browser host is not available yet.")?
+            .zoom_level())
+    }
+
+    pub fn set_zoom_level(&self, level: f64) -> anyhow::Result<()> {
+        log::debug!("set zoom level: {level}");
+
+        self.inner
+            .browser
+            .host()
+            .context("The browser host is not available yet.")?
+            .set_zoom_level(level);
+
+        Ok(())
+    }
+
+    /// 1段階拡大し、変更後のズームレベルを返す。
+    pub fn zoom_in(&self) -> anyhow::Result<f64> {
+        let level = self.zoom_level()?;
+        let level = crate::next_zoom_level(level).unwrap_or(level);
+        self.set_zoom_level(level)?;
+
+        Ok(level)
+    }
+
+    /// 1段階縮小し、変更後のズームレベルを返す。
+    pub fn zoom_out(&self) -> anyhow::Result<f64> {
+        let level = self.zoom_level()?;
+        let level = crate::previous_zoom_level(level).unwrap_or(level);
+        self.set_zoom_level(level)?;
+
+        Ok(level)
+    }
+
+    pub fn reset_zoom(&self) -> anyhow::Result<()> {
+        self.set_zoom_level(0.)
+    }
+
+    /// 表示中のページに適用しているスタイルシートを置き換える。
+    /// 次のナビゲーションからは`EventHandler::style_sheets_for`の結果が使われる。
+    pub fn set_style_sheets(&self, style_sheets: &[String]) -> anyhow::Result<()> {
+        let frame = self
+            .browser
+            .main_frame()
+            .context("メインフレームが未設定")?;
+        apply_style_sheets(&frame, style_sheets);
+
+        Ok(())
+    }
+
+    /// 表示中のページのハイライトを置き換える。
+    /// 次のナビゲーションからは`EventHandler::highlights_for`の結果が使われる。
+    pub fn set_highlights(&self, highlights: &[Highlight]) -> anyhow::Result<()> {
+        let frame = self
+            .browser
+            .main_frame()
+            .context("メインフレームが未設定")?;
+        apply_highlights(&frame, highlights);
+
+        Ok(())
+    }
+
+    /// 選択中の文章の位置を返す。何も選択されていなければ`None`を返す。
+    pub async fn selection_anchor(&self) -> anyhow::Result<Option<TextAnchor>> {
+        let value = self
+            .dev_tools()
+            .evaluate(&selection_anchor_expression())
+            .await?;
+
+        if value.is_null() {
+            return Ok(None);
+        }
+
+        serde_json::from_value(value).context("選択範囲の解析に失敗しました。")
+    }
+
+    /// ページ内を検索する。結果は`EventHandler::on_find_result`で通知される。
+    /// 直前と同じ文字列で呼び出した場合は、`forward`に応じて次か前の一致箇所へ移動する。
+    pub fn find(&self, text: &str, forward: bool, match_case: bool) -> anyhow::Result<()> {
+        let find_next = self.inner.find_text.borrow().as_deref() == Some(text);
+        *self.inner.find_text.borrow_mut() = Some(text.to_owned());
+
+        self.inner
+            .browser
+            .host()
+            .context("The browser host is not available yet.")?
+            .find(
+                Some(&text.into()),
+                forward as _,
+                match_case as _,
+                find_next as _,
+            );
+
+        Ok(())
+    }
+
+    pub fn stop_finding(&self) -> anyhow::Result<()> {
+        self.inner.find_text.borrow_mut().take();
+
+        self.inner
+            .browser
+            .host()
+            .context("The browser host is not available yet.")?
+            .stop_finding(true as _);
+
+        Ok(())
+    }
+
+    /// ページをPDFとして`path`に保存する。保存が終わるまで待つ。
+    /// [`PDF_PRINT_TIMEOUT`]を過ぎても終わらなければ、エラーを返す。
+    pub async fn print_to_pdf(
+        &self,
+        path: &Path,
+        settings: &PdfPrintSettings,
+    ) -> anyhow::Result<()> {
+        let path_string = path.to_str().context("`path`の文字列化に失敗")?;
+        let (result_tx, result_rx) = async_channel::bounded(1);
+        let mut callback = PdfPrintCallbackService::create(result_tx);
+
+        self.inner
+            .browser
+            .host()
+            .context("The browser host is not available yet.")?
+            .print_to_pdf(
+                Some(&path_string.into()),
+                Some(&settings.into()),
+                Some(&mut callback),
+            );
+
+        // ブラウザが閉じられてコールバックが破棄されると、受信はエラーになる。
+        let ok = timeout(PDF_PRINT_TIMEOUT, result_rx.recv())
+            .await
+            .context("PDFへの印刷が時間内に終わりませんでした。")?
+            .context("PDFへの印刷の完了を受け取れませんでした。")?;
+        anyhow::ensure!(ok, "PDFへの印刷に失敗しました：{}", path.display());
+
+        Ok(())
+    }
+
+    /// ファビコンをダウンロードし、PNGのバイト列を返す。ICOなどの形式もPNGに変換される。
+    /// 画像が`max_size`より大きければ縮小する。`0`なら縮小しない。
+    pub async fn download_favicon(&self, url: &str, max_size: u32) -> anyhow::Result<Vec<u8>> {
+        let (result_tx, result_rx) = async_channel::bounded(1);
+        let mut callback = DownloadImageCallbackService::create(result_tx);
+
+        self.inner
+            .browser
+            .host()
+            .context("The browser host is not available yet.")?
+            .download_image(
+                Some(&url.into()),
+                true as _,
+                max_size,
+                false as _,
+                Some(&mut callback),
+            );
+
+        result_rx
+            .recv()
+            .await
+            .context("ファビコンのダウンロードの完了を受け取れませんでした。")?
+            .with_context(|| format!("ファビコンのダウンロードに失敗しました：{url}"))
+    }
+
+    /// DevToolsプロトコルで操作するためのハンドルを返す。
+    pub fn dev_tools(&self) -> DevTools {
+        DevTools::new(self.inner.browser.clone(), self.inner.dev_tools.clone())
+    }
+
+    /// ページのDOMとサブリソースを、単一のMHTMLとして取得する。
+    pub async fn capture_mhtml(&self) -> anyhow::Result<String> {
+        let dev_tools = self.dev_tools();
+
+        let result = dev_tools
+            .call(
+                "Page.captureSnapshot",
+                serde_json::json!({ "format": "mhtml" }),
+            )
+            .await?;
+
+        result["data"]
+            .as_str()
+            .map(ToOwned::to_owned)
+            .context("スナップショットの結果にデータがありません。")
+    }
+
+    /// スクリーンショットを撮り、PNGのバイト列を返す。
+    /// `scale`は出力する画像の拡大率で、サムネイルなら`1.0`より小さくする。
+    pub async fn capture_screenshot(
+        &self,
+        area: ScreenshotArea,
+        scale: f64,
+    ) -> anyhow::Result<Vec<u8>> {
+        let dev_tools = self.dev_tools();
+
+        let metrics = dev_tools
+            .call("Page.getLayoutMetrics", serde_json::json!({}))
+            .await?;
+
+        let clip = match area {
+            ScreenshotArea::Viewport => {
+                let viewport = &metrics["cssVisualViewport"];
+
+                serde_json::json!({
+                    "x": viewport["pageX"],
+                    "y": viewport["pageY"],
+                    "width": viewport["clientWidth"],
+                    "height": viewport["clientHeight"],
+                    "scale": scale,
+                })
+            }
+            ScreenshotArea::FullPage => {
+                let content_size = &metrics["cssContentSize"];
+
+                serde_json::json!({
+                    "x": 0,
+                    "y": 0,
+                    "width": content_size["width"],
+                    "height": content_size["height"],
+                    "scale": scale,
+                })
+            }
+        };
+
+        let result = dev_tools
+            .call(
+                "Page.captureScreenshot",
+                serde_json::json!({
+                    "format": "png",
+                    "clip": clip,
+                    "captureBeyondViewport": area == ScreenshotArea::FullPage,
+                }),
+            )
+            .await?;
+
+        let data = result["data"]
+            .as_str()
+            .context("スクリーンショットの結果にデータがありません。")?;
+
+        base64::engine::general_purpose::STANDARD
+            .decode(data)
+            .context("スクリーンショットのデコードに失敗しました。")
+    }
+
     pub fn title(&self) -> anyhow::Result<String> {
         Ok("title TODO".to_owned())
     }
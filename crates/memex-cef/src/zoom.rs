@@ -0,0 +1,84 @@
+/// ズームの段階として使う倍率。Chromiumの既定のものと同じ。
+const ZOOM_FACTORS: &[f64] = &[
+    0.25, 0.33, 0.5, 0.67, 0.75, 0.8, 0.9, 1.0, 1.1, 1.25, 1.5, 1.75, 2.0, 2.5, 3.0, 4.0, 5.0,
+];
+
+/// ズームレベルを1段階変える時、倍率のずれをどこまで同じ段階とみなすか。
+const EPSILON: f64 = 0.001;
+
+/// CEFのズームレベルを倍率に変換する。ズームレベルは、倍率を1.2を底とした対数で表したもの。
+pub fn zoom_level_to_factor(level: f64) -> f64 {
+    1.2_f64.powf(level)
+}
+
+pub fn zoom_factor_to_level(factor: f64) -> f64 {
+    factor.ln() / 1.2_f64.ln()
+}
+
+/// 1段階拡大したズームレベルを返す。既に最大なら`None`を返す。
+pub fn next_zoom_level(level: f64) -> Option<f64> {
+    let factor = zoom_level_to_factor(level);
+
+    ZOOM_FACTORS
+        .iter()
+        .find(|candidate| **candidate > factor + EPSILON)
+        .map(|factor| zoom_factor_to_level(*factor))
+}
+
+/// 1段階縮小したズームレベルを返す。既に最小なら`None`を返す。
+pub fn previous_zoom_level(level: f64) -> Option<f64> {
+    let factor = zoom_level_to_factor(level);
+
+    ZOOM_FACTORS
+        .iter()
+        .rev()
+        .find(|candidate| **candidate < factor - EPSILON)
+        .map(|factor| zoom_factor_to_level(*factor))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_factor(level: Option<f64>, factor: f64) {
+        let level = level.expect("次の段階がありません。");
+        assert!((zoom_level_to_factor(level) - factor).abs() < EPSILON);
+    }
+
+    #[test]
+    fn steps_through_factors() {
+        let level = zoom_factor_to_level(1.0);
+
+        assert_factor(next_zoom_level(level), 1.1);
+        assert_factor(previous_zoom_level(level), 0.9);
+        assert_factor(next_zoom_level(zoom_factor_to_level(1.1)), 1.25);
+        assert_factor(previous_zoom_level(zoom_factor_to_level(0.9)), 0.8);
+    }
+
+    #[test]
+    fn snaps_between_factors() {
+        // 段階の間にあれば、次の段階へ進む。
+        let level = zoom_factor_to_level(1.05);
+
+        assert_factor(next_zoom_level(level), 1.1);
+        assert_factor(previous_zoom_level(level), 1.0);
+    }
+
+    #[test]
+    fn tolerates_rounding_errors() {
+        // 保存したズームレベルの誤差で、同じ段階に留まらない。
+        let level = zoom_factor_to_level(1.25) + 1e-9;
+
+        assert_factor(next_zoom_level(level), 1.5);
+        assert_factor(previous_zoom_level(level), 1.1);
+    }
+
+    #[test]
+    fn stops_at_limits() {
+        assert_eq!(next_zoom_level(zoom_factor_to_level(5.0)), None);
+        assert_eq!(previous_zoom_level(zoom_factor_to_level(0.25)), None);
+        // 範囲の外からは、一番近い段階へ戻る。
+        assert_factor(next_zoom_level(zoom_factor_to_level(0.1)), 0.25);
+        assert_factor(previous_zoom_level(zoom_factor_to_level(10.0)), 5.0);
+    }
+}
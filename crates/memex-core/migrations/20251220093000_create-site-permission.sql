@@ -0,0 +1,16 @@
+CREATE TABLE site_permission (
+    origin TEXT NOT NULL,
+    workspace_id CHAR(36),
+    permission TEXT NOT NULL,
+    decision TEXT NOT NULL,
+
+    FOREIGN KEY (workspace_id)
+        REFERENCES workspace(id)
+        ON DELETE CASCADE
+);
+
+CREATE INDEX idx_site_permission_origin ON site_permission(origin);
+
+-- UNIQUEではNULL同士が別の値として扱われるので、全体の判断も重複しないよう式で比べる。
+CREATE UNIQUE INDEX idx_site_permission_scope
+    ON site_permission(origin, IFNULL(workspace_id, ''), permission);
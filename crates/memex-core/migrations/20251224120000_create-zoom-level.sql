@@ -0,0 +1,15 @@
+CREATE TABLE zoom_level (
+    origin TEXT NOT NULL,
+    workspace_id CHAR(36),
+    level REAL NOT NULL,
+
+    FOREIGN KEY (workspace_id)
+        REFERENCES workspace(id)
+        ON DELETE CASCADE
+);
+
+CREATE INDEX idx_zoom_level_origin ON zoom_level(origin);
+
+-- UNIQUEではNULL同士が別の値として扱われるので、全体のズームレベルも重複しないよう式で比べる。
+CREATE UNIQUE INDEX idx_zoom_level_scope
+    ON zoom_level(origin, IFNULL(workspace_id, ''));
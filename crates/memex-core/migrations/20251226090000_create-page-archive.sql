@@ -0,0 +1,14 @@
+CREATE TABLE page_archive (
+    id CHAR(36) NOT NULL PRIMARY KEY,
+    workspace_id CHAR(36) NOT NULL,
+    path TEXT NOT NULL,
+    source_url TEXT NOT NULL,
+    title TEXT NOT NULL,
+    captured_at INTEGER NOT NULL,
+
+    FOREIGN KEY (workspace_id)
+        REFERENCES workspace(id)
+        ON DELETE CASCADE
+);
+
+CREATE INDEX idx_page_archive_path ON page_archive(path);
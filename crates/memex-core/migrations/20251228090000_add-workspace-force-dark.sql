@@ -0,0 +1 @@
+ALTER TABLE workspace ADD COLUMN force_dark BOOLEAN NOT NULL DEFAULT FALSE;
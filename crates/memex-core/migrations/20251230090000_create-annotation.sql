@@ -0,0 +1,18 @@
+CREATE TABLE annotation (
+    id CHAR(36) NOT NULL PRIMARY KEY,
+    workspace_id CHAR(36) NOT NULL,
+    url TEXT NOT NULL,
+    quote TEXT NOT NULL,
+    prefix TEXT NOT NULL,
+    suffix TEXT NOT NULL,
+    start_offset INTEGER NOT NULL,
+    end_offset INTEGER NOT NULL,
+    note TEXT NOT NULL,
+    created_at INTEGER NOT NULL,
+
+    FOREIGN KEY (workspace_id)
+        REFERENCES workspace(id)
+        ON DELETE CASCADE
+);
+
+CREATE INDEX idx_annotation_workspace_url ON annotation(workspace_id, url);
@@ -0,0 +1,15 @@
+CREATE TABLE tab_group (
+    id CHAR(36) NOT NULL PRIMARY KEY,
+    workspace_id CHAR(36) NOT NULL,
+    name TEXT NOT NULL,
+    color TEXT NOT NULL,
+    is_collapsed BOOLEAN NOT NULL DEFAULT FALSE,
+
+    FOREIGN KEY (workspace_id)
+        REFERENCES workspace(id)
+        ON DELETE CASCADE
+);
+
+ALTER TABLE tab ADD COLUMN is_pinned BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE tab ADD COLUMN group_id CHAR(36) REFERENCES tab_group(id) ON DELETE SET NULL;
+ALTER TABLE tab ADD COLUMN position INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1,4 @@
+ALTER TABLE workspace ADD COLUMN name TEXT NOT NULL DEFAULT '';
+ALTER TABLE workspace ADD COLUMN position INTEGER NOT NULL DEFAULT 0;
+
+UPDATE workspace SET name = 'ホーム' WHERE icon_type = 'Home';
@@ -0,0 +1,21 @@
+CREATE TABLE workspace_icon_image (
+    workspace_id CHAR(36) NOT NULL,
+    size INTEGER NOT NULL,
+    path TEXT NOT NULL,
+
+    PRIMARY KEY (workspace_id, size),
+    FOREIGN KEY (workspace_id)
+        REFERENCES workspace(id)
+        ON DELETE CASCADE
+);
+
+CREATE TABLE site_visit (
+    workspace_id CHAR(36) NOT NULL,
+    origin TEXT NOT NULL,
+    visit_count INTEGER NOT NULL DEFAULT 0,
+
+    PRIMARY KEY (workspace_id, origin),
+    FOREIGN KEY (workspace_id)
+        REFERENCES workspace(id)
+        ON DELETE CASCADE
+);
@@ -0,0 +1,8 @@
+CREATE TABLE page_favicon (
+    page_url TEXT NOT NULL PRIMARY KEY,
+    icon_url TEXT NOT NULL,
+    -- ファビコンの内容のSHA-256。キャッシュのファイル名になる。
+    hash TEXT NOT NULL
+);
+
+CREATE INDEX page_favicon_icon_url ON page_favicon(icon_url);
@@ -0,0 +1,27 @@
+CREATE TABLE history (
+    workspace_id CHAR(36) NOT NULL,
+    url TEXT NOT NULL,
+    title TEXT NOT NULL DEFAULT '',
+    visit_count INTEGER NOT NULL DEFAULT 0,
+    last_visited_at INTEGER NOT NULL,
+
+    PRIMARY KEY (workspace_id, url),
+    FOREIGN KEY (workspace_id)
+        REFERENCES workspace(id)
+        ON DELETE CASCADE
+);
+
+CREATE TABLE bookmark (
+    id CHAR(36) NOT NULL PRIMARY KEY,
+    -- NULLなら、全てのワークスペースで使う。
+    workspace_id CHAR(36),
+    url TEXT NOT NULL,
+    title TEXT NOT NULL,
+    created_at INTEGER NOT NULL,
+
+    FOREIGN KEY (workspace_id)
+        REFERENCES workspace(id)
+        ON DELETE CASCADE
+);
+
+CREATE INDEX idx_bookmark_workspace ON bookmark(workspace_id);
@@ -0,0 +1,22 @@
+CREATE TABLE search_engine (
+    id CHAR(36) NOT NULL PRIMARY KEY,
+    name TEXT NOT NULL,
+    keyword TEXT NOT NULL UNIQUE,
+    -- `{searchTerms}`を検索語に置き換える。
+    url_template TEXT NOT NULL,
+    suggest_url_template TEXT,
+    position INTEGER NOT NULL DEFAULT 0
+);
+
+-- ワークスペースの既定の検索エンジン。なければ、並び順で最初のものを使う。
+CREATE TABLE workspace_search_engine (
+    workspace_id CHAR(36) NOT NULL PRIMARY KEY,
+    search_engine_id CHAR(36) NOT NULL,
+
+    FOREIGN KEY (workspace_id)
+        REFERENCES workspace(id)
+        ON DELETE CASCADE,
+    FOREIGN KEY (search_engine_id)
+        REFERENCES search_engine(id)
+        ON DELETE CASCADE
+);
@@ -0,0 +1,139 @@
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
+
+use memex_cef::Highlight;
+
+use crate::{
+    AnnotationMarker, Id, WorkspaceMarker,
+    db::{self, AnnotationData, Database},
+    normalize_url,
+};
+
+/// ページの文章に付けたハイライトと注釈。
+/// ナビゲーションの確定時にすぐ表示できるよう、読み込み済みのワークスペースの分をメモリに持っておく。
+#[derive(Clone, Default)]
+pub struct Annotations {
+    annotations: Rc<RefCell<HashMap<Id<WorkspaceMarker>, Vec<AnnotationData>>>>,
+}
+
+impl Annotations {
+    pub(crate) async fn load(
+        &self,
+        db: &Database,
+        workspace_id: Id<WorkspaceMarker>,
+    ) -> anyhow::Result<()> {
+        let annotations = db::list_annotations(db, workspace_id).await?;
+        self.annotations
+            .borrow_mut()
+            .insert(workspace_id, annotations);
+
+        Ok(())
+    }
+
+    /// ワークスペースの全ての注釈を、URL毎にページ内の順で返す。
+    pub fn list(&self, workspace_id: Id<WorkspaceMarker>) -> Vec<AnnotationData> {
+        self.annotations
+            .borrow()
+            .get(&workspace_id)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// ページの注釈を、ページ内の順で返す。
+    pub fn list_for_url(
+        &self,
+        workspace_id: Id<WorkspaceMarker>,
+        url: &str,
+    ) -> Vec<AnnotationData> {
+        let url = normalize_url(url);
+
+        self.list(workspace_id)
+            .into_iter()
+            .filter(|annotation| annotation.url == url)
+            .collect()
+    }
+
+    pub(crate) fn highlights_for(
+        &self,
+        workspace_id: Id<WorkspaceMarker>,
+        url: &str,
+    ) -> Vec<Highlight> {
+        self.list_for_url(workspace_id, url)
+            .into_iter()
+            .map(|annotation| Highlight {
+                id: annotation.id.to_string(),
+                anchor: annotation.anchor,
+                note: annotation.note,
+            })
+            .collect()
+    }
+
+    pub(crate) async fn add(&self, db: &Database, data: AnnotationData) -> anyhow::Result<()> {
+        db::add_annotation(db, &data).await?;
+
+        let mut annotations = self.annotations.borrow_mut();
+        let annotations = annotations.entry(data.workspace_id).or_default();
+        let index = annotations.partition_point(|annotation| {
+            (&annotation.url, annotation.anchor.start) <= (&data.url, data.anchor.start)
+        });
+        annotations.insert(index, data);
+
+        Ok(())
+    }
+
+    pub(crate) async fn update_note(
+        &self,
+        db: &Database,
+        id: Id<AnnotationMarker>,
+        note: &str,
+    ) -> anyhow::Result<()> {
+        db::update_annotation_note(db, id, note).await?;
+
+        for annotations in self.annotations.borrow_mut().values_mut() {
+            if let Some(annotation) = annotations
+                .iter_mut()
+                .find(|annotation| annotation.id == id)
+            {
+                annotation.note = note.to_owned();
+            }
+        }
+
+        Ok(())
+    }
+
+    pub(crate) async fn remove(
+        &self,
+        db: &Database,
+        id: Id<AnnotationMarker>,
+    ) -> anyhow::Result<()> {
+        db::remove_annotation(db, id).await?;
+
+        for annotations in self.annotations.borrow_mut().values_mut() {
+            annotations.retain(|annotation| annotation.id != id);
+        }
+
+        Ok(())
+    }
+}
+
+/// 注釈をページ毎にまとめたMarkdownの文書を作る。
+pub fn annotations_to_markdown(title: &str, annotations: &[AnnotationData]) -> String {
+    let mut markdown = format!("# {title}\n");
+    let mut current_url = None;
+
+    for annotation in annotations {
+        if current_url != Some(&annotation.url) {
+            markdown.push_str(&format!("\n## <{}>\n", annotation.url));
+            current_url = Some(&annotation.url);
+        }
+
+        markdown.push('\n');
+        for line in annotation.anchor.quote.lines() {
+            markdown.push_str(&format!("> {}\n", line.trim()));
+        }
+        if !annotation.note.is_empty() {
+            markdown.push_str(&format!("\n{}\n", annotation.note));
+        }
+    }
+
+    markdown
+}
@@ -1,10 +1,40 @@
-use std::{cell::Cell, collections::HashMap, rc::Rc};
+use std::{
+    cell::Cell,
+    collections::HashMap,
+    path::{Path, PathBuf},
+    pin::Pin,
+    rc::Rc,
+    time::{Instant, SystemTime, UNIX_EPOCH},
+};
 
 use anyhow::Context;
-use memex_cef::Profile;
+use memex_cef::{
+    BeforeUnloadRequest, FindResult, JsDialogRequest, PermissionKind, Profile, UIThreadMarker,
+};
 use raw_window_handle::RawWindowHandle;
 
-use crate::{Id, TabMarker, Workspace, WorkspaceMarker, db::Database, fs::DataContext};
+use crate::{
+    Annotations, BookmarkMarker, FaviconFetch, History, Id, IpcRouter, LetterAvatar, OmniboxInput,
+    OmniboxResult, OmniboxSources, OpenTabEntry, PaletteItem, PaletteTarget, SearchEngineMarker,
+    SitePermissionPrompt, SiteVisits, Tab, TabCrash, TabGroupMarker, TabLifecyclePolicy, TabMarker,
+    UserScripts, UserStyles, Workspace, WorkspaceMarker, ZoomLevels,
+    db::{
+        self, BookmarkData, Database, SearchEngineData, SitePermissionData, TabData, TabGroupData,
+        TabLocationData, WorkspaceData, WorkspaceIconData,
+    },
+    favicon::{cached_favicon, favicon_data_url},
+    fs::{DataContext, FileSystemItem},
+    internal_page::register_builtin_channels,
+    lifecycle::{LiveTab, select_tabs_to_discard},
+    omnibox_suggestions, omnibox_terms, parse_omnibox_input,
+    reader::remove_reader_view,
+    search_engine::{
+        SEARCH_SUGGESTIONS_MAX_BYTES, SearchEngines, normalize_search_engine,
+        parse_open_search_description, parse_search_suggestions,
+    },
+    thumbnail::remove_thumbnail,
+    workspace::normalize_workspace_name,
+};
 
 pub use memex_cef::WebViewBounds;
 
@@ -15,6 +45,14 @@ pub struct BrowserContext {
     pub(crate) delegate: SharedBrowserDelegate,
     pub(crate) profile: Profile,
     pub(crate) window_handle: RawWindowHandle,
+    pub(crate) zoom_levels: ZoomLevels,
+    pub(crate) ipc_router: IpcRouter,
+    pub(crate) user_scripts: UserScripts,
+    pub(crate) user_styles: UserStyles,
+    pub(crate) annotations: Annotations,
+    pub(crate) site_visits: SiteVisits,
+    pub(crate) history: History,
+    pub(crate) search_engines: SearchEngines,
     pub rect: Rc<Cell<WebViewBounds>>,
 }
 
@@ -29,29 +67,59 @@ impl BrowserContext {
         let profile_path = data.chromium_data_dir();
         let profile = Profile::new(&profile_path).context("プロファイルの作成に失敗しました。")?;
 
+        let ipc_router = IpcRouter::default();
+        register_builtin_channels(&ipc_router);
+
         Ok(Self {
             data,
             db,
             delegate: Rc::new(delegate),
             profile,
             window_handle,
+            zoom_levels: ZoomLevels::default(),
+            ipc_router,
+            user_scripts: UserScripts::default(),
+            user_styles: UserStyles::default(),
+            annotations: Annotations::default(),
+            site_visits: SiteVisits::default(),
+            history: History::default(),
+            search_engines: SearchEngines::default(),
             rect: Rc::new(Cell::new(bounds)),
         })
     }
+
+    /// 非同期の処理を、アプリの実行環境で実行させる。
+    pub(crate) fn spawn(&self, future: impl Future<Output = ()> + 'static) {
+        self.delegate.spawn(Box::pin(future));
+    }
 }
 
 pub struct Browser {
     context: BrowserContext,
     workspaces: HashMap<Id<WorkspaceMarker>, Workspace>,
+    workspace_order: Vec<Id<WorkspaceMarker>>,
     selected_workspace: Id<WorkspaceMarker>,
+    tab_lifecycle_policy: TabLifecyclePolicy,
+    /// 選択された順のワークスペース。最後が最も新しい。
+    recently_selected: Vec<Id<WorkspaceMarker>>,
+    /// 読み込んだままにするワークスペースの数の上限。
+    max_loaded_workspaces: usize,
+    /// 読み込み中のワークスペース。`select`のFutureがドロップされた場合に、ここに残る。
+    loading_workspace: Option<Id<WorkspaceMarker>>,
 }
 
 impl Browser {
-    pub(crate) fn new(context: BrowserContext) -> anyhow::Result<Self> {
+    /// ワークスペースを持たないブラウザを作る。[`Browser::restore_workspaces`]で保存済みのものを読み込むこと。
+    pub fn new(context: BrowserContext) -> anyhow::Result<Self> {
         Ok(Self {
             context,
             workspaces: HashMap::new(),
+            workspace_order: Vec::new(),
             selected_workspace: Id::home(),
+            tab_lifecycle_policy: TabLifecyclePolicy::default(),
+            recently_selected: Vec::new(),
+            max_loaded_workspaces: 3,
+            loading_workspace: None,
         })
     }
 
@@ -63,34 +131,955 @@ impl Browser {
         }
     }
 
-    pub async fn select(&mut self, id: Id<WorkspaceMarker>) -> anyhow::Result<()> {
-        if let Some(workspace) = self.workspaces.get_mut(&id) {
-            self.selected_workspace = id;
+    /// ワークスペースを読み込まれていなければ読み込み、読み込めたら選択する。
+    /// 読み込みに失敗した場合は、選択中のワークスペースは変わらない。
+    /// 読み込み中に別のワークスペースが選択されて前の`select`のFutureがドロップされた場合は、
+    /// 途中まで読み込んだワークスペースを読み込み前の状態に戻す。
+    /// 読み込んだワークスペースが上限を超えたら、最も長く選択されていないものから閉じる。
+    pub async fn select(
+        &mut self,
+        id: Id<WorkspaceMarker>,
+        utm: UIThreadMarker,
+    ) -> anyhow::Result<()> {
+        anyhow::ensure!(
+            self.workspaces.contains_key(&id),
+            "そのワークスペースは存在しません。"
+        );
 
-            if !workspace.is_loaded() && !workspace.is_loading() {
-                workspace
-                    .load()
-                    .await
-                    .context("ワークスペースの読み込みに失敗しました。")?;
-            }
-        } else {
+        if let Some(loading_id) = self.loading_workspace.take()
+            && let Some(workspace) = self.workspaces.get_mut(&loading_id)
+        {
+            workspace.unload(utm)?;
+        }
+
+        let Some(workspace) = self.workspaces.get_mut(&id) else {
             anyhow::bail!("そのワークスペースは存在しません。");
+        };
+
+        if !workspace.is_loaded() {
+            self.loading_workspace = Some(id);
+            let result = workspace.load().await;
+            self.loading_workspace = None;
+
+            if let Err(error) = result {
+                workspace.unload(utm)?;
+                return Err(error.context("ワークスペースの読み込みに失敗しました。"));
+            }
+        }
+
+        self.selected_workspace = id;
+        self.context.delegate.on_workspace_select(id);
+        self.recently_selected.retain(|recent| *recent != id);
+        self.recently_selected.push(id);
+
+        self.unload_least_recently_selected(utm)
+    }
+
+    pub fn selected_workspace(&self) -> Id<WorkspaceMarker> {
+        self.selected_workspace
+    }
+
+    pub fn workspace_order(&self) -> &[Id<WorkspaceMarker>] {
+        &self.workspace_order
+    }
+
+    pub fn workspace(&self, id: Id<WorkspaceMarker>) -> Option<&Workspace> {
+        self.workspaces.get(&id)
+    }
+
+    pub fn workspace_mut(&mut self, id: Id<WorkspaceMarker>) -> anyhow::Result<&mut Workspace> {
+        self.workspaces
+            .get_mut(&id)
+            .context("そのワークスペースは存在しません。")
+    }
+
+    /// 読み込み済みのワークスペースから、タブを探す。
+    pub fn tab(&self, id: Id<TabMarker>) -> Option<&Tab> {
+        self.workspaces
+            .values()
+            .find_map(|workspace| workspace.tabs().get(&id))
+    }
+
+    /// 保存されているワークスペースを全て、並び順に加える。タブの`WebView`はまだ作らない。
+    /// タブのホームページに使うので、検索エンジンも読み込む。
+    pub async fn restore_workspaces(&mut self) -> anyhow::Result<()> {
+        self.context
+            .search_engines
+            .load(&self.context.db)
+            .await
+            .context("検索エンジンの読み込みに失敗しました。")?;
+
+        for data in db::list_workspaces(&self.context.db).await? {
+            let workspace = Workspace::new(self.context.clone(), data)?;
+            for tab in workspace.tabs().values() {
+                if let Err(error) = tab.restore_favicon().await {
+                    log::warn!("ファビコンの読み込みに失敗しました：{error:#}");
+                }
+                if let Err(error) = tab.restore_archive().await {
+                    log::warn!("アーカイブの情報の読み込みに失敗しました：{error:#}");
+                }
+            }
+            self.add_workspace(workspace);
+        }
+
+        Ok(())
+    }
+
+    /// 空のワークスペースを作り、末尾に加える。
+    pub async fn create_workspace(
+        &mut self,
+        name: &str,
+        icon: WorkspaceIconData,
+    ) -> anyhow::Result<Id<WorkspaceMarker>> {
+        anyhow::ensure!(
+            !matches!(icon, WorkspaceIconData::Home),
+            "ホームのアイコンは使えません。"
+        );
+
+        let data = WorkspaceData {
+            id: Id::default(),
+            name: normalize_workspace_name(name)?,
+            icon,
+            icon_images: Vec::new(),
+            tabs: Vec::new(),
+            selected_tab: None,
+            tab_groups: Vec::new(),
+        };
+        let id = data.id;
+
+        let mut workspace_order = self.workspace_order.clone();
+        workspace_order.push(id);
+        db::add_workspace(&self.context.db, &data, &workspace_order).await?;
+
+        self.workspaces
+            .insert(id, Workspace::new(self.context.clone(), data)?);
+        self.workspace_order = workspace_order;
+
+        self.context.delegate.on_workspace_create(id);
+        self.notify_workspace_order_change();
+
+        Ok(id)
+    }
+
+    /// ワークスペースを、タブ・タブグループ・注釈などと共に削除する。ホームは削除できない。
+    /// 選択中のワークスペースであれば、先にホームを選択する。
+    /// ワークスペースのディレクトリのファイルは、ユーザーのものなので残す。
+    pub async fn delete_workspace(
+        &mut self,
+        id: Id<WorkspaceMarker>,
+        utm: UIThreadMarker,
+    ) -> anyhow::Result<()> {
+        anyhow::ensure!(id != Id::home(), "ホームのワークスペースは削除できません。");
+        anyhow::ensure!(
+            self.workspaces.contains_key(&id),
+            "そのワークスペースは存在しません。"
+        );
+
+        if self.selected_workspace == id {
+            self.select(Id::home(), utm).await?;
+        }
+
+        let mut workspace_order = self.workspace_order.clone();
+        workspace_order.retain(|workspace_id| *workspace_id != id);
+        db::remove_workspace(&self.context.db, id, &workspace_order).await?;
+
+        if let Some(mut workspace) = self.workspaces.remove(&id) {
+            workspace.unload(utm)?;
+            for tab_id in workspace.tabs.keys() {
+                remove_thumbnail(&self.context.data, *tab_id).await;
+                remove_reader_view(&self.context.data, *tab_id).await;
+            }
+        }
+        self.workspace_order = workspace_order;
+        self.recently_selected.retain(|recent| *recent != id);
+        if self.loading_workspace == Some(id) {
+            self.loading_workspace = None;
         }
 
+        self.context.delegate.on_workspace_delete(id);
+        self.notify_workspace_order_change();
+
+        Ok(())
+    }
+
+    /// ワークスペースを`index`の位置へ移す。
+    pub async fn move_workspace(
+        &mut self,
+        id: Id<WorkspaceMarker>,
+        index: usize,
+    ) -> anyhow::Result<()> {
+        anyhow::ensure!(
+            self.workspaces.contains_key(&id),
+            "そのワークスペースは存在しません。"
+        );
+
+        let mut workspace_order = self.workspace_order.clone();
+        workspace_order.retain(|workspace_id| *workspace_id != id);
+        let index = index.min(workspace_order.len());
+        workspace_order.insert(index, id);
+
+        db::update_workspace_positions(&self.context.db, &workspace_order).await?;
+        self.workspace_order = workspace_order;
+        self.notify_workspace_order_change();
+
         Ok(())
     }
 
+    fn notify_workspace_order_change(&self) {
+        self.context
+            .delegate
+            .on_workspace_order_change(self.workspace_order.clone());
+    }
+
+    /// 読み込んだままにするワークスペースの数の上限を設定する。`1`未満にはできない。
+    pub fn set_max_loaded_workspaces(
+        &mut self,
+        max: usize,
+        utm: UIThreadMarker,
+    ) -> anyhow::Result<()> {
+        self.max_loaded_workspaces = max.max(1);
+
+        self.unload_least_recently_selected(utm)
+    }
+
+    fn unload_least_recently_selected(&mut self, utm: UIThreadMarker) -> anyhow::Result<()> {
+        let loaded = self
+            .recently_selected
+            .iter()
+            .copied()
+            .filter(|id| {
+                self.workspaces
+                    .get(id)
+                    .is_some_and(|workspace| workspace.is_loaded())
+            })
+            .collect::<Vec<_>>();
+        let excess = loaded.len().saturating_sub(self.max_loaded_workspaces);
+
+        for id in loaded.into_iter().take(excess) {
+            if id == self.selected_workspace {
+                continue;
+            }
+            if let Some(workspace) = self.workspaces.get_mut(&id) {
+                workspace.unload(utm)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// 読み込み済みのワークスペースのCSSのファイルが変更されていれば読み込み直し、表示中のページにも適用する。
+    /// ファイルの変更は通知されないので、定期的に呼び出すこと。
+    pub async fn refresh_user_styles(&self) {
+        for workspace in self
+            .workspaces
+            .values()
+            .filter(|workspace| workspace.is_loaded())
+        {
+            if let Err(error) = workspace.refresh_user_styles().await {
+                log::warn!(
+                    "ワークスペース「{}」のCSSの読み込みに失敗しました：{error:#}",
+                    workspace.name()
+                );
+            }
+        }
+    }
+
+    pub fn set_tab_lifecycle_policy(&mut self, policy: TabLifecyclePolicy) {
+        self.tab_lifecycle_policy = policy;
+    }
+
+    /// 条件に従って、使われていないタブを破棄し、破棄したタブを返す。
+    /// 表示中のタブ、固定されたタブ、音声を再生中のタブは破棄しない。定期的に呼び出すこと。
+    pub async fn discard_inactive_tabs(
+        &mut self,
+        utm: UIThreadMarker,
+    ) -> anyhow::Result<Vec<Id<TabMarker>>> {
+        let policy = self.tab_lifecycle_policy;
+        let visible_tab = self
+            .workspaces
+            .get(&self.selected_workspace)
+            .and_then(Workspace::selected_tab);
+
+        let mut live_tabs = Vec::new();
+
+        for workspace in self.workspaces.values() {
+            for tab in workspace.tabs().values() {
+                if !tab.is_loaded() {
+                    continue;
+                }
+
+                let is_visible = Some(tab.id()) == visible_tab;
+                // 破棄しないと決まっているタブは、再生状態を調べない。
+                let is_playing_audio = if is_visible || tab.is_pinned() {
+                    false
+                } else {
+                    tab.is_playing_audio().await.unwrap_or_else(|error| {
+                        // 判定できないタブは、再生中かもしれないので残しておく。
+                        log::warn!("音声の再生状態の取得に失敗しました：{error:#}");
+                        true
+                    })
+                };
+
+                live_tabs.push(LiveTab {
+                    workspace_id: workspace.id(),
+                    tab_id: tab.id(),
+                    last_active: tab.last_active(),
+                    is_visible,
+                    is_pinned: tab.is_pinned(),
+                    is_playing_audio,
+                });
+            }
+        }
+
+        let mut discarded = Vec::new();
+
+        for (workspace_id, tab_id) in select_tabs_to_discard(&live_tabs, policy, Instant::now()) {
+            let Some(tab) = self
+                .workspaces
+                .get_mut(&workspace_id)
+                .and_then(|workspace| workspace.tabs.get_mut(&tab_id))
+            else {
+                continue;
+            };
+
+            tab.discard(utm)?;
+            discarded.push(tab_id);
+        }
+
+        Ok(discarded)
+    }
+
+    /// タブを他のワークスペースの`index`の位置へ移す。
+    /// 移した先のワークスペースが読み込まれていなければ、タブを破棄する。
+    pub async fn move_tab(
+        &mut self,
+        id: Id<TabMarker>,
+        to: Id<WorkspaceMarker>,
+        index: usize,
+        utm: UIThreadMarker,
+    ) -> anyhow::Result<()> {
+        let from = self.workspace_of(id)?;
+        anyhow::ensure!(
+            self.workspaces.contains_key(&to),
+            "移動先のワークスペースは存在しません。"
+        );
+        if from == to {
+            return self.workspace_mut(to)?.move_tab(id, index).await;
+        }
+
+        let db = self.context.db.clone();
+
+        // 保存する並びを作るために先に移し、保存に失敗したら元に戻す。
+        let source = self.workspace_mut(from)?;
+        let source_arrangement = source.tab_arrangement();
+        let source_selection = source.selected_tab();
+        let mut tab = source.detach_tab(id)?;
+        let source_layout = source.tab_layout();
+        let group_id = tab.group_id();
+        tab.set_group_id(None);
+
+        let target = self.workspace_mut(to)?;
+        let target_arrangement = target.tab_arrangement();
+        let target_selection = target.selected_tab();
+        target.attach_tab(tab, index);
+        let target_layout = target.tab_layout();
+
+        if let Err(error) =
+            db::move_tab_to_workspace(&db, id, to, &source_layout, &target_layout).await
+        {
+            let target = self.workspace_mut(to)?;
+            let mut tab = target
+                .tabs
+                .remove(&id)
+                .context("そのタブは存在しません。")?;
+            target.restore_tab_arrangement(target_arrangement);
+            tab.set_group_id(group_id);
+
+            let source = self.workspace_mut(from)?;
+            source.tabs.insert(id, tab);
+            source.restore_tab_arrangement(source_arrangement);
+
+            return Err(error);
+        }
+
+        let target = self.workspace_mut(to)?;
+        let is_target_loaded = target.is_loaded();
+        let tab = target
+            .tabs
+            .get_mut(&id)
+            .context("そのタブは存在しません。")?;
+        tab.set_workspace(to)?;
+        // 全てのタブは同じウィンドウに`WebView`を置いているので、読み込まれていればそのまま使える。
+        if !is_target_loaded {
+            tab.discard(utm)?;
+        }
+
+        self.context.delegate.on_tab_move(id, from, to);
+        for (workspace_id, previous_selection) in [(from, source_selection), (to, target_selection)]
+        {
+            let workspace = self.workspace_mut(workspace_id)?;
+            workspace.notify_tab_order_change();
+            workspace.sync_selection(previous_selection).await?;
+        }
+
+        Ok(())
+    }
+
+    /// タブの場所を複製した新しいタブを、他のワークスペースの`index`の位置に作る。
+    /// 新しいタブは、選択されるまで読み込まない。
+    pub async fn copy_tab(
+        &mut self,
+        id: Id<TabMarker>,
+        to: Id<WorkspaceMarker>,
+        index: usize,
+    ) -> anyhow::Result<Id<TabMarker>> {
+        let from = self.workspace_of(id)?;
+        let location = self
+            .workspace_mut(from)?
+            .tabs()
+            .get(&id)
+            .context("そのタブは存在しません。")?
+            .location();
+
+        let data = TabData {
+            id: Id::default(),
+            location,
+            is_pinned: false,
+            group_id: None,
+        };
+        let tab = Tab::new(self.context.clone(), to, data)?;
+        let new_id = tab.id();
+
+        self.workspace_mut(to)?.add_tab(tab, index, false).await?;
+
+        Ok(new_id)
+    }
+
+    fn workspace_of(&self, tab_id: Id<TabMarker>) -> anyhow::Result<Id<WorkspaceMarker>> {
+        self.workspaces
+            .values()
+            .find(|workspace| workspace.tabs().contains_key(&tab_id))
+            .map(Workspace::id)
+            .context("そのタブは存在しません。")
+    }
+
+    /// ページの`window.memex.invoke`を処理するハンドラの登録先。
+    pub fn ipc_router(&self) -> &IpcRouter {
+        &self.context.ipc_router
+    }
+
     pub fn add_workspace(&mut self, workspace: Workspace) {
-        self.workspaces.insert(workspace.id(), workspace);
+        let id = workspace.id();
+        if self.workspaces.insert(id, workspace).is_none() {
+            self.workspace_order.push(id);
+        }
+    }
+
+    /// ページのファビコンのキャッシュのパス。まだ取得していなければ`None`を返す。
+    pub async fn favicon_for(&self, url: &str) -> anyhow::Result<Option<PathBuf>> {
+        cached_favicon(&self.context.data, &self.context.db, url).await
+    }
+
+    /// 内部ページに埋め込む、ページのアイコンの`data:`のURL。
+    /// ファビコンがなければ、頭文字のアバターを返す。
+    pub async fn page_icon_data_url(&self, url: &str, title: &str) -> String {
+        let favicon = match self.favicon_for(url).await {
+            Ok(favicon) => favicon,
+            Err(error) => {
+                log::warn!("ファビコンの読み込みに失敗しました：{error:#}");
+                None
+            }
+        };
+
+        if let Some(path) = favicon {
+            match favicon_data_url(&path).await {
+                Ok(data_url) => return data_url,
+                Err(error) => log::warn!("{error:#}"),
+            }
+        }
+
+        LetterAvatar::new(url, title).to_data_url(16)
+    }
+
+    /// 溜まっているサイトの訪問回数と閲覧履歴を保存する。定期的に呼び出すこと。
+    pub async fn save_site_visits(&self) -> anyhow::Result<()> {
+        let site_visits = self.context.site_visits.flush(&self.context.db).await;
+        let history = self.context.history.flush(&self.context.db).await;
+
+        site_visits.and(history)
+    }
+
+    /// オムニボックスの入力に対する候補。
+    /// 選択中のワークスペースの閲覧履歴・ブックマーク・ファイルと、全てのワークスペースで開いているタブから探す。
+    pub async fn omnibox_suggestions(&self, text: &str) -> anyhow::Result<OmniboxResult> {
+        let db = &self.context.db;
+        let workspace_id = self.selected_workspace;
+
+        // 直前の訪問も候補に出せるよう、先に保存する。
+        if let Err(error) = self.context.history.flush(db).await {
+            log::warn!("閲覧履歴の保存に失敗しました：{error:#}");
+        }
+
+        // データベースでは最も長い語で絞り込み、残りの語は候補を作る時に比べる。
+        let engines = self.context.search_engines.list();
+        let default_engine = self.context.search_engines.default_for(workspace_id);
+        let terms = omnibox_terms(text, &engines);
+        let history = match terms.iter().max_by_key(|term| term.len()) {
+            Some(term) => db::search_history(db, workspace_id, term, 50).await?,
+            None => Vec::new(),
+        };
+
+        let open_tabs = self
+            .workspace_order
+            .iter()
+            .filter_map(|id| self.workspaces.get(id))
+            .flat_map(|workspace| {
+                workspace.tab_order().iter().filter_map(|tab_id| {
+                    let tab = workspace.tabs().get(tab_id)?;
+                    let TabLocationData::WebPage { url } = tab.location() else {
+                        return None;
+                    };
+
+                    Some(OpenTabEntry {
+                        workspace_id: workspace.id(),
+                        tab_id: *tab_id,
+                        title: tab.title(),
+                        url,
+                    })
+                })
+            })
+            .collect();
+
+        let mut files = Vec::new();
+        if let Some(workspace) = self.workspaces.get(&workspace_id) {
+            collect_files(workspace.files(), &mut files);
+        }
+
+        let sources = OmniboxSources {
+            history,
+            bookmarks: db::list_bookmarks(db, workspace_id).await?,
+            open_tabs,
+            files,
+        };
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .context("現在時刻の取得に失敗しました。")?
+            .as_secs() as i64;
+
+        Ok(omnibox_suggestions(
+            text,
+            &engines,
+            default_engine.as_ref(),
+            &sources,
+            now,
+        ))
+    }
+
+    /// 選択中のワークスペースの既定の検索エンジンから、検索候補を取得する。
+    /// 検索エンジンのキーワードで始まる入力では、その検索エンジンを使う。
+    pub async fn search_suggestions(&self, text: &str) -> anyhow::Result<Vec<String>> {
+        let engines = self.context.search_engines.list();
+        let (engine, query) = match parse_omnibox_input(text, &engines) {
+            Some(OmniboxInput::Search {
+                keyword: Some(keyword),
+                query,
+            }) => (
+                engines.into_iter().find(|engine| engine.keyword == keyword),
+                query,
+            ),
+            Some(OmniboxInput::Search {
+                keyword: None,
+                query,
+            }) => (
+                self.context
+                    .search_engines
+                    .default_for(self.selected_workspace),
+                query,
+            ),
+            _ => return Ok(Vec::new()),
+        };
+
+        let Some(url) = engine.and_then(|engine| engine.suggest_url(&query)) else {
+            return Ok(Vec::new());
+        };
+        let resource = self
+            .context
+            .profile
+            .download(&url, SEARCH_SUGGESTIONS_MAX_BYTES)
+            .await?;
+
+        parse_search_suggestions(&resource.data)
+    }
+
+    /// 検索エンジンを並び順に全て返す。
+    pub fn search_engines(&self) -> Vec<SearchEngineData> {
+        self.context.search_engines.list()
+    }
+
+    /// ワークスペースで使う既定の検索エンジン。
+    pub fn default_search_engine(
+        &self,
+        workspace_id: Id<WorkspaceMarker>,
+    ) -> Option<SearchEngineData> {
+        self.context.search_engines.default_for(workspace_id)
+    }
+
+    /// ワークスペース毎に設定した既定の検索エンジン。
+    /// `None`なら、並び順で最初の検索エンジンを使っている。
+    pub fn workspace_search_engine(
+        &self,
+        workspace_id: Id<WorkspaceMarker>,
+    ) -> Option<Id<SearchEngineMarker>> {
+        self.context.search_engines.workspace_default(workspace_id)
+    }
+
+    /// ワークスペースの既定の検索エンジンを設定する。
+    /// `search_engine_id`が`None`なら、並び順で最初の検索エンジンを使うよう戻す。
+    pub async fn set_workspace_search_engine(
+        &self,
+        workspace_id: Id<WorkspaceMarker>,
+        search_engine_id: Option<Id<SearchEngineMarker>>,
+    ) -> anyhow::Result<()> {
+        anyhow::ensure!(
+            self.workspaces.contains_key(&workspace_id),
+            "そのワークスペースは存在しません。"
+        );
+        if let Some(id) = search_engine_id {
+            self.context
+                .search_engines
+                .get(id)
+                .context("その検索エンジンは存在しません。")?;
+        }
+
+        db::set_workspace_search_engine(&self.context.db, workspace_id, search_engine_id).await?;
+        self.context.search_engines.load(&self.context.db).await
+    }
+
+    /// 検索エンジンを末尾に加える。キーワードは他の検索エンジンと重複できない。
+    pub async fn add_search_engine(
+        &self,
+        name: &str,
+        keyword: &str,
+        url_template: &str,
+        suggest_url_template: Option<&str>,
+    ) -> anyhow::Result<Id<SearchEngineMarker>> {
+        let data = normalize_search_engine(SearchEngineData {
+            id: Id::default(),
+            name: name.to_owned(),
+            keyword: keyword.to_owned(),
+            url_template: url_template.to_owned(),
+            suggest_url_template: suggest_url_template.map(ToOwned::to_owned),
+        })?;
+        self.ensure_unique_keyword(&data)?;
+
+        let mut order = self.context.search_engines.order();
+        order.push(data.id);
+        db::add_search_engine(&self.context.db, &data, &order).await?;
+        self.context.search_engines.load(&self.context.db).await?;
+
+        Ok(data.id)
+    }
+
+    /// OpenSearch description XMLのファイルから、検索エンジンを加える。
+    pub async fn import_search_engine(
+        &self,
+        path: &Path,
+        keyword: &str,
+    ) -> anyhow::Result<Id<SearchEngineMarker>> {
+        let xml = async_fs::read_to_string(path)
+            .await
+            .with_context(|| format!("ファイルの読み込みに失敗しました：{}", path.display()))?;
+        let description = parse_open_search_description(&xml)?;
+
+        self.add_search_engine(
+            &description.name,
+            keyword,
+            &description.url_template,
+            description.suggest_url_template.as_deref(),
+        )
+        .await
+    }
+
+    pub async fn update_search_engine(&self, data: SearchEngineData) -> anyhow::Result<()> {
+        self.context
+            .search_engines
+            .get(data.id)
+            .context("その検索エンジンは存在しません。")?;
+        let data = normalize_search_engine(data)?;
+        self.ensure_unique_keyword(&data)?;
+
+        db::update_search_engine(&self.context.db, &data).await?;
+        self.context.search_engines.load(&self.context.db).await
+    }
+
+    /// 検索エンジンを削除する。最後の1つは削除できない。
+    /// 既定にしていたワークスペースは、並び順で最初の検索エンジンを使うようになる。
+    pub async fn remove_search_engine(&self, id: Id<SearchEngineMarker>) -> anyhow::Result<()> {
+        let mut order = self.context.search_engines.order();
+        anyhow::ensure!(order.contains(&id), "その検索エンジンは存在しません。");
+        anyhow::ensure!(order.len() > 1, "最後の検索エンジンは削除できません。");
+
+        order.retain(|engine_id| *engine_id != id);
+        db::remove_search_engine(&self.context.db, id, &order).await?;
+        self.context.search_engines.load(&self.context.db).await
+    }
+
+    /// 検索エンジンを並び替える。先頭のものを、既定を設定していないワークスペースで使う。
+    pub async fn move_search_engine(
+        &self,
+        id: Id<SearchEngineMarker>,
+        index: usize,
+    ) -> anyhow::Result<()> {
+        let mut order = self.context.search_engines.order();
+        let current = order
+            .iter()
+            .position(|engine_id| *engine_id == id)
+            .context("その検索エンジンは存在しません。")?;
+
+        order.remove(current);
+        order.insert(index.min(order.len()), id);
+        db::update_search_engine_positions(&self.context.db, &order).await?;
+        self.context.search_engines.load(&self.context.db).await
+    }
+
+    fn ensure_unique_keyword(&self, data: &SearchEngineData) -> anyhow::Result<()> {
+        let is_duplicated = self
+            .context
+            .search_engines
+            .list()
+            .iter()
+            .any(|engine| engine.id != data.id && engine.keyword == data.keyword);
+        anyhow::ensure!(
+            !is_duplicated,
+            "そのキーワードは他の検索エンジンで使われています：{}",
+            data.keyword
+        );
+
+        Ok(())
+    }
+
+    /// ワークスペースで使うブックマーク。全てのワークスペースで使うものも含む。
+    pub async fn bookmarks(
+        &self,
+        workspace_id: Id<WorkspaceMarker>,
+    ) -> anyhow::Result<Vec<BookmarkData>> {
+        db::list_bookmarks(&self.context.db, workspace_id).await
+    }
+
+    /// ブックマークを加える。`workspace_id`が`None`なら、全てのワークスペースで使う。
+    pub async fn add_bookmark(
+        &self,
+        workspace_id: Option<Id<WorkspaceMarker>>,
+        url: &str,
+        title: &str,
+    ) -> anyhow::Result<Id<BookmarkMarker>> {
+        let data = BookmarkData {
+            id: Id::default(),
+            workspace_id,
+            url: url.to_owned(),
+            title: title.trim().to_owned(),
+            created_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .context("現在時刻の取得に失敗しました。")?
+                .as_secs() as i64,
+        };
+        db::add_bookmark(&self.context.db, &data).await?;
+
+        Ok(data.id)
+    }
+
+    pub async fn remove_bookmark(&self, id: Id<BookmarkMarker>) -> anyhow::Result<()> {
+        db::remove_bookmark(&self.context.db, id).await
+    }
+
+    /// コマンドパレットに出す項目。
+    /// 全てのワークスペースとそのタブ、選択中のワークスペースのブックマークとファイルを並べる。
+    /// アプリのコマンドは含まないので、呼び出し側で加える。
+    pub async fn palette_items(&self) -> anyhow::Result<Vec<PaletteItem>> {
+        let workspaces = self
+            .workspace_order
+            .iter()
+            .filter_map(|id| self.workspaces.get(id))
+            .collect::<Vec<_>>();
+
+        let mut items = workspaces
+            .iter()
+            .flat_map(|workspace| {
+                workspace.tab_order().iter().filter_map(|tab_id| {
+                    let tab = workspace.tabs().get(tab_id)?;
+                    let detail = match tab.location() {
+                        TabLocationData::WebPage { url } => url,
+                        _ => workspace.name().to_owned(),
+                    };
+
+                    Some(PaletteItem {
+                        target: PaletteTarget::Tab {
+                            workspace_id: workspace.id(),
+                            tab_id: *tab_id,
+                        },
+                        title: tab.title(),
+                        detail,
+                    })
+                })
+            })
+            .collect::<Vec<_>>();
+
+        items.extend(workspaces.iter().map(|workspace| PaletteItem {
+            target: PaletteTarget::Workspace(workspace.id()),
+            title: workspace.name().to_owned(),
+            detail: format!("{}個のタブ", workspace.tab_order().len()),
+        }));
+
+        items.extend(
+            db::list_bookmarks(&self.context.db, self.selected_workspace)
+                .await?
+                .into_iter()
+                .map(|bookmark| PaletteItem {
+                    title: if bookmark.title.is_empty() {
+                        bookmark.url.clone()
+                    } else {
+                        bookmark.title
+                    },
+                    detail: bookmark.url.clone(),
+                    target: PaletteTarget::Bookmark { url: bookmark.url },
+                }),
+        );
+
+        if let Some(workspace) = self.workspaces.get(&self.selected_workspace) {
+            let mut files = Vec::new();
+            collect_files(workspace.files(), &mut files);
+            items.extend(files.into_iter().map(|path| {
+                PaletteItem {
+                    title: path
+                        .file_name()
+                        .map(|name| name.to_string_lossy().into_owned())
+                        .unwrap_or_default(),
+                    detail: path.display().to_string(),
+                    target: PaletteTarget::File(path),
+                }
+            }));
+        }
+
+        Ok(items)
+    }
+
+    /// 保存されているサイト毎の権限の判断を全て取得する。
+    pub async fn site_permissions(&self) -> anyhow::Result<Vec<SitePermissionData>> {
+        db::list_site_permissions(&self.context.db).await
+    }
+
+    /// 保存されているサイトの権限の判断を取り消す。
+    /// `workspace_id`が`None`なら、全てのワークスペースに適用されている判断を取り消す。
+    pub async fn revoke_site_permission(
+        &self,
+        origin: &str,
+        workspace_id: Option<Id<WorkspaceMarker>>,
+        permission: PermissionKind,
+    ) -> anyhow::Result<()> {
+        db::revoke_site_permission(&self.context.db, origin, workspace_id, permission).await
+    }
+
+    /// サイトに対する権限の判断を、ワークスペースや権限の種類を問わずに全て取り消す。
+    pub async fn revoke_site_permissions(&self, origin: &str) -> anyhow::Result<()> {
+        db::revoke_site_permissions(&self.context.db, origin).await
     }
 }
 
 pub type SharedBrowserDelegate = Rc<dyn BrowserDelegate>;
 
+/// UIスレッドで実行する非同期の処理。
+pub type LocalTask = Pin<Box<dyn Future<Output = ()>>>;
+
 pub trait BrowserDelegate {
+    /// memex-coreの非同期の処理を、UIスレッドで実行する。
+    /// CEFの通知から同期的に呼ばれるので、`task`はその場で実行せず、後で実行するよう予約すること。
+    fn spawn(&self, task: LocalTask);
+
     fn on_workspace_loading_start(&self, id: Id<WorkspaceMarker>);
 
     fn on_workspace_load(&self, id: Id<WorkspaceMarker>);
 
+    /// ワークスペースが閉じられ、タブの`WebView`とファイルの一覧が破棄された。
+    fn on_workspace_unload(&self, id: Id<WorkspaceMarker>);
+
+    fn on_workspace_create(&self, id: Id<WorkspaceMarker>);
+
+    fn on_workspace_rename(&self, id: Id<WorkspaceMarker>, name: String);
+
+    fn on_workspace_icon_change(&self, id: Id<WorkspaceMarker>, icon: WorkspaceIconData);
+
+    /// ワークスペースが削除された。タブの`WebView`は既に閉じられている。
+    fn on_workspace_delete(&self, id: Id<WorkspaceMarker>);
+
+    fn on_workspace_order_change(&self, order: Vec<Id<WorkspaceMarker>>);
+
+    fn on_workspace_select(&self, id: Id<WorkspaceMarker>);
+
+    fn on_tab_open(&self, workspace_id: Id<WorkspaceMarker>, id: Id<TabMarker>);
+
+    fn on_tab_close(&self, workspace_id: Id<WorkspaceMarker>, id: Id<TabMarker>);
+
+    /// タブが他のワークスペースへ移された。
+    fn on_tab_move(&self, id: Id<TabMarker>, from: Id<WorkspaceMarker>, to: Id<WorkspaceMarker>);
+
+    /// ワークスペースのタブの並びが変わった。タブを開いた・閉じた場合にも通知する。
+    fn on_tab_order_change(&self, workspace_id: Id<WorkspaceMarker>, order: Vec<Id<TabMarker>>);
+
+    /// ワークスペースで選択中のタブが変わった。タブがなくなった場合は`None`となる。
+    fn on_tab_select(&self, workspace_id: Id<WorkspaceMarker>, id: Option<Id<TabMarker>>);
+
+    fn on_tab_pinned_change(&self, id: Id<TabMarker>, is_pinned: bool);
+
+    fn on_tab_group_change(&self, id: Id<TabMarker>, group_id: Option<Id<TabGroupMarker>>);
+
+    /// ワークスペースのタブグループが作られた・変更された・削除された。
+    fn on_tab_groups_change(&self, workspace_id: Id<WorkspaceMarker>, groups: Vec<TabGroupData>);
+
     fn on_tab_title_change(&self, id: Id<TabMarker>, title: String);
+
+    fn on_tab_navigation_commit(&self, id: Id<TabMarker>, url: String);
+
+    /// タブのページのファビコンが見つかった。[`FaviconFetch::fetch`]を非同期に実行すること。
+    fn on_tab_favicon_request(&self, fetch: FaviconFetch);
+
+    /// タブのファビコンが変わった。`None`なら、[`LetterAvatar`]などで代わりのものを表示すると良い。
+    fn on_tab_favicon_change(&self, id: Id<TabMarker>, path: Option<PathBuf>);
+
+    /// タブで権限が要求された。
+    /// [`SitePermissionPrompt::resolve_stored`]で保存済みの判断を適用し、
+    /// 残ったものだけをユーザーに尋ねると良い。
+    fn on_site_permission_request(&self, prompt: SitePermissionPrompt);
+
+    fn on_site_permission_prompt_dismiss(&self, id: Id<TabMarker>, prompt_id: u64);
+
+    /// タブでJavaScriptのダイアログが開かれた。
+    /// 裏のタブからのものであっても、フォーカスを奪わずにタブが選択されるまで待たせること。
+    fn on_tab_js_dialog(&self, id: Id<TabMarker>, request: JsDialogRequest);
+
+    fn on_tab_before_unload_dialog(&self, id: Id<TabMarker>, request: BeforeUnloadRequest);
+
+    /// タブの表示待ちのダイアログが全て無効になった。
+    fn on_tab_js_dialog_reset(&self, id: Id<TabMarker>);
+
+    fn on_tab_find_result(&self, id: Id<TabMarker>, result: FindResult);
+
+    /// タブのページを表示していたレンダラープロセスがクラッシュした。
+    /// GPUプロセスのクラッシュはCEFから通知されないので、ここでは扱えない。
+    /// [`TabCrash::reload_after`]が`Some`なら、その時間の後にmemex-coreが自動で読み込み直す。
+    /// `None`の場合は、ユーザーが選んだ時に[`Tab::reload_after_crash`]を呼ぶこと。
+    ///
+    /// [`Tab::reload_after_crash`]: crate::Tab::reload_after_crash
+    fn on_tab_crash(&self, id: Id<TabMarker>, crash: TabCrash);
+
+    /// タブが破棄された、または破棄されたタブが読み込み直された。
+    fn on_tab_discard_change(&self, id: Id<TabMarker>, is_discarded: bool);
+}
+
+fn collect_files(items: &[FileSystemItem], files: &mut Vec<PathBuf>) {
+    for item in items {
+        match item {
+            FileSystemItem::File(path) => files.push(path.clone()),
+            FileSystemItem::Dir { children, .. } => collect_files(children, files),
+        }
+    }
 }
@@ -0,0 +1,183 @@
+use std::time::{Duration, Instant};
+
+use memex_cef::{RenderProcessTermination, WeakWebView};
+
+/// 前回のクラッシュからこの時間内に再びクラッシュしたら、続けてクラッシュしたとみなす。
+const CRASH_LOOP_WINDOW: Duration = Duration::from_secs(5 * 60);
+/// 続けてクラッシュした場合に、自動で読み込み直す回数の上限。
+const MAX_AUTO_RELOADS: u32 = 3;
+/// 最初に自動で読み込み直すまでの時間。続けてクラッシュする度に倍にする。
+const INITIAL_RELOAD_DELAY: Duration = Duration::from_secs(1);
+
+/// タブのページを表示していたレンダラープロセスのクラッシュ。
+/// GPUプロセスのクラッシュはCEFから通知されないので、ここには含まれない。
+#[derive(Clone, Debug)]
+pub struct TabCrash {
+    pub termination: RenderProcessTermination,
+    /// このタブがクラッシュした回数。
+    pub crash_count: u32,
+    /// この時間が経った後に、自動で読み込み直す。
+    /// クラッシュを繰り返している場合は`None`となり、ユーザーに読み込み直すかを任せる。
+    pub reload_after: Option<Duration>,
+}
+
+/// タブのクラッシュの記録。
+#[derive(Default)]
+pub(crate) struct CrashState {
+    /// 読み込み直されていない、最後のクラッシュ。
+    pub(crate) crash: Option<TabCrash>,
+    crash_count: u32,
+    consecutive_crashes: u32,
+    last_crashed_at: Option<Instant>,
+    /// 自動で読み込み直す`WebView`。タブが`WebView`を閉じたら外す。
+    /// `WebView`のクライアントがこの状態を持つので、循環しないよう弱い参照にする。
+    pub(crate) webview: Option<WeakWebView>,
+}
+
+impl CrashState {
+    pub(crate) fn record(&mut self, termination: RenderProcessTermination) -> TabCrash {
+        self.record_at(termination, Instant::now())
+    }
+
+    fn record_at(&mut self, termination: RenderProcessTermination, now: Instant) -> TabCrash {
+        let is_consecutive = self
+            .last_crashed_at
+            .is_some_and(|last| now.duration_since(last) < CRASH_LOOP_WINDOW);
+
+        self.consecutive_crashes = if is_consecutive {
+            self.consecutive_crashes + 1
+        } else {
+            1
+        };
+        self.crash_count += 1;
+        self.last_crashed_at = Some(now);
+
+        let reload_after = (self.consecutive_crashes <= MAX_AUTO_RELOADS)
+            .then(|| INITIAL_RELOAD_DELAY * 2u32.pow(self.consecutive_crashes - 1));
+
+        let crash = TabCrash {
+            termination,
+            crash_count: self.crash_count,
+            reload_after,
+        };
+        self.crash = Some(crash.clone());
+
+        crash
+    }
+
+    pub(crate) fn crash_count(&self) -> u32 {
+        self.crash_count
+    }
+
+    /// `crash_count`回目のクラッシュが、まだ読み込み直されていなければ読み込み直す。
+    /// 待っている間にユーザーが読み込み直したり、タブを閉じたりした場合は何もしない。
+    pub(crate) fn auto_reload(&mut self, crash_count: u32) {
+        if self
+            .crash
+            .as_ref()
+            .is_none_or(|crash| crash.crash_count != crash_count)
+        {
+            return;
+        }
+
+        if let Some(webview) = self.webview.as_ref().and_then(WeakWebView::upgrade) {
+            log::info!("クラッシュしたページを自動で読み込み直します。");
+            webview.reload();
+            self.crash = None;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use memex_cef::TerminationKind;
+
+    use super::*;
+
+    fn termination() -> RenderProcessTermination {
+        RenderProcessTermination {
+            kind: TerminationKind::Crashed,
+            error_code: 1,
+            error_message: String::new(),
+        }
+    }
+
+    #[test]
+    fn doubles_delay_for_consecutive_crashes() {
+        let mut state = CrashState::default();
+        let start = Instant::now();
+
+        let delays = (0..3)
+            .map(|index| {
+                state
+                    .record_at(termination(), start + Duration::from_secs(10 * index))
+                    .reload_after
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            delays,
+            [
+                Some(Duration::from_secs(1)),
+                Some(Duration::from_secs(2)),
+                Some(Duration::from_secs(4)),
+            ]
+        );
+        assert_eq!(state.crash_count(), 3);
+    }
+
+    #[test]
+    fn stops_auto_reload_after_limit() {
+        let mut state = CrashState::default();
+        let start = Instant::now();
+
+        for index in 0..MAX_AUTO_RELOADS {
+            let crash = state.record_at(termination(), start + Duration::from_secs(index as u64));
+            assert!(crash.reload_after.is_some());
+        }
+
+        let crash = state.record_at(
+            termination(),
+            start + Duration::from_secs(MAX_AUTO_RELOADS as u64),
+        );
+        assert_eq!(crash.reload_after, None);
+        assert_eq!(crash.crash_count, MAX_AUTO_RELOADS + 1);
+        assert!(state.crash.is_some());
+    }
+
+    #[test]
+    fn resets_delay_outside_window() {
+        let mut state = CrashState::default();
+        let start = Instant::now();
+
+        state.record_at(termination(), start);
+        state.record_at(termination(), start + Duration::from_secs(1));
+        let crash = state.record_at(
+            termination(),
+            start + Duration::from_secs(1) + CRASH_LOOP_WINDOW,
+        );
+
+        assert_eq!(crash.reload_after, Some(INITIAL_RELOAD_DELAY));
+        // 回数は続けてクラッシュしたかに関わらず数える。
+        assert_eq!(crash.crash_count, 3);
+    }
+
+    #[test]
+    fn skips_auto_reload_of_handled_crash() {
+        let mut state = CrashState::default();
+        let crash = state.record(termination());
+
+        // ユーザーが先に読み込み直した。
+        state.crash = None;
+        state.auto_reload(crash.crash_count);
+        assert!(state.crash.is_none());
+
+        // 待っている間に、また別のクラッシュがあった。
+        let next = state.record(termination());
+        state.auto_reload(crash.crash_count);
+        assert_eq!(
+            state.crash.as_ref().map(|crash| crash.crash_count),
+            Some(next.crash_count)
+        );
+    }
+}
@@ -0,0 +1,113 @@
+pub use model::*;
+
+use memex_cef::TextAnchor;
+use sqlx::types::Uuid;
+
+use crate::{AnnotationMarker, Id, WorkspaceMarker, db::Database};
+
+pub async fn add_annotation(db: &Database, data: &AnnotationData) -> anyhow::Result<()> {
+    let id = *data.id;
+    let workspace_id = *data.workspace_id;
+    let anchor = &data.anchor;
+
+    sqlx::query!(
+        "
+        INSERT INTO annotation (
+            id, workspace_id, url, quote, prefix, suffix, start_offset, end_offset, note, created_at
+        )
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?);
+        ",
+        id,
+        workspace_id,
+        data.url,
+        anchor.quote,
+        anchor.prefix,
+        anchor.suffix,
+        anchor.start,
+        anchor.end,
+        data.note,
+        data.created_at
+    )
+    .execute(db)
+    .await?;
+
+    Ok(())
+}
+
+/// ワークスペースの注釈を、URL毎にページ内の順で取得する。
+pub async fn list_annotations(
+    db: &Database,
+    workspace_id: Id<WorkspaceMarker>,
+) -> anyhow::Result<Vec<AnnotationData>> {
+    let workspace_id = *workspace_id;
+
+    let records = sqlx::query!(
+        r#"
+        SELECT
+            id as "id: Uuid", workspace_id as "workspace_id: Uuid", url, quote, prefix, suffix,
+            start_offset, end_offset, note, created_at
+        FROM annotation
+        WHERE workspace_id = ?
+        ORDER BY url, start_offset;
+        "#,
+        workspace_id
+    )
+    .fetch_all(db)
+    .await?;
+
+    Ok(records
+        .into_iter()
+        .map(|record| AnnotationData {
+            id: record.id.into(),
+            workspace_id: record.workspace_id.into(),
+            url: record.url,
+            anchor: TextAnchor {
+                quote: record.quote,
+                prefix: record.prefix,
+                suffix: record.suffix,
+                start: record.start_offset as u32,
+                end: record.end_offset as u32,
+            },
+            note: record.note,
+            created_at: record.created_at,
+        })
+        .collect())
+}
+
+pub async fn update_annotation_note(
+    db: &Database,
+    id: Id<AnnotationMarker>,
+    note: &str,
+) -> anyhow::Result<()> {
+    sqlx::query!("UPDATE annotation SET note = ? WHERE id = ?;", note, *id)
+        .execute(db)
+        .await?;
+
+    Ok(())
+}
+
+pub async fn remove_annotation(db: &Database, id: Id<AnnotationMarker>) -> anyhow::Result<()> {
+    sqlx::query!("DELETE FROM annotation WHERE id = ?;", *id)
+        .execute(db)
+        .await?;
+
+    Ok(())
+}
+
+mod model {
+    use memex_cef::TextAnchor;
+
+    use crate::{AnnotationMarker, Id, WorkspaceMarker};
+
+    #[derive(Clone, Debug)]
+    pub struct AnnotationData {
+        pub id: Id<AnnotationMarker>,
+        pub workspace_id: Id<WorkspaceMarker>,
+        /// 正規化したページのURL。
+        pub url: String,
+        pub anchor: TextAnchor,
+        pub note: String,
+        /// 作成した日時。UNIX時間（秒）。
+        pub created_at: i64,
+    }
+}
@@ -0,0 +1,81 @@
+pub use model::*;
+
+use sqlx::types::Uuid;
+
+use crate::{BookmarkMarker, Id, WorkspaceMarker, db::Database};
+
+pub async fn add_bookmark(db: &Database, data: &BookmarkData) -> anyhow::Result<()> {
+    let id = *data.id;
+    let workspace_id = data.workspace_id.map(|id| *id);
+
+    sqlx::query!(
+        "
+        INSERT INTO bookmark (id, workspace_id, url, title, created_at)
+        VALUES (?, ?, ?, ?, ?);
+        ",
+        id,
+        workspace_id,
+        data.url,
+        data.title,
+        data.created_at
+    )
+    .execute(db)
+    .await?;
+
+    Ok(())
+}
+
+/// ワークスペースで使うブックマークを、作った順に取得する。全体のものも含む。
+pub async fn list_bookmarks(
+    db: &Database,
+    workspace_id: Id<WorkspaceMarker>,
+) -> anyhow::Result<Vec<BookmarkData>> {
+    let workspace_id = *workspace_id;
+
+    let records = sqlx::query!(
+        r#"
+        SELECT
+            id as "id: Uuid", workspace_id as "workspace_id?: Uuid", url, title, created_at
+        FROM bookmark
+        WHERE workspace_id IS NULL OR workspace_id = ?
+        ORDER BY created_at;
+        "#,
+        workspace_id
+    )
+    .fetch_all(db)
+    .await?;
+
+    Ok(records
+        .into_iter()
+        .map(|record| BookmarkData {
+            id: record.id.into(),
+            workspace_id: record.workspace_id.map(Id::from),
+            url: record.url,
+            title: record.title,
+            created_at: record.created_at,
+        })
+        .collect())
+}
+
+pub async fn remove_bookmark(db: &Database, id: Id<BookmarkMarker>) -> anyhow::Result<()> {
+    sqlx::query!("DELETE FROM bookmark WHERE id = ?;", *id)
+        .execute(db)
+        .await?;
+
+    Ok(())
+}
+
+mod model {
+    use crate::{BookmarkMarker, Id, WorkspaceMarker};
+
+    #[derive(Clone, Debug)]
+    pub struct BookmarkData {
+        pub id: Id<BookmarkMarker>,
+        /// `None`なら、全てのワークスペースで使う。
+        pub workspace_id: Option<Id<WorkspaceMarker>>,
+        pub url: String,
+        pub title: String,
+        /// UNIX時間の秒。
+        pub created_at: i64,
+    }
+}
@@ -0,0 +1,61 @@
+pub use model::*;
+
+use crate::db::Database;
+
+/// ページのファビコンを取得する。`page_url`は正規化したものを渡す。
+pub async fn get_page_favicon(
+    db: &Database,
+    page_url: &str,
+) -> anyhow::Result<Option<PageFaviconData>> {
+    let record = sqlx::query!(
+        "SELECT page_url, icon_url, hash FROM page_favicon WHERE page_url = ?;",
+        page_url
+    )
+    .fetch_optional(db)
+    .await?;
+
+    Ok(record.map(|record| PageFaviconData {
+        page_url: record.page_url,
+        icon_url: record.icon_url,
+        hash: record.hash,
+    }))
+}
+
+/// ファビコンのURLから、既にキャッシュしている内容のハッシュを探す。
+pub async fn find_favicon_hash(db: &Database, icon_url: &str) -> anyhow::Result<Option<String>> {
+    let record = sqlx::query!(
+        "SELECT hash FROM page_favicon WHERE icon_url = ? LIMIT 1;",
+        icon_url
+    )
+    .fetch_optional(db)
+    .await?;
+
+    Ok(record.map(|record| record.hash))
+}
+
+/// ページのファビコンを保存する。既に保存されていれば上書きする。
+pub async fn set_page_favicon(db: &Database, data: &PageFaviconData) -> anyhow::Result<()> {
+    sqlx::query!(
+        "
+        INSERT INTO page_favicon (page_url, icon_url, hash)
+        VALUES (?, ?, ?)
+        ON CONFLICT (page_url) DO UPDATE SET icon_url = excluded.icon_url, hash = excluded.hash;
+        ",
+        data.page_url,
+        data.icon_url,
+        data.hash
+    )
+    .execute(db)
+    .await?;
+
+    Ok(())
+}
+
+mod model {
+    #[derive(Clone, Debug)]
+    pub struct PageFaviconData {
+        pub page_url: String,
+        pub icon_url: String,
+        pub hash: String,
+    }
+}
@@ -0,0 +1,100 @@
+pub use model::*;
+
+use crate::{Id, WorkspaceMarker, db::Database};
+
+/// 閲覧履歴に訪問を加える。タイトルが空なら、保存済みのタイトルを残す。
+pub async fn add_history_visits(db: &Database, visits: &[HistoryVisitData]) -> anyhow::Result<()> {
+    let mut tx = db.begin().await?;
+
+    for visit in visits {
+        let workspace_id = *visit.workspace_id;
+
+        sqlx::query!(
+            "
+            INSERT INTO history (workspace_id, url, title, visit_count, last_visited_at)
+            VALUES (?, ?, ?, ?, ?)
+            ON CONFLICT (workspace_id, url) DO UPDATE SET
+                title = CASE WHEN excluded.title = '' THEN title ELSE excluded.title END,
+                visit_count = visit_count + excluded.visit_count,
+                last_visited_at = MAX(last_visited_at, excluded.last_visited_at);
+            ",
+            workspace_id,
+            visit.url,
+            visit.title,
+            visit.visit_count,
+            visit.visited_at
+        )
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    tx.commit().await?;
+
+    Ok(())
+}
+
+/// URLかタイトルに`term`を含む閲覧履歴を、訪問回数の多い順に取得する。
+pub async fn search_history(
+    db: &Database,
+    workspace_id: Id<WorkspaceMarker>,
+    term: &str,
+    limit: u32,
+) -> anyhow::Result<Vec<HistoryData>> {
+    let workspace_id = *workspace_id;
+    let pattern = format!(
+        "%{}%",
+        term.replace('\\', "\\\\")
+            .replace('%', "\\%")
+            .replace('_', "\\_")
+    );
+
+    let records = sqlx::query!(
+        r#"
+        SELECT url, title, visit_count as "visit_count: u32", last_visited_at
+        FROM history
+        WHERE workspace_id = ? AND (url LIKE ? ESCAPE '\' OR title LIKE ? ESCAPE '\')
+        ORDER BY visit_count DESC, last_visited_at DESC
+        LIMIT ?;
+        "#,
+        workspace_id,
+        pattern,
+        pattern,
+        limit
+    )
+    .fetch_all(db)
+    .await?;
+
+    Ok(records
+        .into_iter()
+        .map(|record| HistoryData {
+            url: record.url,
+            title: record.title,
+            visit_count: record.visit_count,
+            last_visited_at: record.last_visited_at,
+        })
+        .collect())
+}
+
+mod model {
+    use crate::{Id, WorkspaceMarker};
+
+    #[derive(Clone, Debug)]
+    pub struct HistoryData {
+        pub url: String,
+        pub title: String,
+        pub visit_count: u32,
+        /// UNIX時間の秒。
+        pub last_visited_at: i64,
+    }
+
+    /// まだ保存していない訪問。
+    #[derive(Clone, Debug)]
+    pub struct HistoryVisitData {
+        pub workspace_id: Id<WorkspaceMarker>,
+        pub url: String,
+        pub title: String,
+        pub visit_count: u32,
+        /// UNIX時間の秒。
+        pub visited_at: i64,
+    }
+}
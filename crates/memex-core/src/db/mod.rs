@@ -3,11 +3,33 @@ use sqlx::SqlitePool;
 
 use crate::fs::DataContext;
 
+pub use annotation::*;
+pub use bookmark::*;
+pub use favicon::*;
+pub use history::*;
+pub use page_archive::*;
+pub use search_engine::*;
+pub use site_permission::*;
+pub use site_visit::*;
 pub use tab::*;
+pub use tab_group::*;
 pub use workspace::*;
+pub use workspace_icon::*;
+pub use zoom_level::*;
 
+mod annotation;
+mod bookmark;
+mod favicon;
+mod history;
+mod page_archive;
+mod search_engine;
+mod site_permission;
+mod site_visit;
 mod tab;
+mod tab_group;
 mod workspace;
+mod workspace_icon;
+mod zoom_level;
 
 pub type Database = SqlitePool;
 
@@ -19,14 +41,42 @@ pub async fn setup_database(context: &DataContext) -> anyhow::Result<Database> {
         .await
         .context("データベースへの接続に失敗しました。")?;
 
+    prepare_database(&db).await?;
+
+    Ok(db)
+}
+
+/// マイグレーションを行い、既定のデータを用意する。
+async fn prepare_database(db: &Database) -> anyhow::Result<()> {
     sqlx::migrate!("./migrations")
-        .run(&db)
+        .run(db)
         .await
         .context("データベースのマイグレーションに失敗しました。")?;
 
-    setup_workspace_table(&db)
+    setup_workspace_table(db)
         .await
         .context("ワークスペースのデータベースでの準備に失敗しました。")?;
 
-    Ok(db)
+    setup_search_engine_table(db)
+        .await
+        .context("検索エンジンのデータベースでの準備に失敗しました。")?;
+
+    Ok(())
+}
+
+/// テスト用の、メモリ上のデータベース。
+#[cfg(test)]
+pub(crate) async fn setup_test_database() -> Database {
+    // メモリ上のデータベースは接続毎に別なので、一つの接続を使い続ける。
+    let db = sqlx::sqlite::SqlitePoolOptions::new()
+        .max_connections(1)
+        .idle_timeout(None)
+        .max_lifetime(None)
+        .connect("sqlite::memory:")
+        .await
+        .unwrap();
+
+    prepare_database(&db).await.unwrap();
+
+    db
 }
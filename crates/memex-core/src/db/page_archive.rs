@@ -0,0 +1,115 @@
+pub use model::*;
+
+use std::path::{Path, PathBuf};
+
+use anyhow::Context as _;
+use sqlx::types::Uuid;
+
+use crate::{Id, PageArchiveMarker, WorkspaceMarker, db::Database};
+
+pub async fn add_page_archive(db: &Database, data: &PageArchiveData) -> anyhow::Result<()> {
+    let id = *data.id;
+    let workspace_id = *data.workspace_id;
+    let path = data.path.to_str().context("パスの文字列化に失敗")?;
+
+    sqlx::query!(
+        "
+        INSERT INTO page_archive (id, workspace_id, path, source_url, title, captured_at)
+        VALUES (?, ?, ?, ?, ?, ?);
+        ",
+        id,
+        workspace_id,
+        path,
+        data.source_url,
+        data.title,
+        data.captured_at
+    )
+    .execute(db)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn list_page_archives(
+    db: &Database,
+    workspace_id: Id<WorkspaceMarker>,
+) -> anyhow::Result<Vec<PageArchiveData>> {
+    let workspace_id = *workspace_id;
+
+    let records = sqlx::query!(
+        r#"
+        SELECT id as "id: Uuid", workspace_id as "workspace_id: Uuid", path, source_url, title, captured_at
+        FROM page_archive
+        WHERE workspace_id = ?
+        ORDER BY captured_at DESC;
+        "#,
+        workspace_id
+    )
+    .fetch_all(db)
+    .await?;
+
+    Ok(records
+        .into_iter()
+        .map(|record| PageArchiveData {
+            id: record.id.into(),
+            workspace_id: record.workspace_id.into(),
+            path: PathBuf::from(record.path),
+            source_url: record.source_url,
+            title: record.title,
+            captured_at: record.captured_at,
+        })
+        .collect())
+}
+
+/// アーカイブのファイルのパスから、そのメタデータを取得する。
+pub async fn get_page_archive_by_path(
+    db: &Database,
+    path: &Path,
+) -> anyhow::Result<Option<PageArchiveData>> {
+    let path = path.to_str().context("パスの文字列化に失敗")?;
+
+    let record = sqlx::query!(
+        r#"
+        SELECT id as "id: Uuid", workspace_id as "workspace_id: Uuid", path, source_url, title, captured_at
+        FROM page_archive
+        WHERE path = ?;
+        "#,
+        path
+    )
+    .fetch_optional(db)
+    .await?;
+
+    Ok(record.map(|record| PageArchiveData {
+        id: record.id.into(),
+        workspace_id: record.workspace_id.into(),
+        path: PathBuf::from(record.path),
+        source_url: record.source_url,
+        title: record.title,
+        captured_at: record.captured_at,
+    }))
+}
+
+pub async fn remove_page_archive(db: &Database, id: Id<PageArchiveMarker>) -> anyhow::Result<()> {
+    sqlx::query!("DELETE FROM page_archive WHERE id = ?;", *id)
+        .execute(db)
+        .await?;
+
+    Ok(())
+}
+
+mod model {
+    use std::path::PathBuf;
+
+    use crate::{Id, PageArchiveMarker, WorkspaceMarker};
+
+    #[derive(Clone, Debug)]
+    pub struct PageArchiveData {
+        pub id: Id<PageArchiveMarker>,
+        pub workspace_id: Id<WorkspaceMarker>,
+        pub path: PathBuf,
+        pub source_url: String,
+        pub title: String,
+        /// 取得した日時。UNIX時間（秒）。
+        pub captured_at: i64,
+    }
+}
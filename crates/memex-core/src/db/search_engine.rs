@@ -0,0 +1,268 @@
+pub use model::*;
+
+use sqlx::{SqliteConnection, types::Uuid};
+
+use crate::{Id, SearchEngineMarker, WorkspaceMarker, db::Database};
+
+/// 検索エンジンが1つもなければ、最初から使えるものを加える。
+pub(super) async fn setup_search_engine_table(db: &Database) -> anyhow::Result<()> {
+    let count = sqlx::query_scalar!("SELECT COUNT(*) FROM search_engine;")
+        .fetch_one(db)
+        .await?;
+    if count > 0 {
+        return Ok(());
+    }
+
+    let engines = [
+        (
+            "Google",
+            "g",
+            "https://www.google.com/search?q={searchTerms}",
+            "https://www.google.com/complete/search?client=firefox&q={searchTerms}",
+        ),
+        (
+            "DuckDuckGo",
+            "d",
+            "https://duckduckgo.com/?q={searchTerms}",
+            "https://duckduckgo.com/ac/?type=list&q={searchTerms}",
+        ),
+        (
+            "Wikipedia",
+            "w",
+            "https://ja.wikipedia.org/w/index.php?search={searchTerms}",
+            "https://ja.wikipedia.org/w/api.php?action=opensearch&search={searchTerms}",
+        ),
+    ];
+
+    let mut tx = db.begin().await?;
+    for (position, (name, keyword, url_template, suggest_url_template)) in
+        engines.into_iter().enumerate()
+    {
+        let id = *Id::<SearchEngineMarker>::default();
+        let position = position as i64;
+
+        sqlx::query!(
+            "
+            INSERT INTO search_engine (id, name, keyword, url_template, suggest_url_template, position)
+            VALUES (?, ?, ?, ?, ?, ?);
+            ",
+            id,
+            name,
+            keyword,
+            url_template,
+            suggest_url_template,
+            position
+        )
+        .execute(&mut *tx)
+        .await?;
+    }
+    tx.commit().await?;
+
+    Ok(())
+}
+
+/// 検索エンジンを並び順に全て取得する。
+pub async fn list_search_engines(db: &Database) -> anyhow::Result<Vec<SearchEngineData>> {
+    let records = sqlx::query!(
+        r#"
+        SELECT id as "id: Uuid", name, keyword, url_template, suggest_url_template
+        FROM search_engine
+        ORDER BY position, rowid;
+        "#
+    )
+    .fetch_all(db)
+    .await?;
+
+    Ok(records
+        .into_iter()
+        .map(|record| SearchEngineData {
+            id: record.id.into(),
+            name: record.name,
+            keyword: record.keyword,
+            url_template: record.url_template,
+            suggest_url_template: record.suggest_url_template,
+        })
+        .collect())
+}
+
+/// 検索エンジンを加え、並び順を`order`にする。
+pub async fn add_search_engine(
+    db: &Database,
+    data: &SearchEngineData,
+    order: &[Id<SearchEngineMarker>],
+) -> anyhow::Result<()> {
+    let id = *data.id;
+    let mut tx = db.begin().await?;
+
+    sqlx::query!(
+        "
+        INSERT INTO search_engine (id, name, keyword, url_template, suggest_url_template)
+        VALUES (?, ?, ?, ?, ?);
+        ",
+        id,
+        data.name,
+        data.keyword,
+        data.url_template,
+        data.suggest_url_template
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    write_search_engine_positions(&mut tx, order).await?;
+    tx.commit().await?;
+
+    Ok(())
+}
+
+pub async fn update_search_engine(db: &Database, data: &SearchEngineData) -> anyhow::Result<()> {
+    let id = *data.id;
+
+    sqlx::query!(
+        "
+        UPDATE search_engine
+        SET name = ?, keyword = ?, url_template = ?, suggest_url_template = ?
+        WHERE id = ?;
+        ",
+        data.name,
+        data.keyword,
+        data.url_template,
+        data.suggest_url_template,
+        id
+    )
+    .execute(db)
+    .await?;
+
+    Ok(())
+}
+
+/// 検索エンジンを削除し、並び順を`order`にする。
+/// 既定にしていたワークスペースは、並び順で最初のものを使うようになる。
+pub async fn remove_search_engine(
+    db: &Database,
+    id: Id<SearchEngineMarker>,
+    order: &[Id<SearchEngineMarker>],
+) -> anyhow::Result<()> {
+    let id = *id;
+    let mut tx = db.begin().await?;
+
+    sqlx::query!("DELETE FROM search_engine WHERE id = ?;", id)
+        .execute(&mut *tx)
+        .await?;
+
+    write_search_engine_positions(&mut tx, order).await?;
+    tx.commit().await?;
+
+    Ok(())
+}
+
+pub async fn update_search_engine_positions(
+    db: &Database,
+    order: &[Id<SearchEngineMarker>],
+) -> anyhow::Result<()> {
+    let mut tx = db.begin().await?;
+    write_search_engine_positions(&mut tx, order).await?;
+    tx.commit().await?;
+
+    Ok(())
+}
+
+async fn write_search_engine_positions(
+    conn: &mut SqliteConnection,
+    order: &[Id<SearchEngineMarker>],
+) -> anyhow::Result<()> {
+    for (position, id) in order.iter().enumerate() {
+        let position = position as i64;
+
+        sqlx::query!(
+            "UPDATE search_engine SET position = ? WHERE id = ?;",
+            position,
+            **id
+        )
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    Ok(())
+}
+
+/// ワークスペース毎の既定の検索エンジンを全て取得する。
+pub async fn list_workspace_search_engines(
+    db: &Database,
+) -> anyhow::Result<Vec<WorkspaceSearchEngineData>> {
+    let records = sqlx::query!(
+        r#"
+        SELECT workspace_id as "workspace_id: Uuid", search_engine_id as "search_engine_id: Uuid"
+        FROM workspace_search_engine;
+        "#
+    )
+    .fetch_all(db)
+    .await?;
+
+    Ok(records
+        .into_iter()
+        .map(|record| WorkspaceSearchEngineData {
+            workspace_id: record.workspace_id.into(),
+            search_engine_id: record.search_engine_id.into(),
+        })
+        .collect())
+}
+
+/// ワークスペースの既定の検索エンジンを設定する。`None`なら、並び順で最初のものを使うよう戻す。
+pub async fn set_workspace_search_engine(
+    db: &Database,
+    workspace_id: Id<WorkspaceMarker>,
+    search_engine_id: Option<Id<SearchEngineMarker>>,
+) -> anyhow::Result<()> {
+    let workspace_id = *workspace_id;
+
+    match search_engine_id {
+        Some(search_engine_id) => {
+            let search_engine_id = *search_engine_id;
+
+            sqlx::query!(
+                "
+                INSERT INTO workspace_search_engine (workspace_id, search_engine_id)
+                VALUES (?, ?)
+                ON CONFLICT (workspace_id) DO UPDATE SET search_engine_id = excluded.search_engine_id;
+                ",
+                workspace_id,
+                search_engine_id
+            )
+            .execute(db)
+            .await?;
+        }
+        None => {
+            sqlx::query!(
+                "DELETE FROM workspace_search_engine WHERE workspace_id = ?;",
+                workspace_id
+            )
+            .execute(db)
+            .await?;
+        }
+    }
+
+    Ok(())
+}
+
+mod model {
+    use crate::{Id, SearchEngineMarker, WorkspaceMarker};
+
+    #[derive(Clone, Debug, PartialEq, Eq)]
+    pub struct SearchEngineData {
+        pub id: Id<SearchEngineMarker>,
+        pub name: String,
+        /// オムニボックスで、検索語の前に書いてこの検索エンジンを使うためのキーワード。
+        pub keyword: String,
+        /// `{searchTerms}`を含む、検索結果のURLのテンプレート。
+        pub url_template: String,
+        /// `{searchTerms}`を含む、検索候補のURLのテンプレート。
+        /// OpenSearchの`application/x-suggestions+json`の形式で返すものに限る。
+        pub suggest_url_template: Option<String>,
+    }
+
+    #[derive(Clone, Debug)]
+    pub struct WorkspaceSearchEngineData {
+        pub workspace_id: Id<WorkspaceMarker>,
+        pub search_engine_id: Id<SearchEngineMarker>,
+    }
+}
@@ -0,0 +1,276 @@
+pub use model::*;
+
+use memex_cef::{PermissionDecision, PermissionKind};
+use sqlx::types::Uuid;
+
+use crate::{Id, WorkspaceMarker, db::Database};
+
+/// 権限の判断を保存する。同じ範囲に既に判断があれば上書きする。
+pub async fn set_site_permission(db: &Database, data: &SitePermissionData) -> anyhow::Result<()> {
+    let workspace_id = data.workspace_id.map(|id| *id);
+    let permission = data.permission.as_str();
+    let decision = data.decision.as_str();
+
+    sqlx::query!(
+        "
+        INSERT INTO site_permission (origin, workspace_id, permission, decision)
+        VALUES (?, ?, ?, ?)
+        ON CONFLICT (origin, IFNULL(workspace_id, ''), permission)
+        DO UPDATE SET decision = excluded.decision;
+        ",
+        data.origin,
+        workspace_id,
+        permission,
+        decision
+    )
+    .execute(db)
+    .await?;
+
+    Ok(())
+}
+
+/// 保存されている権限の判断を取得する。
+/// ワークスペース毎の判断がある場合は、全体の判断よりもそちらを優先する。
+pub async fn get_site_permission(
+    db: &Database,
+    origin: &str,
+    workspace_id: Option<Id<WorkspaceMarker>>,
+    permission: PermissionKind,
+) -> anyhow::Result<Option<PermissionDecision>> {
+    let workspace_id = workspace_id.map(|id| *id);
+    let permission = permission.as_str();
+
+    let record = sqlx::query!(
+        "
+        SELECT decision FROM site_permission
+        WHERE origin = ? AND permission = ? AND (workspace_id IS NULL OR workspace_id = ?)
+        ORDER BY workspace_id IS NULL
+        LIMIT 1;
+        ",
+        origin,
+        permission,
+        workspace_id
+    )
+    .fetch_optional(db)
+    .await?;
+
+    record.map(|record| record.decision.parse()).transpose()
+}
+
+/// 保存されている権限の判断を全て取得する。
+pub async fn list_site_permissions(db: &Database) -> anyhow::Result<Vec<SitePermissionData>> {
+    let records = sqlx::query!(
+        r#"
+        SELECT origin, workspace_id as "workspace_id?: Uuid", permission, decision
+        FROM site_permission
+        ORDER BY origin;
+        "#
+    )
+    .fetch_all(db)
+    .await?;
+
+    records
+        .into_iter()
+        .map(|record| {
+            Ok(SitePermissionData {
+                origin: record.origin,
+                workspace_id: record.workspace_id.map(Id::from),
+                permission: record.permission.parse()?,
+                decision: record.decision.parse()?,
+            })
+        })
+        .collect()
+}
+
+/// 特定の範囲の権限の判断を取り消す。
+pub async fn revoke_site_permission(
+    db: &Database,
+    origin: &str,
+    workspace_id: Option<Id<WorkspaceMarker>>,
+    permission: PermissionKind,
+) -> anyhow::Result<()> {
+    let workspace_id = workspace_id.map(|id| *id);
+    let permission = permission.as_str();
+
+    sqlx::query!(
+        "
+        DELETE FROM site_permission
+        WHERE origin = ? AND workspace_id IS ? AND permission = ?;
+        ",
+        origin,
+        workspace_id,
+        permission
+    )
+    .execute(db)
+    .await?;
+
+    Ok(())
+}
+
+/// オリジンに対する権限の判断を、範囲を問わずに全て取り消す。
+pub async fn revoke_site_permissions(db: &Database, origin: &str) -> anyhow::Result<()> {
+    sqlx::query!("DELETE FROM site_permission WHERE origin = ?;", origin)
+        .execute(db)
+        .await?;
+
+    Ok(())
+}
+
+mod model {
+    use memex_cef::{PermissionDecision, PermissionKind};
+
+    use crate::{Id, WorkspaceMarker};
+
+    #[derive(Clone, Debug)]
+    pub struct SitePermissionData {
+        pub origin: String,
+        /// `None`の場合は、全てのワークスペースに適用される。
+        pub workspace_id: Option<Id<WorkspaceMarker>>,
+        pub permission: PermissionKind,
+        pub decision: PermissionDecision,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures_lite::future::block_on;
+
+    use super::*;
+    use crate::db::setup_test_database;
+
+    fn data(
+        origin: &str,
+        workspace_id: Option<Id<WorkspaceMarker>>,
+        decision: PermissionDecision,
+    ) -> SitePermissionData {
+        SitePermissionData {
+            origin: origin.to_owned(),
+            workspace_id,
+            permission: PermissionKind::Camera,
+            decision,
+        }
+    }
+
+    #[test]
+    fn gets_saved_decision() {
+        block_on(async {
+            let db = setup_test_database().await;
+            let origin = "https://example.com";
+
+            assert_eq!(
+                get_site_permission(&db, origin, None, PermissionKind::Camera)
+                    .await
+                    .unwrap(),
+                None
+            );
+
+            set_site_permission(&db, &data(origin, None, PermissionDecision::Allow))
+                .await
+                .unwrap();
+
+            assert_eq!(
+                get_site_permission(&db, origin, None, PermissionKind::Camera)
+                    .await
+                    .unwrap(),
+                Some(PermissionDecision::Allow)
+            );
+            assert_eq!(
+                get_site_permission(&db, origin, None, PermissionKind::Microphone)
+                    .await
+                    .unwrap(),
+                None
+            );
+            assert_eq!(
+                get_site_permission(&db, "https://example.org", None, PermissionKind::Camera)
+                    .await
+                    .unwrap(),
+                None
+            );
+        });
+    }
+
+    #[test]
+    fn overwrites_decision_in_same_scope() {
+        block_on(async {
+            let db = setup_test_database().await;
+            let origin = "https://example.com";
+            let home = Some(Id::home());
+
+            for decision in [PermissionDecision::Allow, PermissionDecision::Deny] {
+                set_site_permission(&db, &data(origin, None, decision))
+                    .await
+                    .unwrap();
+                set_site_permission(&db, &data(origin, home, decision))
+                    .await
+                    .unwrap();
+            }
+
+            let permissions = list_site_permissions(&db).await.unwrap();
+            assert_eq!(permissions.len(), 2);
+            assert!(
+                permissions
+                    .iter()
+                    .all(|permission| permission.decision == PermissionDecision::Deny)
+            );
+        });
+    }
+
+    #[test]
+    fn prefers_workspace_decision() {
+        block_on(async {
+            let db = setup_test_database().await;
+            let origin = "https://example.com";
+            let home = Id::home();
+
+            set_site_permission(&db, &data(origin, None, PermissionDecision::Deny))
+                .await
+                .unwrap();
+            set_site_permission(&db, &data(origin, Some(home), PermissionDecision::Allow))
+                .await
+                .unwrap();
+
+            assert_eq!(
+                get_site_permission(&db, origin, Some(home), PermissionKind::Camera)
+                    .await
+                    .unwrap(),
+                Some(PermissionDecision::Allow)
+            );
+            // 他のワークスペースには、全体の判断が適用される。
+            assert_eq!(
+                get_site_permission(&db, origin, Some(Id::default()), PermissionKind::Camera)
+                    .await
+                    .unwrap(),
+                Some(PermissionDecision::Deny)
+            );
+        });
+    }
+
+    #[test]
+    fn revokes_only_given_scope() {
+        block_on(async {
+            let db = setup_test_database().await;
+            let origin = "https://example.com";
+            let home = Id::home();
+
+            set_site_permission(&db, &data(origin, None, PermissionDecision::Deny))
+                .await
+                .unwrap();
+            set_site_permission(&db, &data(origin, Some(home), PermissionDecision::Allow))
+                .await
+                .unwrap();
+
+            revoke_site_permission(&db, origin, Some(home), PermissionKind::Camera)
+                .await
+                .unwrap();
+            assert_eq!(
+                get_site_permission(&db, origin, Some(home), PermissionKind::Camera)
+                    .await
+                    .unwrap(),
+                Some(PermissionDecision::Deny)
+            );
+
+            revoke_site_permissions(&db, origin).await.unwrap();
+            assert!(list_site_permissions(&db).await.unwrap().is_empty());
+        });
+    }
+}
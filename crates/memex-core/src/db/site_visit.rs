@@ -0,0 +1,51 @@
+use crate::{Id, WorkspaceMarker, db::Database};
+
+/// オリジン毎の訪問回数を加算する。
+pub async fn add_site_visits(
+    db: &Database,
+    visits: &[(Id<WorkspaceMarker>, String, u32)],
+) -> anyhow::Result<()> {
+    let mut tx = db.begin().await?;
+
+    for (workspace_id, origin, count) in visits {
+        let workspace_id = **workspace_id;
+
+        sqlx::query!(
+            "
+            INSERT INTO site_visit (workspace_id, origin, visit_count)
+            VALUES (?, ?, ?)
+            ON CONFLICT (workspace_id, origin)
+            DO UPDATE SET visit_count = visit_count + excluded.visit_count;
+            ",
+            workspace_id,
+            origin,
+            count
+        )
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    tx.commit().await?;
+
+    Ok(())
+}
+
+/// ワークスペースで最も多く訪問したオリジンを取得する。
+pub async fn most_visited_origin(
+    db: &Database,
+    workspace_id: Id<WorkspaceMarker>,
+) -> anyhow::Result<Option<String>> {
+    let record = sqlx::query!(
+        "
+        SELECT origin FROM site_visit
+        WHERE workspace_id = ?
+        ORDER BY visit_count DESC
+        LIMIT 1;
+        ",
+        *workspace_id
+    )
+    .fetch_optional(db)
+    .await?;
+
+    Ok(record.map(|record| record.origin))
+}
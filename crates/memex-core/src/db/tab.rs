@@ -1,11 +1,15 @@
 pub use model::*;
 
+use sqlx::{SqliteConnection, types::Uuid};
+
 use crate::{Id, Tab, TabMarker, WorkspaceMarker, db::Database};
 
+/// タブを加え、ワークスペースのタブの並びを保存する。
 pub async fn add_tab(
     db: &Database,
     workspace_id: Id<WorkspaceMarker>,
     tab: &Tab,
+    layout: &[TabLayoutData],
 ) -> anyhow::Result<()> {
     let id = tab.id();
 
@@ -13,6 +17,8 @@ pub async fn add_tab(
     let location_type = location.r#type();
     let location_source = location.source();
 
+    let mut tx = db.begin().await?;
+
     sqlx::query!(
         "
         INSERT INTO tab (id, workspace_id, location_type, location_source)
@@ -23,20 +29,69 @@ pub async fn add_tab(
         location_type,
         location_source
     )
-    .execute(db)
+    .execute(&mut *tx)
     .await?;
 
+    write_tab_layout(&mut tx, layout).await?;
+
+    tx.commit().await?;
+
     Ok(())
 }
 
-pub async fn remove_tab(db: &Database, id: Id<TabMarker>) -> anyhow::Result<()> {
+/// タブを削除し、残ったタブの並びを保存する。
+pub async fn remove_tab(
+    db: &Database,
+    id: Id<TabMarker>,
+    layout: &[TabLayoutData],
+) -> anyhow::Result<()> {
+    let mut tx = db.begin().await?;
+
     sqlx::query!("DELETE FROM tab WHERE id = ?;", *id)
-        .execute(db)
+        .execute(&mut *tx)
         .await?;
 
+    write_tab_layout(&mut tx, layout).await?;
+
+    tx.commit().await?;
+
     Ok(())
 }
 
+/// ワークスペースのタブを、場所を含めて並び順に全て取得する。
+pub async fn list_tabs(
+    db: &Database,
+    workspace_id: Id<WorkspaceMarker>,
+) -> anyhow::Result<Vec<TabData>> {
+    let workspace_id = *workspace_id;
+
+    let records = sqlx::query!(
+        r#"
+        SELECT
+            id as "id: Uuid", location_type, location_source,
+            is_pinned as "is_pinned: bool", group_id as "group_id: Uuid"
+        FROM tab
+        WHERE workspace_id = ?
+        ORDER BY position, rowid;
+        "#,
+        workspace_id
+    )
+    .fetch_all(db)
+    .await?;
+
+    records
+        .into_iter()
+        .map(|record| {
+            Ok(TabData {
+                id: record.id.into(),
+                location: TabLocationData::parse(&record.location_type, record.location_source)?,
+                is_pinned: record.is_pinned,
+                group_id: record.group_id.map(Into::into),
+            })
+        })
+        .collect()
+}
+
 pub async fn update_location(
     db: &Database,
     id: Id<TabMarker>,
@@ -57,10 +112,68 @@ pub async fn update_location(
     Ok(())
 }
 
+/// タブを他のワークスペースへ移し、両方のワークスペースのタブの並びを保存する。
+/// グループは元のワークスペースのものなので外す。
+pub async fn move_tab_to_workspace(
+    db: &Database,
+    id: Id<TabMarker>,
+    workspace_id: Id<WorkspaceMarker>,
+    source_layout: &[TabLayoutData],
+    target_layout: &[TabLayoutData],
+) -> anyhow::Result<()> {
+    let mut tx = db.begin().await?;
+
+    sqlx::query!(
+        "UPDATE tab SET workspace_id = ?, group_id = NULL WHERE id = ?;",
+        *workspace_id,
+        *id
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    write_tab_layout(&mut tx, source_layout).await?;
+    write_tab_layout(&mut tx, target_layout).await?;
+
+    tx.commit().await?;
+
+    Ok(())
+}
+
+/// タブの並び・固定・グループを保存する。
+pub async fn update_tab_layout(db: &Database, layout: &[TabLayoutData]) -> anyhow::Result<()> {
+    let mut tx = db.begin().await?;
+    write_tab_layout(&mut tx, layout).await?;
+    tx.commit().await?;
+
+    Ok(())
+}
+
+pub(super) async fn write_tab_layout(
+    conn: &mut SqliteConnection,
+    layout: &[TabLayoutData],
+) -> anyhow::Result<()> {
+    for tab in layout {
+        let position = tab.position as i64;
+        let group_id = tab.group_id.map(|group_id| *group_id);
+
+        sqlx::query!(
+            "UPDATE tab SET position = ?, is_pinned = ?, group_id = ? WHERE id = ?;",
+            position,
+            tab.is_pinned,
+            group_id,
+            *tab.id
+        )
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    Ok(())
+}
+
 mod model {
     use std::path::PathBuf;
 
-    use crate::{Id, TabMarker};
+    use crate::{Id, TabGroupMarker, TabMarker};
 
     #[derive(Clone, Debug)]
     pub enum TabLocationData {
@@ -87,11 +200,31 @@ mod model {
                 Self::NativeHomePage => None,
             }
         }
+
+        pub fn parse(r#type: &str, source: Option<String>) -> anyhow::Result<Self> {
+            Ok(match (r#type, source) {
+                ("WebPage", Some(url)) => Self::WebPage { url },
+                ("FileViewer", Some(path)) => Self::FileViewer { path: path.into() },
+                ("NativeHomePage", _) => Self::NativeHomePage,
+                (r#type, _) => anyhow::bail!("不明なタブの場所です：{}", r#type),
+            })
+        }
     }
 
     #[derive(Debug)]
     pub struct TabData {
         pub id: Id<TabMarker>,
         pub location: TabLocationData,
+        pub is_pinned: bool,
+        pub group_id: Option<Id<TabGroupMarker>>,
+    }
+
+    /// タブの並びでの位置と、固定・グループの状態。
+    #[derive(Clone, Copy, Debug)]
+    pub struct TabLayoutData {
+        pub id: Id<TabMarker>,
+        pub position: usize,
+        pub is_pinned: bool,
+        pub group_id: Option<Id<TabGroupMarker>>,
     }
 }
@@ -0,0 +1,158 @@
+pub use model::*;
+
+use sqlx::types::Uuid;
+
+use crate::{
+    Id, TabGroupMarker, WorkspaceMarker,
+    db::{Database, TabLayoutData, tab::write_tab_layout},
+};
+
+/// グループを作り、グループに入れたタブを含めてタブの並びを保存する。
+pub async fn add_tab_group(
+    db: &Database,
+    data: &TabGroupData,
+    layout: &[TabLayoutData],
+) -> anyhow::Result<()> {
+    let id = *data.id;
+    let workspace_id = *data.workspace_id;
+    let color = data.color.as_str();
+
+    let mut tx = db.begin().await?;
+
+    sqlx::query!(
+        "
+        INSERT INTO tab_group (id, workspace_id, name, color, is_collapsed)
+        VALUES (?, ?, ?, ?, ?);
+        ",
+        id,
+        workspace_id,
+        data.name,
+        color,
+        data.is_collapsed
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    write_tab_layout(&mut tx, layout).await?;
+
+    tx.commit().await?;
+
+    Ok(())
+}
+
+pub async fn list_tab_groups(
+    db: &Database,
+    workspace_id: Id<WorkspaceMarker>,
+) -> anyhow::Result<Vec<TabGroupData>> {
+    let workspace_id = *workspace_id;
+
+    let records = sqlx::query!(
+        r#"
+        SELECT
+            id as "id: Uuid", workspace_id as "workspace_id: Uuid", name, color,
+            is_collapsed as "is_collapsed: bool"
+        FROM tab_group
+        WHERE workspace_id = ?;
+        "#,
+        workspace_id
+    )
+    .fetch_all(db)
+    .await?;
+
+    records
+        .into_iter()
+        .map(|record| {
+            Ok(TabGroupData {
+                id: record.id.into(),
+                workspace_id: record.workspace_id.into(),
+                name: record.name,
+                color: TabGroupColor::parse(&record.color)?,
+                is_collapsed: record.is_collapsed,
+            })
+        })
+        .collect()
+}
+
+/// 名前・色・折りたたみの状態を保存する。
+pub async fn update_tab_group(db: &Database, data: &TabGroupData) -> anyhow::Result<()> {
+    let color = data.color.as_str();
+
+    sqlx::query!(
+        "UPDATE tab_group SET name = ?, color = ?, is_collapsed = ? WHERE id = ?;",
+        data.name,
+        color,
+        data.is_collapsed,
+        *data.id
+    )
+    .execute(db)
+    .await?;
+
+    Ok(())
+}
+
+/// グループを削除する。グループにあったタブは、どのグループにも属さなくなる。
+pub async fn remove_tab_group(db: &Database, id: Id<TabGroupMarker>) -> anyhow::Result<()> {
+    sqlx::query!("DELETE FROM tab_group WHERE id = ?;", *id)
+        .execute(db)
+        .await?;
+
+    Ok(())
+}
+
+mod model {
+    use crate::{Id, TabGroupMarker, WorkspaceMarker};
+
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+    pub enum TabGroupColor {
+        #[default]
+        Grey,
+        Blue,
+        Red,
+        Yellow,
+        Green,
+        Pink,
+        Purple,
+        Cyan,
+        Orange,
+    }
+
+    impl TabGroupColor {
+        pub fn as_str(&self) -> &'static str {
+            match self {
+                Self::Grey => "Grey",
+                Self::Blue => "Blue",
+                Self::Red => "Red",
+                Self::Yellow => "Yellow",
+                Self::Green => "Green",
+                Self::Pink => "Pink",
+                Self::Purple => "Purple",
+                Self::Cyan => "Cyan",
+                Self::Orange => "Orange",
+            }
+        }
+
+        pub fn parse(value: &str) -> anyhow::Result<Self> {
+            Ok(match value {
+                "Grey" => Self::Grey,
+                "Blue" => Self::Blue,
+                "Red" => Self::Red,
+                "Yellow" => Self::Yellow,
+                "Green" => Self::Green,
+                "Pink" => Self::Pink,
+                "Purple" => Self::Purple,
+                "Cyan" => Self::Cyan,
+                "Orange" => Self::Orange,
+                _ => anyhow::bail!("不明なタブグループの色です：{value}"),
+            })
+        }
+    }
+
+    #[derive(Clone, Debug)]
+    pub struct TabGroupData {
+        pub id: Id<TabGroupMarker>,
+        pub workspace_id: Id<WorkspaceMarker>,
+        pub name: String,
+        pub color: TabGroupColor,
+        pub is_collapsed: bool,
+    }
+}
@@ -1,14 +1,19 @@
 pub use model::*;
 
-use crate::{db::{Database}, Id, WorkspaceMarker};
+use sqlx::{SqliteConnection, types::Uuid};
+
+use crate::{
+    Id, TabMarker, WorkspaceMarker,
+    db::{Database, list_tab_groups, list_tabs, list_workspace_icon_images},
+};
 
 pub(super) async fn setup_workspace_table(db: &Database) -> anyhow::Result<()> {
     let home = Id::<WorkspaceMarker>::home();
 
     sqlx::query!(
         "
-        INSERT INTO workspace (id, icon_type)
-        VALUES (?, 'Home');
+        INSERT OR IGNORE INTO workspace (id, name, icon_type)
+        VALUES (?, 'ホーム', 'Home');
         ",
         *home
     )
@@ -18,10 +23,185 @@ pub(super) async fn setup_workspace_table(db: &Database) -> anyhow::Result<()> {
     Ok(())
 }
 
+/// ワークスペースを、タブとタブグループを含めて並び順に全て取得する。
+pub async fn list_workspaces(db: &Database) -> anyhow::Result<Vec<WorkspaceData>> {
+    let records = sqlx::query!(
+        r#"
+        SELECT
+            id as "id: Uuid", name, icon_type, icon_source,
+            selected_tab as "selected_tab: Uuid"
+        FROM workspace
+        ORDER BY position, rowid;
+        "#
+    )
+    .fetch_all(db)
+    .await?;
+
+    let mut workspaces = Vec::with_capacity(records.len());
+    for record in records {
+        let id = record.id.into();
+
+        workspaces.push(WorkspaceData {
+            id,
+            name: record.name,
+            icon: WorkspaceIconData::parse(&record.icon_type, record.icon_source)?,
+            icon_images: list_workspace_icon_images(db, id).await?,
+            tabs: list_tabs(db, id).await?,
+            selected_tab: record.selected_tab.map(Into::into),
+            tab_groups: list_tab_groups(db, id).await?,
+        });
+    }
+
+    Ok(workspaces)
+}
+
+/// ワークスペースを加え、ワークスペースの並びを保存する。タブは保存しない。
+pub async fn add_workspace(
+    db: &Database,
+    data: &WorkspaceData,
+    workspace_order: &[Id<WorkspaceMarker>],
+) -> anyhow::Result<()> {
+    let icon_type = data.icon.r#type();
+    let icon_source = data.icon.source();
+
+    let mut tx = db.begin().await?;
+
+    sqlx::query!(
+        "
+        INSERT INTO workspace (id, name, icon_type, icon_source)
+        VALUES (?, ?, ?, ?);
+        ",
+        *data.id,
+        data.name,
+        icon_type,
+        icon_source
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    write_workspace_positions(&mut tx, workspace_order).await?;
+
+    tx.commit().await?;
+
+    Ok(())
+}
+
+pub async fn rename_workspace(
+    db: &Database,
+    id: Id<WorkspaceMarker>,
+    name: &str,
+) -> anyhow::Result<()> {
+    sqlx::query!("UPDATE workspace SET name = ? WHERE id = ?;", name, *id)
+        .execute(db)
+        .await?;
+
+    Ok(())
+}
+
+/// ワークスペースを削除し、残ったワークスペースの並びを保存する。
+/// タブ・タブグループ・注釈などのワークスペースのデータも削除される。
+pub async fn remove_workspace(
+    db: &Database,
+    id: Id<WorkspaceMarker>,
+    workspace_order: &[Id<WorkspaceMarker>],
+) -> anyhow::Result<()> {
+    let mut tx = db.begin().await?;
+
+    sqlx::query!("DELETE FROM workspace WHERE id = ?;", *id)
+        .execute(&mut *tx)
+        .await?;
+
+    write_workspace_positions(&mut tx, workspace_order).await?;
+
+    tx.commit().await?;
+
+    Ok(())
+}
+
+/// ワークスペースの並び順を保存する。`workspace_order`の順に`position`を振り直す。
+pub async fn update_workspace_positions(
+    db: &Database,
+    workspace_order: &[Id<WorkspaceMarker>],
+) -> anyhow::Result<()> {
+    let mut tx = db.begin().await?;
+    write_workspace_positions(&mut tx, workspace_order).await?;
+    tx.commit().await?;
+
+    Ok(())
+}
+
+async fn write_workspace_positions(
+    conn: &mut SqliteConnection,
+    workspace_order: &[Id<WorkspaceMarker>],
+) -> anyhow::Result<()> {
+    for (position, id) in workspace_order.iter().enumerate() {
+        let position = position as i64;
+
+        sqlx::query!(
+            "UPDATE workspace SET position = ? WHERE id = ?;",
+            position,
+            **id
+        )
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    Ok(())
+}
+
+pub async fn set_selected_tab(
+    db: &Database,
+    id: Id<WorkspaceMarker>,
+    tab_id: Option<Id<TabMarker>>,
+) -> anyhow::Result<()> {
+    let tab_id = tab_id.map(|tab_id| *tab_id);
+
+    sqlx::query!(
+        "UPDATE workspace SET selected_tab = ? WHERE id = ?;",
+        tab_id,
+        *id
+    )
+    .execute(db)
+    .await?;
+
+    Ok(())
+}
+
+/// ワークスペースで、ページを強制的に暗い配色にするかどうかを取得する。
+pub async fn get_force_dark(db: &Database, id: Id<WorkspaceMarker>) -> anyhow::Result<bool> {
+    let record = sqlx::query!(
+        r#"SELECT force_dark as "force_dark: bool" FROM workspace WHERE id = ?;"#,
+        *id
+    )
+    .fetch_optional(db)
+    .await?;
+
+    Ok(record.is_some_and(|record| record.force_dark))
+}
+
+pub async fn set_force_dark(
+    db: &Database,
+    id: Id<WorkspaceMarker>,
+    force_dark: bool,
+) -> anyhow::Result<()> {
+    sqlx::query!(
+        "UPDATE workspace SET force_dark = ? WHERE id = ?;",
+        force_dark,
+        *id
+    )
+    .execute(db)
+    .await?;
+
+    Ok(())
+}
+
 mod model {
     use std::path::PathBuf;
 
-    use crate::{Id, TabMarker, WorkspaceMarker};
+    use crate::{
+        Id, TabMarker, WorkspaceMarker,
+        db::{TabData, TabGroupData, WorkspaceIconImageData},
+    };
 
     #[derive(Debug, Default, Clone)]
     pub enum WorkspaceIconData {
@@ -33,13 +213,50 @@ mod model {
         Default,
     }
 
+    impl WorkspaceIconData {
+        pub fn r#type(&self) -> &'static str {
+            match self {
+                Self::Home => "Home",
+                Self::Emoji(_) => "Emoji",
+                Self::Text(_) => "Text",
+                Self::Image(_) => "Image",
+                Self::Default => "Default",
+            }
+        }
+
+        pub fn source(&self) -> Option<String> {
+            match self {
+                Self::Emoji(emoji) => Some(emoji.clone()),
+                Self::Text(text) => Some(text.clone()),
+                Self::Image(path) => Some(path.to_str().expect("パスの文字列化に失敗").to_owned()),
+                Self::Home | Self::Default => None,
+            }
+        }
+
+        pub fn parse(r#type: &str, source: Option<String>) -> anyhow::Result<Self> {
+            Ok(match (r#type, source) {
+                ("Home", _) => Self::Home,
+                ("Emoji", Some(emoji)) => Self::Emoji(emoji),
+                ("Text", Some(text)) => Self::Text(text),
+                ("Image", Some(path)) => Self::Image(path.into()),
+                ("Default", _) => Self::Default,
+                (r#type, _) => anyhow::bail!("不明なワークスペースのアイコンです：{}", r#type),
+            })
+        }
+    }
+
     #[derive(Debug)]
     pub struct WorkspaceData {
         pub id: Id<WorkspaceMarker>,
         pub name: String,
         pub icon: WorkspaceIconData,
+        /// アイコンが画像の場合の、サイズ毎に縮小した画像。小さい順に並べる。
+        pub icon_images: Vec<WorkspaceIconImageData>,
 
-        pub tabs: Vec<Id<TabMarker>>,
+        /// 並び順のタブ。
+        pub tabs: Vec<TabData>,
         pub selected_tab: Option<Id<TabMarker>>,
+        /// タブグループ。表示される順は、グループのタブの並びで決まる。
+        pub tab_groups: Vec<TabGroupData>,
     }
 }
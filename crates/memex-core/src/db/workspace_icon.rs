@@ -0,0 +1,86 @@
+pub use model::*;
+
+use crate::{
+    Id, WorkspaceMarker,
+    db::{Database, WorkspaceIconData},
+};
+
+/// ワークスペースのアイコンと、そのサイズ毎の画像を保存する。前の画像の記録は消す。
+pub async fn set_workspace_icon(
+    db: &Database,
+    id: Id<WorkspaceMarker>,
+    icon: &WorkspaceIconData,
+    images: &[WorkspaceIconImageData],
+) -> anyhow::Result<()> {
+    let icon_type = icon.r#type();
+    let icon_source = icon.source();
+
+    let mut tx = db.begin().await?;
+
+    sqlx::query!(
+        "UPDATE workspace SET icon_type = ?, icon_source = ? WHERE id = ?;",
+        icon_type,
+        icon_source,
+        *id
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query!(
+        "DELETE FROM workspace_icon_image WHERE workspace_id = ?;",
+        *id
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    for image in images {
+        let size = image.size as i64;
+        let path = image.path.to_str().expect("パスの文字列化に失敗");
+
+        sqlx::query!(
+            "INSERT INTO workspace_icon_image (workspace_id, size, path) VALUES (?, ?, ?);",
+            *id,
+            size,
+            path
+        )
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    tx.commit().await?;
+
+    Ok(())
+}
+
+/// ワークスペースのアイコンの画像を、小さい順に取得する。
+pub async fn list_workspace_icon_images(
+    db: &Database,
+    id: Id<WorkspaceMarker>,
+) -> anyhow::Result<Vec<WorkspaceIconImageData>> {
+    let records = sqlx::query!(
+        "SELECT size, path FROM workspace_icon_image WHERE workspace_id = ? ORDER BY size;",
+        *id
+    )
+    .fetch_all(db)
+    .await?;
+
+    Ok(records
+        .into_iter()
+        .map(|record| WorkspaceIconImageData {
+            size: record.size as u32,
+            path: record.path.into(),
+        })
+        .collect())
+}
+
+mod model {
+    use std::path::PathBuf;
+
+    /// 決まった大きさに縮小した、アイコンの画像。
+    #[derive(Clone, Debug)]
+    pub struct WorkspaceIconImageData {
+        /// 一辺のピクセル数。
+        pub size: u32,
+        pub path: PathBuf,
+    }
+}
@@ -0,0 +1,84 @@
+pub use model::*;
+
+use sqlx::types::Uuid;
+
+use crate::{Id, WorkspaceMarker, db::Database};
+
+/// ワークスペースで使うズームレベルを取得する。全体に適用されるものも含む。
+pub async fn list_zoom_levels(
+    db: &Database,
+    workspace_id: Id<WorkspaceMarker>,
+) -> anyhow::Result<Vec<ZoomLevelData>> {
+    let workspace_id = *workspace_id;
+
+    let records = sqlx::query!(
+        r#"
+        SELECT origin, workspace_id as "workspace_id?: Uuid", level
+        FROM zoom_level
+        WHERE workspace_id IS NULL OR workspace_id = ?;
+        "#,
+        workspace_id
+    )
+    .fetch_all(db)
+    .await?;
+
+    Ok(records
+        .into_iter()
+        .map(|record| ZoomLevelData {
+            origin: record.origin,
+            workspace_id: record.workspace_id.map(Id::from),
+            level: record.level,
+        })
+        .collect())
+}
+
+/// ズームレベルを保存する。同じ範囲に既に保存されていれば上書きする。
+pub async fn set_zoom_level(db: &Database, data: &ZoomLevelData) -> anyhow::Result<()> {
+    let workspace_id = data.workspace_id.map(|id| *id);
+
+    sqlx::query!(
+        "
+        INSERT INTO zoom_level (origin, workspace_id, level)
+        VALUES (?, ?, ?)
+        ON CONFLICT (origin, IFNULL(workspace_id, ''))
+        DO UPDATE SET level = excluded.level;
+        ",
+        data.origin,
+        workspace_id,
+        data.level
+    )
+    .execute(db)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn remove_zoom_level(
+    db: &Database,
+    origin: &str,
+    workspace_id: Option<Id<WorkspaceMarker>>,
+) -> anyhow::Result<()> {
+    let workspace_id = workspace_id.map(|id| *id);
+
+    sqlx::query!(
+        "DELETE FROM zoom_level WHERE origin = ? AND workspace_id IS ?;",
+        origin,
+        workspace_id
+    )
+    .execute(db)
+    .await?;
+
+    Ok(())
+}
+
+mod model {
+    use crate::{Id, WorkspaceMarker};
+
+    #[derive(Clone, Debug)]
+    pub struct ZoomLevelData {
+        pub origin: String,
+        /// `None`の場合は、全てのワークスペースに適用される。
+        pub workspace_id: Option<Id<WorkspaceMarker>>,
+        pub level: f64,
+    }
+}
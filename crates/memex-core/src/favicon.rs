@@ -0,0 +1,318 @@
+use std::{
+    cell::RefCell,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+use anyhow::Context as _;
+use base64::Engine as _;
+use image::{GenericImageView as _, ImageFormat};
+use sha2::{Digest as _, Sha256};
+use url::Url;
+
+use crate::{
+    BrowserContext, Id, TabMarker,
+    db::{self, Database, PageFaviconData},
+    fs::{DataContext, utils},
+    normalize_url, origin_of,
+};
+
+/// キャッシュするファビコンの、一辺の最大のピクセル数。
+const FAVICON_MAX_SIZE: u32 = 64;
+
+/// ダウンロードするファビコンの、最大のバイト数。
+const FAVICON_MAX_BYTES: usize = 1024 * 1024;
+
+/// タブのファビコンの状態。`Tab`と`TabEventHandler`で共有する。
+#[derive(Default)]
+pub(crate) struct FaviconState {
+    /// ナビゲーションが確定したページのURL。
+    pub(crate) page_url: String,
+    /// 表示中のファビコンのキャッシュのパス。
+    pub(crate) path: Option<PathBuf>,
+    /// ナビゲーションやファビコンの変更の度に増やし、古い取得の結果を捨てるのに使う。
+    pub(crate) generation: u64,
+}
+
+pub(crate) type SharedFaviconState = Rc<RefCell<FaviconState>>;
+
+/// ファビコンのキャッシュのパス。
+pub(crate) fn favicon_path(data: &DataContext, hash: &str) -> PathBuf {
+    data.favicon_dir().join(format!("{hash}.png"))
+}
+
+/// 保存されている、ページのファビコンのキャッシュのパスを返す。
+/// キャッシュのファイルが消えていれば`None`を返す。
+pub(crate) async fn cached_favicon(
+    data: &DataContext,
+    db: &Database,
+    page_url: &str,
+) -> anyhow::Result<Option<PathBuf>> {
+    let Some(favicon) = db::get_page_favicon(db, &normalize_url(page_url)).await? else {
+        return Ok(None);
+    };
+
+    let path = favicon_path(data, &favicon.hash);
+    Ok(utils::exists(&path).await?.then_some(path))
+}
+
+/// タブのページで見つかったファビコン。[`FaviconFetch::fetch`]で取得してキャッシュする。
+pub struct FaviconFetch {
+    context: BrowserContext,
+    tab_id: Id<TabMarker>,
+    state: SharedFaviconState,
+    generation: u64,
+    page_url: String,
+    icon_urls: Vec<String>,
+}
+
+impl FaviconFetch {
+    pub(crate) fn new(
+        context: BrowserContext,
+        tab_id: Id<TabMarker>,
+        state: SharedFaviconState,
+        icon_urls: Vec<String>,
+    ) -> Self {
+        let (generation, page_url) = {
+            let state = state.borrow();
+            (state.generation, state.page_url.clone())
+        };
+
+        Self {
+            context,
+            tab_id,
+            state,
+            generation,
+            page_url,
+            icon_urls,
+        }
+    }
+
+    pub fn tab_id(&self) -> Id<TabMarker> {
+        self.tab_id
+    }
+
+    /// ファビコンを候補の順に取得し、最初に読み込めたものをタブのファビコンにする。
+    /// 一度取得したURLのものはキャッシュを使う。取得中にページが移動していれば、結果を捨てる。
+    pub async fn fetch(self) -> anyhow::Result<()> {
+        let data = &self.context.data;
+        let db = &self.context.db;
+
+        let mut candidates = self.icon_urls.clone();
+        if candidates.is_empty()
+            && let Some(origin) = origin_of(&self.page_url)
+        {
+            candidates.push(format!("{origin}/favicon.ico"));
+        }
+
+        for icon_url in candidates {
+            if let Some(hash) = db::find_favicon_hash(db, &icon_url).await? {
+                let path = favicon_path(data, &hash);
+                if utils::exists(&path).await? {
+                    return self.apply(icon_url, hash, path).await;
+                }
+            }
+
+            let resource = match self
+                .context
+                .profile
+                .download(&icon_url, FAVICON_MAX_BYTES)
+                .await
+            {
+                Ok(resource) => resource,
+                Err(error) => {
+                    log::warn!("ファビコンのダウンロードに失敗しました：{error:#}");
+                    continue;
+                }
+            };
+
+            match store_favicon(data, resource.data).await {
+                Ok((hash, path)) => return self.apply(icon_url, hash, path).await,
+                Err(error) => {
+                    log::warn!("ファビコンを読み込めませんでした（{icon_url}）：{error:#}")
+                }
+            }
+        }
+
+        // 取得できなければ、前のページのファビコンを残さないよう外す。
+        let cleared = {
+            let mut state = self.state.borrow_mut();
+            state.generation == self.generation && state.path.take().is_some()
+        };
+        if cleared {
+            self.context
+                .delegate
+                .on_tab_favicon_change(self.tab_id, None);
+        }
+
+        anyhow::bail!("ファビコンを取得できませんでした：{}", self.page_url)
+    }
+
+    async fn apply(self, icon_url: String, hash: String, path: PathBuf) -> anyhow::Result<()> {
+        let favicon = PageFaviconData {
+            page_url: normalize_url(&self.page_url),
+            icon_url,
+            hash,
+        };
+        db::set_page_favicon(&self.context.db, &favicon).await?;
+
+        {
+            let mut state = self.state.borrow_mut();
+            if state.generation != self.generation {
+                return Ok(());
+            }
+            state.path = Some(path.clone());
+        }
+
+        self.context
+            .delegate
+            .on_tab_favicon_change(self.tab_id, Some(path));
+
+        Ok(())
+    }
+}
+
+/// ファビコンを、内容のハッシュをファイル名にしてキャッシュする。
+/// ICOなどもPNGに変換し、大きければ縮小する。既にキャッシュしていれば書き出さない。
+async fn store_favicon(data: &DataContext, source: Vec<u8>) -> anyhow::Result<(String, PathBuf)> {
+    let hash = Sha256::digest(&source)
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect::<String>();
+    let path = favicon_path(data, &hash);
+
+    if !utils::exists(&path).await? {
+        let output = path.clone();
+
+        blocking::unblock(move || {
+            let image = image::load_from_memory(&source)
+                .context("ファビコンの画像を読み込めませんでした。")?;
+            let (width, height) = image.dimensions();
+            let image = if width > FAVICON_MAX_SIZE || height > FAVICON_MAX_SIZE {
+                image.thumbnail(FAVICON_MAX_SIZE, FAVICON_MAX_SIZE)
+            } else {
+                image
+            };
+
+            image
+                .save_with_format(&output, ImageFormat::Png)
+                .context("ファビコンの書き出しに失敗しました。")
+        })
+        .await?;
+    }
+
+    Ok((hash, path))
+}
+
+/// ファビコンがない場合に代わりに表示する、頭文字と色。
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LetterAvatar {
+    pub letter: String,
+    /// 背景色の色相。`0..360`の範囲。
+    pub hue: u16,
+}
+
+impl LetterAvatar {
+    /// ホスト名の頭文字を使う。ホスト名がなければタイトルの頭文字を使う。
+    /// 色はホスト名から決めるので、同じサイトなら同じ色になる。
+    pub fn new(url: &str, title: &str) -> Self {
+        let host = Url::parse(url)
+            .ok()
+            .and_then(|url| url.host_str().map(ToOwned::to_owned))
+            .map(|host| host.trim_start_matches("www.").to_owned())
+            .unwrap_or_default();
+
+        let letter = host
+            .chars()
+            .chain(title.chars())
+            .find(|c| c.is_alphanumeric())
+            .map(|c| c.to_uppercase().collect())
+            .unwrap_or_else(|| "?".to_owned());
+
+        let key = if host.is_empty() { title } else { &host };
+        // FNV-1a。実行毎に変わらないハッシュであれば良い。
+        let hash = key.bytes().fold(0x811c9dc5u32, |hash, byte| {
+            (hash ^ byte as u32).wrapping_mul(0x01000193)
+        });
+
+        Self {
+            letter,
+            hue: (hash % 360) as u16,
+        }
+    }
+
+    pub fn to_svg(&self, size: u32) -> String {
+        let letter = self
+            .letter
+            .replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;");
+
+        format!(
+            r##"<svg xmlns="http://www.w3.org/2000/svg" width="{size}" height="{size}" viewBox="0 0 16 16"><rect width="16" height="16" rx="3" fill="hsl({hue}, 55%, 45%)"/><text x="8" y="12" font-family="sans-serif" font-size="10" font-weight="bold" text-anchor="middle" fill="#fff">{letter}</text></svg>"##,
+            hue = self.hue,
+        )
+    }
+
+    /// 内部ページの`<img>`などに使える`data:`のURL。
+    pub fn to_data_url(&self, size: u32) -> String {
+        let svg = base64::engine::general_purpose::STANDARD.encode(self.to_svg(size));
+
+        format!("data:image/svg+xml;base64,{svg}")
+    }
+}
+
+/// キャッシュしたファビコンを、内部ページに埋め込める`data:`のURLにする。
+pub(crate) async fn favicon_data_url(path: &Path) -> anyhow::Result<String> {
+    let png = async_fs::read(path)
+        .await
+        .context("ファビコンの読み込みに失敗しました。")?;
+    let png = base64::engine::general_purpose::STANDARD.encode(png);
+
+    Ok(format!("data:image/png;base64,{png}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn uses_host_initial() {
+        assert_eq!(
+            LetterAvatar::new("https://www.example.com/page", "Title").letter,
+            "E"
+        );
+        assert_eq!(LetterAvatar::new("https://docs.rs/", "Title").letter, "D");
+    }
+
+    #[test]
+    fn falls_back_to_title_initial() {
+        assert_eq!(LetterAvatar::new("about:blank", "  memex").letter, "M");
+        assert_eq!(LetterAvatar::new("not a url", "ページ").letter, "ペ");
+        assert_eq!(LetterAvatar::new("about:blank", "").letter, "?");
+    }
+
+    #[test]
+    fn keeps_color_for_same_site() {
+        let avatar = LetterAvatar::new("https://example.com/a", "A");
+
+        assert_eq!(
+            LetterAvatar::new("https://www.example.com/b", "B").hue,
+            avatar.hue
+        );
+        assert!(avatar.hue < 360);
+    }
+
+    #[test]
+    fn escapes_letter_in_svg() {
+        let avatar = LetterAvatar {
+            letter: "<&>".to_owned(),
+            hue: 120,
+        };
+        let svg = avatar.to_svg(32);
+
+        assert!(svg.contains(r#"width="32" height="32""#));
+        assert!(svg.contains("hsl(120, 55%, 45%)"));
+        assert!(svg.contains(">&lt;&amp;&gt;</text>"));
+    }
+}
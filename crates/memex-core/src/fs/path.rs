@@ -32,6 +32,24 @@ pub(super) async fn setup_data_directory(context: &DataContext) -> anyhow::Resul
             .context("ワークスペースディレクトリの作成に失敗しました。")?;
     }
 
+    if !utils::exists(context.thumbnail_dir()).await? {
+        async_fs::create_dir(context.thumbnail_dir())
+            .await
+            .context("サムネイルのディレクトリの作成に失敗しました。")?;
+    }
+
+    if !utils::exists(context.favicon_dir()).await? {
+        async_fs::create_dir(context.favicon_dir())
+            .await
+            .context("ファビコンのディレクトリの作成に失敗しました。")?;
+    }
+
+    if !utils::exists(context.reader_dir()).await? {
+        async_fs::create_dir(context.reader_dir())
+            .await
+            .context("リーダービューのディレクトリの作成に失敗しました。")?;
+    }
+
     Ok(())
 }
 
@@ -78,6 +96,45 @@ impl DataContext {
         self.data_local_dir().join("workspaces")
     }
 
+    pub fn thumbnail_dir(&self) -> PathBuf {
+        self.data_local_dir().join("thumbnails")
+    }
+
+    /// ファビコンのキャッシュのディレクトリ。ファイル名は内容のハッシュとする。
+    pub fn favicon_dir(&self) -> PathBuf {
+        self.data_local_dir().join("favicons")
+    }
+
+    /// リーダービューで表示するHTMLのディレクトリ。
+    pub fn reader_dir(&self) -> PathBuf {
+        self.data_local_dir().join("reader")
+    }
+
+    /// キーマップのファイルを置くディレクトリ。
+    pub fn keymap_dir(&self) -> PathBuf {
+        self.data_local_dir()
+    }
+
+    /// 全てのワークスペースで使うユーザースクリプトのディレクトリ。
+    pub fn user_script_dir(&self) -> PathBuf {
+        self.data_local_dir().join("user_scripts")
+    }
+
+    /// ワークスペースでのみ使うユーザースクリプトのディレクトリ。
+    pub fn workspace_user_script_dir(&self, id: Id<WorkspaceMarker>) -> PathBuf {
+        self.workspace_dir(id).join("user_scripts")
+    }
+
+    /// ワークスペースのページに適用するCSSのディレクトリ。
+    pub fn workspace_user_style_dir(&self, id: Id<WorkspaceMarker>) -> PathBuf {
+        self.workspace_dir(id).join("user_styles")
+    }
+
+    /// ワークスペースのアイコンの画像のディレクトリ。
+    pub fn workspace_icon_dir(&self, id: Id<WorkspaceMarker>) -> PathBuf {
+        self.workspace_dir(id).join("icon")
+    }
+
     pub fn workspace_dir(&self, id: Id<WorkspaceMarker>) -> PathBuf {
         self.workspace_list_dir().join(id.to_string())
     }
@@ -1,4 +1,7 @@
-use std::{io, path::Path};
+use std::{
+    io,
+    path::{Path, PathBuf},
+};
 
 pub async fn exists(path: impl AsRef<Path>) -> io::Result<bool> {
     Ok(match async_fs::metadata(path).await {
@@ -7,3 +10,45 @@ pub async fn exists(path: impl AsRef<Path>) -> io::Result<bool> {
         Err(error) => return Err(error),
     })
 }
+
+/// ファイル名に使えない文字を`_`に置き換える。空になった場合は`untitled`を返す。
+pub fn sanitize_file_name(name: &str) -> String {
+    const MAX_CHARS: usize = 100;
+
+    let sanitized = name
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .take(MAX_CHARS)
+        .collect::<String>();
+    // 先頭の`.`は隠しファイル扱いになるので取り除く。
+    let sanitized = sanitized.trim().trim_start_matches('.').trim_end();
+
+    if sanitized.is_empty() {
+        "untitled".to_owned()
+    } else {
+        sanitized.to_owned()
+    }
+}
+
+/// `directory`内で、既存のファイルと被らない`{stem}.{extension}`のパスを返す。
+/// 被る場合は`{stem} (2).{extension}`のように番号を付ける。
+pub async fn unique_path(
+    directory: impl AsRef<Path>,
+    stem: &str,
+    extension: &str,
+) -> io::Result<PathBuf> {
+    let directory = directory.as_ref();
+    let mut path = directory.join(format!("{stem}.{extension}"));
+    let mut number = 2;
+
+    while exists(&path).await? {
+        path = directory.join(format!("{stem} ({number}).{extension}"));
+        number += 1;
+    }
+
+    Ok(path)
+}
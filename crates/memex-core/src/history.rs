@@ -0,0 +1,108 @@
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    rc::Rc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use url::Url;
+
+use crate::{
+    Id, WorkspaceMarker,
+    db::{self, Database, HistoryVisitData},
+    normalize_url,
+};
+
+#[derive(Default)]
+struct PendingVisit {
+    title: String,
+    visit_count: u32,
+    visited_at: i64,
+}
+
+/// ワークスペース毎の閲覧履歴。
+/// ナビゲーションの通知は同期的なので、訪問をメモリに溜めておき、`flush`でまとめて保存する。
+#[derive(Clone, Default)]
+pub(crate) struct History {
+    pending: Rc<RefCell<HashMap<(Id<WorkspaceMarker>, String), PendingVisit>>>,
+}
+
+impl History {
+    /// HTTP(S)のページへの訪問を記録する。
+    pub(crate) fn record_visit(&self, workspace_id: Id<WorkspaceMarker>, url: &str) {
+        let Some(url) = history_url(url) else {
+            return;
+        };
+
+        let mut pending = self.pending.borrow_mut();
+        let visit = pending.entry((workspace_id, url)).or_default();
+        visit.visit_count += 1;
+        visit.visited_at = now();
+    }
+
+    /// 訪問したページのタイトルを記録する。タイトルはナビゲーションの確定より後に通知される。
+    pub(crate) fn record_title(&self, workspace_id: Id<WorkspaceMarker>, url: &str, title: &str) {
+        let Some(url) = history_url(url) else {
+            return;
+        };
+        if title.is_empty() {
+            return;
+        }
+
+        let mut pending = self.pending.borrow_mut();
+        let visit = pending.entry((workspace_id, url)).or_default();
+        visit.title = title.to_owned();
+        if visit.visited_at == 0 {
+            visit.visited_at = now();
+        }
+    }
+
+    /// 溜まっている訪問を保存する。保存に失敗したら、次に保存できるよう戻しておく。
+    pub(crate) async fn flush(&self, db: &Database) -> anyhow::Result<()> {
+        let visits = self
+            .pending
+            .take()
+            .into_iter()
+            .map(|((workspace_id, url), visit)| HistoryVisitData {
+                workspace_id,
+                url,
+                title: visit.title,
+                visit_count: visit.visit_count,
+                visited_at: visit.visited_at,
+            })
+            .collect::<Vec<_>>();
+        if visits.is_empty() {
+            return Ok(());
+        }
+
+        if let Err(error) = db::add_history_visits(db, &visits).await {
+            let mut pending = self.pending.borrow_mut();
+            for visit in visits {
+                let entry = pending.entry((visit.workspace_id, visit.url)).or_default();
+                entry.visit_count += visit.visit_count;
+                entry.visited_at = entry.visited_at.max(visit.visited_at);
+                if entry.title.is_empty() {
+                    entry.title = visit.title;
+                }
+            }
+
+            return Err(error);
+        }
+
+        Ok(())
+    }
+}
+
+/// 履歴に残すURL。HTTP(S)以外のページは残さない。
+fn history_url(url: &str) -> Option<String> {
+    let scheme = Url::parse(url).ok()?.scheme().to_owned();
+
+    matches!(scheme.as_str(), "http" | "https").then(|| normalize_url(url))
+}
+
+fn now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs() as i64)
+        .unwrap_or_default()
+}
@@ -0,0 +1,69 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::Context as _;
+use image::{ImageFormat, imageops::FilterType};
+
+use crate::db::WorkspaceIconImageData;
+
+/// ワークスペースのアイコンとして書き出す画像の、一辺のピクセル数。
+pub const WORKSPACE_ICON_SIZES: [u32; 4] = [16, 32, 64, 128];
+
+/// 画像を中央で正方形に切り抜き、決まった大きさに縮小して`directory`にPNGとして書き出す。
+/// 元の画像より大きいサイズは、最も小さいサイズを除いて書き出さない。
+/// デコードと縮小は重いので、別のスレッドで行う。
+pub(crate) async fn write_icon_images(
+    directory: PathBuf,
+    source: Vec<u8>,
+) -> anyhow::Result<Vec<WorkspaceIconImageData>> {
+    blocking::unblock(move || {
+        let image =
+            image::load_from_memory(&source).context("アイコンの画像を読み込めませんでした。")?;
+
+        let side = image.width().min(image.height());
+        anyhow::ensure!(side > 0, "アイコンの画像が空です。");
+        let image = image.crop_imm(
+            (image.width() - side) / 2,
+            (image.height() - side) / 2,
+            side,
+            side,
+        );
+
+        WORKSPACE_ICON_SIZES
+            .iter()
+            .enumerate()
+            .filter(|(index, size)| *index == 0 || **size <= side)
+            .map(|(_, &size)| {
+                let path = directory.join(format!("{size}.png"));
+                image
+                    .resize_exact(size, size, FilterType::Lanczos3)
+                    .save_with_format(&path, ImageFormat::Png)
+                    .context("アイコンの画像の書き出しに失敗しました。")?;
+
+                Ok(WorkspaceIconImageData { size, path })
+            })
+            .collect()
+    })
+    .await
+}
+
+/// アイコンのディレクトリから、`keep`以外の古い画像を削除する。
+/// 削除に失敗しても、使われないファイルが残るだけなので警告に留める。
+pub(crate) async fn remove_stale_icon_images(icon_dir: &Path, keep: Option<&Path>) {
+    let Ok(mut entries) = async_fs::read_dir(icon_dir).await else {
+        return;
+    };
+
+    while let Some(entry) = futures_lite::StreamExt::next(&mut entries).await {
+        let Ok(entry) = entry else {
+            continue;
+        };
+        let path = entry.path();
+        if Some(path.as_path()) == keep {
+            continue;
+        }
+
+        if let Err(error) = async_fs::remove_dir_all(&path).await {
+            log::warn!("古いアイコンの画像の削除に失敗しました：{error}");
+        }
+    }
+}
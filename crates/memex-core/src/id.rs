@@ -39,6 +39,15 @@ impl<T> Default for Id<T> {
     }
 }
 
+impl<T> From<Uuid> for Id<T> {
+    fn from(value: Uuid) -> Self {
+        Self {
+            phantom: PhantomData,
+            value,
+        }
+    }
+}
+
 impl<T> std::ops::Deref for Id<T> {
     type Target = Uuid;
 
@@ -62,4 +71,19 @@ mod marker {
 
     #[derive(PartialEq, Eq)]
     pub struct TabMarker;
+
+    #[derive(PartialEq, Eq)]
+    pub struct PageArchiveMarker;
+
+    #[derive(PartialEq, Eq)]
+    pub struct AnnotationMarker;
+
+    #[derive(PartialEq, Eq)]
+    pub struct TabGroupMarker;
+
+    #[derive(PartialEq, Eq)]
+    pub struct BookmarkMarker;
+
+    #[derive(PartialEq, Eq)]
+    pub struct SearchEngineMarker;
 }
@@ -0,0 +1,168 @@
+use memex_cef::DownloadedResource;
+use serde::{Deserialize, Serialize};
+
+use crate::{INTERNAL_SCHEME, IpcContext, IpcOriginPolicy, IpcRouter, db};
+
+/// `memex://<ホスト>/<パス>`で表示する、組み込みのページとそのリソース。
+const INTERNAL_PAGES: &[(&str, &str, &str)] = &[
+    (
+        "workspaces",
+        "text/html",
+        include_str!("pages/workspaces.html"),
+    ),
+    ("history", "text/html", include_str!("pages/history.html")),
+    ("notes", "text/html", include_str!("pages/notes.html")),
+    ("style/page.css", "text/css", include_str!("pages/page.css")),
+];
+
+/// 閲覧履歴のページで、一度に返す件数の上限。
+const MAX_HISTORY_ENTRIES: u32 = 500;
+
+/// `memex://`のURLに対する組み込みのリソースを返す。
+/// CEFのIOスレッドから呼ばれるので、静的なデータだけを扱う。
+pub(crate) fn internal_page(url: &str) -> Option<DownloadedResource> {
+    let url = url::Url::parse(url).ok()?;
+    if url.scheme() != INTERNAL_SCHEME {
+        return None;
+    }
+
+    let host = url.host_str()?;
+    let path = url.path().trim_matches('/');
+    let name = if path.is_empty() {
+        host.to_owned()
+    } else {
+        format!("{host}/{path}")
+    };
+
+    INTERNAL_PAGES
+        .iter()
+        .find(|(page, _, _)| *page == name)
+        .map(|(_, mime_type, content)| DownloadedResource {
+            mime_type: (*mime_type).to_owned(),
+            data: content.as_bytes().to_vec(),
+        })
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct WorkspaceEntry {
+    id: String,
+    name: String,
+    tab_count: usize,
+}
+
+#[derive(Deserialize)]
+struct HistorySearch {
+    #[serde(default)]
+    query: String,
+    limit: Option<u32>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct HistoryEntry {
+    url: String,
+    title: String,
+    visit_count: u32,
+    last_visited_at: i64,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct NoteEntry {
+    id: String,
+    url: String,
+    quote: String,
+    note: String,
+    created_at: i64,
+}
+
+/// 組み込みのページが使うチャンネルを登録する。いずれも内部ページからのみ呼び出せる。
+pub(crate) fn register_builtin_channels(router: &IpcRouter) {
+    router.register(
+        "workspaces.list",
+        IpcOriginPolicy::Internal,
+        |context: IpcContext, (): ()| async move {
+            let workspaces = db::list_workspaces(&context.db).await?;
+
+            Ok(workspaces
+                .into_iter()
+                .map(|workspace| WorkspaceEntry {
+                    id: workspace.id.to_string(),
+                    name: workspace.name,
+                    tab_count: workspace.tabs.len(),
+                })
+                .collect::<Vec<_>>())
+        },
+    );
+
+    router.register(
+        "history.search",
+        IpcOriginPolicy::Internal,
+        |context: IpcContext, search: HistorySearch| async move {
+            let limit = search
+                .limit
+                .unwrap_or(MAX_HISTORY_ENTRIES)
+                .min(MAX_HISTORY_ENTRIES);
+            let history = db::search_history(
+                &context.db,
+                context.workspace_id,
+                search.query.trim(),
+                limit,
+            )
+            .await?;
+
+            Ok(history
+                .into_iter()
+                .map(|entry| HistoryEntry {
+                    url: entry.url,
+                    title: entry.title,
+                    visit_count: entry.visit_count,
+                    last_visited_at: entry.last_visited_at,
+                })
+                .collect::<Vec<_>>())
+        },
+    );
+
+    router.register(
+        "notes.list",
+        IpcOriginPolicy::Internal,
+        |context: IpcContext, (): ()| async move {
+            let annotations = db::list_annotations(&context.db, context.workspace_id).await?;
+
+            Ok(annotations
+                .into_iter()
+                .map(|annotation| NoteEntry {
+                    id: annotation.id.to_string(),
+                    url: annotation.url,
+                    quote: annotation.anchor.quote,
+                    note: annotation.note,
+                    created_at: annotation.created_at,
+                })
+                .collect::<Vec<_>>())
+        },
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn serves_builtin_pages() {
+        let page = internal_page("memex://history").unwrap();
+        assert_eq!(page.mime_type, "text/html");
+        assert!(
+            String::from_utf8(page.data)
+                .unwrap()
+                .contains("history.search")
+        );
+
+        let style = internal_page("memex://style/page.css").unwrap();
+        assert_eq!(style.mime_type, "text/css");
+
+        assert!(internal_page("memex://workspaces/").is_some());
+        assert!(internal_page("memex://missing").is_none());
+        assert!(internal_page("https://history/").is_none());
+    }
+}
@@ -0,0 +1,155 @@
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
+
+use anyhow::Context as _;
+use futures_lite::future::BoxedLocal;
+use memex_cef::IpcRequest;
+use serde::{Serialize, de::DeserializeOwned};
+use serde_json::Value;
+
+use crate::{Id, TabMarker, WorkspaceMarker, db::Database, origin_of};
+
+pub use memex_cef::INTERNAL_SCHEME;
+
+/// IPCのハンドラを呼び出せるページの範囲。
+#[derive(Clone, Debug)]
+pub enum IpcOriginPolicy {
+    /// `memex://`の内部ページのみ。
+    Internal,
+    /// 内部ページと、指定されたオリジンのページ。
+    Origins(Vec<String>),
+    /// 全てのページ。
+    Any,
+}
+
+impl IpcOriginPolicy {
+    fn allows(&self, url: &str) -> bool {
+        let is_internal = url::Url::parse(url).is_ok_and(|url| url.scheme() == INTERNAL_SCHEME);
+
+        match self {
+            Self::Internal => is_internal,
+            Self::Origins(origins) => {
+                is_internal || origin_of(url).is_some_and(|origin| origins.contains(&origin))
+            }
+            Self::Any => true,
+        }
+    }
+}
+
+/// ハンドラに渡される、要求元の情報。
+#[derive(Clone)]
+pub struct IpcContext {
+    pub db: Database,
+    pub tab_id: Id<TabMarker>,
+    pub workspace_id: Id<WorkspaceMarker>,
+    /// 要求元のフレームのURL。
+    pub url: String,
+}
+
+type IpcHandler = Rc<dyn Fn(IpcContext, Value) -> BoxedLocal<anyhow::Result<Value>>>;
+
+struct IpcRoute {
+    policy: IpcOriginPolicy,
+    handler: IpcHandler,
+}
+
+/// `window.memex.invoke`のチャンネル毎のハンドラの登録先。
+#[derive(Clone, Default)]
+pub struct IpcRouter {
+    routes: Rc<RefCell<HashMap<String, IpcRoute>>>,
+}
+
+impl IpcRouter {
+    /// ハンドラを登録する。同じチャンネルに登録済みであれば置き換える。
+    /// 要求と結果はJSONとして変換されるので、その形がそのままページとの取り決めとなる。
+    pub fn register<P, R, F, Fut>(&self, channel: &str, policy: IpcOriginPolicy, handler: F)
+    where
+        P: DeserializeOwned,
+        R: Serialize,
+        F: Fn(IpcContext, P) -> Fut + 'static,
+        Fut: Future<Output = anyhow::Result<R>> + 'static,
+    {
+        let handler: IpcHandler = Rc::new(move |context, payload| {
+            let future =
+                serde_json::from_value::<P>(payload).map(|payload| handler(context, payload));
+
+            Box::pin(async move {
+                let result = future.context("要求の形式が不正です。")?.await?;
+
+                Ok(serde_json::to_value(result).context("結果のJSONへの変換に失敗しました。")?)
+            })
+        });
+
+        self.routes
+            .borrow_mut()
+            .insert(channel.to_owned(), IpcRoute { policy, handler });
+    }
+
+    pub fn unregister(&self, channel: &str) {
+        self.routes.borrow_mut().remove(channel);
+    }
+
+    /// チャンネルのハンドラを、要求元のURLが許可されている場合に限って返す。
+    fn route(&self, channel: &str, url: &str) -> Result<IpcHandler, String> {
+        let routes = self.routes.borrow();
+        let route = routes
+            .get(channel)
+            .ok_or_else(|| format!("不明なチャンネルです：{channel}"))?;
+
+        if !route.policy.allows(url) {
+            return Err(format!(
+                "このページからはチャンネル`{channel}`を使えません。"
+            ));
+        }
+
+        Ok(route.handler.clone())
+    }
+}
+
+/// タブから届いたIPCの要求。[`IpcCall::handle`]で、登録されたハンドラに渡す。
+pub struct IpcCall {
+    router: IpcRouter,
+    context: IpcContext,
+    request: IpcRequest,
+}
+
+impl IpcCall {
+    pub(crate) fn new(router: IpcRouter, context: IpcContext, request: IpcRequest) -> Self {
+        Self {
+            router,
+            context,
+            request,
+        }
+    }
+
+    pub fn tab_id(&self) -> Id<TabMarker> {
+        self.context.tab_id
+    }
+
+    pub fn channel(&self) -> &str {
+        self.request.channel()
+    }
+
+    /// オリジンを確認してハンドラを呼び出し、その結果をページに返す。
+    pub async fn handle(self) {
+        let Self {
+            router,
+            context,
+            request,
+        } = self;
+
+        let handler = match router.route(request.channel(), request.url()) {
+            Ok(handler) => handler,
+            Err(message) => {
+                log::warn!("{message}（{}）", request.url());
+                request.respond(Err(message));
+                return;
+            }
+        };
+
+        let result = handler(context, request.payload().clone())
+            .await
+            .map_err(|error| format!("{error:#}"));
+
+        request.respond(result);
+    }
+}
@@ -2,19 +2,56 @@ use std::sync::OnceLock;
 
 use memex_cef::CefContext;
 
-use crate::{db::Database, fs::DataContext};
+use crate::{db::Database, fs::DataContext, internal_page::internal_page};
 
+pub use annotation::*;
 pub use browser::*;
+pub use crash::*;
+pub use favicon::*;
+pub use history::*;
+pub use icon::*;
 pub use id::*;
+pub use ipc::*;
+pub use lifecycle::*;
+pub use omnibox::*;
+pub use origin::*;
+pub use palette::*;
+pub use permission::*;
+pub use reader::*;
+pub use search_engine::*;
+pub use site_visit::*;
 pub use tab::*;
+pub use user_script::*;
+pub use user_style::*;
 pub use workspace::*;
+pub use zoom::*;
 
+mod annotation;
 mod browser;
+mod crash;
+mod favicon;
 pub mod db;
 pub mod fs;
+mod history;
+mod icon;
 mod id;
+mod internal_page;
+mod ipc;
+mod lifecycle;
+mod omnibox;
+mod origin;
+mod palette;
+mod permission;
+mod reader;
+mod search_engine;
+mod site_visit;
 mod tab;
+mod tab_group;
+mod thumbnail;
+mod user_script;
+mod user_style;
 mod workspace;
+mod zoom;
 
 pub async fn setup_application_data(
     application_identifier: &'static str,
@@ -39,7 +76,8 @@ pub fn setup_chromium(
     }
 
     let root_cache_path = &data_context.chromium_data_dir();
-    memex_cef::boot(root_cache_path, root_cache_path, product_name, locale)?;
+    memex_cef::boot(root_cache_path, root_cache_path, product_name, locale, false)?;
+    memex_cef::register_internal_scheme_handler(internal_page)?;
     _ = CEF_CONTEXT.set(CefContext::default());
 
     Ok(())
@@ -0,0 +1,164 @@
+use std::time::{Duration, Instant};
+
+use crate::{Id, TabMarker, WorkspaceMarker};
+
+/// 使われていないタブを破棄して、メモリを空けるための条件。
+/// 破棄したタブは場所を保ったまま`WebView`を閉じ、次に選択された時に読み込み直す。
+#[derive(Clone, Copy, Debug)]
+pub struct TabLifecyclePolicy {
+    /// 最後に選択されてから、この時間が経ったタブを破棄する。
+    pub inactive_threshold: Duration,
+    /// ロードしたままにするタブの数の上限。超えた分は、長く使われていないものから破棄する。
+    pub max_live_tabs: usize,
+}
+
+impl Default for TabLifecyclePolicy {
+    fn default() -> Self {
+        Self {
+            inactive_threshold: Duration::from_secs(30 * 60),
+            max_live_tabs: 12,
+        }
+    }
+}
+
+/// 音声を再生中の`<audio>`・`<video>`があるかを調べる式。
+/// メインフレームの要素しか見ないので、iframeやWeb Audio APIによる再生は検出できない。
+pub(crate) const IS_PLAYING_AUDIO_EXPRESSION: &str = r#"[...document.querySelectorAll("audio, video")].some(
+  (media) => !media.paused && !media.ended && !media.muted && media.volume > 0,
+)"#;
+
+/// 破棄するかを決めるための、読み込み済みのタブの状態。
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct LiveTab {
+    pub(crate) workspace_id: Id<WorkspaceMarker>,
+    pub(crate) tab_id: Id<TabMarker>,
+    pub(crate) last_active: Instant,
+    /// 選択中のワークスペースで表示しているか。
+    pub(crate) is_visible: bool,
+    pub(crate) is_pinned: bool,
+    pub(crate) is_playing_audio: bool,
+}
+
+/// 読み込み済みの全てのタブから、`policy`に従って破棄するタブを選ぶ。
+/// 表示中・固定・音声を再生中のタブは選ばない。
+/// 残りを長く使われていない順に見て、使われていない時間が閾値を超えたものと、
+/// 読み込み済みのタブの数が上限を超えている間のものを選ぶ。
+pub(crate) fn select_tabs_to_discard(
+    tabs: &[LiveTab],
+    policy: TabLifecyclePolicy,
+    now: Instant,
+) -> Vec<(Id<WorkspaceMarker>, Id<TabMarker>)> {
+    let mut candidates = tabs
+        .iter()
+        .filter(|tab| !tab.is_visible && !tab.is_pinned && !tab.is_playing_audio)
+        .collect::<Vec<_>>();
+    candidates.sort_by_key(|tab| tab.last_active);
+
+    let mut live_tabs = tabs.len();
+    let mut selected = Vec::new();
+
+    for tab in candidates {
+        let is_inactive = now.duration_since(tab.last_active) >= policy.inactive_threshold;
+        if !is_inactive && live_tabs <= policy.max_live_tabs {
+            continue;
+        }
+
+        live_tabs -= 1;
+        selected.push((tab.workspace_id, tab.tab_id));
+    }
+
+    selected
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const POLICY: TabLifecyclePolicy = TabLifecyclePolicy {
+        inactive_threshold: Duration::from_secs(60),
+        max_live_tabs: 3,
+    };
+
+    /// `now`の`idle_secs`秒前に使われた、破棄できるタブ。
+    fn live_tab(now: Instant, idle_secs: u64) -> LiveTab {
+        LiveTab {
+            workspace_id: Id::default(),
+            tab_id: Id::default(),
+            last_active: now - Duration::from_secs(idle_secs),
+            is_visible: false,
+            is_pinned: false,
+            is_playing_audio: false,
+        }
+    }
+
+    fn select(tabs: &[LiveTab], now: Instant) -> Vec<Id<TabMarker>> {
+        select_tabs_to_discard(tabs, POLICY, now)
+            .into_iter()
+            .map(|(_, tab_id)| tab_id)
+            .collect()
+    }
+
+    #[test]
+    fn discards_inactive_tabs() {
+        let now = Instant::now();
+        let tabs = [live_tab(now, 120), live_tab(now, 10)];
+
+        assert_eq!(select(&tabs, now), [tabs[0].tab_id]);
+    }
+
+    #[test]
+    fn keeps_visible_pinned_and_playing_tabs() {
+        let now = Instant::now();
+        let tabs = [
+            LiveTab {
+                is_visible: true,
+                ..live_tab(now, 120)
+            },
+            LiveTab {
+                is_pinned: true,
+                ..live_tab(now, 120)
+            },
+            LiveTab {
+                is_playing_audio: true,
+                ..live_tab(now, 120)
+            },
+        ];
+
+        assert!(select(&tabs, now).is_empty());
+    }
+
+    #[test]
+    fn discards_least_recently_used_over_budget() {
+        let now = Instant::now();
+        let tabs = [
+            live_tab(now, 20),
+            live_tab(now, 40),
+            live_tab(now, 10),
+            live_tab(now, 30),
+            live_tab(now, 0),
+        ];
+
+        // 上限の3つになるまで、長く使われていないものから破棄する。
+        assert_eq!(select(&tabs, now), [tabs[1].tab_id, tabs[3].tab_id]);
+    }
+
+    #[test]
+    fn counts_kept_tabs_in_budget() {
+        let now = Instant::now();
+        let tabs = [
+            LiveTab {
+                is_visible: true,
+                ..live_tab(now, 0)
+            },
+            LiveTab {
+                is_pinned: true,
+                ..live_tab(now, 50)
+            },
+            live_tab(now, 30),
+            live_tab(now, 20),
+        ];
+
+        // 残すタブも上限に数えるので、破棄できるタブから1つ減らす。
+        assert_eq!(select(&tabs, now), [tabs[2].tab_id]);
+    }
+}
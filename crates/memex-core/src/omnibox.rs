@@ -0,0 +1,695 @@
+use std::{cmp::Reverse, collections::HashSet, path::PathBuf};
+
+use url::{Host, Url};
+
+use crate::{
+    INTERNAL_SCHEME, Id, TabMarker, WorkspaceMarker,
+    db::{BookmarkData, HistoryData, SearchEngineData, TabLocationData},
+    normalize_url,
+};
+
+/// 表示する候補の最大数。
+const MAX_SUGGESTIONS: usize = 8;
+
+/// 入力にあれば、そのままURLとして開くスキーム。
+const KNOWN_SCHEMES: &[&str] = &[
+    "http",
+    "https",
+    "file",
+    "about",
+    "data",
+    "chrome",
+    "view-source",
+];
+
+/// オムニボックスの入力を解釈したもの。
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum OmniboxInput {
+    Url(String),
+    /// `keyword`が`None`なら、既定の検索エンジンで検索する。
+    Search {
+        keyword: Option<String>,
+        query: String,
+    },
+}
+
+/// 入力を、開くURLか検索語に解釈する。空の入力には`None`を返す。
+/// `?`で始めると必ず検索になり、検索エンジンのキーワードで始めるとその検索エンジンで検索する。
+/// スキームのないURLは、`localhost`やIPアドレスであれば`http`、ドメイン名であれば`https`で開く。
+pub fn parse_omnibox_input(text: &str, engines: &[SearchEngineData]) -> Option<OmniboxInput> {
+    let text = text.trim();
+    if text.is_empty() {
+        return None;
+    }
+
+    if let Some(query) = text.strip_prefix('?') {
+        let query = query.trim();
+        return (!query.is_empty()).then(|| OmniboxInput::Search {
+            keyword: None,
+            query: query.to_owned(),
+        });
+    }
+
+    if let Some((keyword, query)) = text.split_once(char::is_whitespace)
+        && engines.iter().any(|engine| engine.keyword == keyword)
+    {
+        return Some(OmniboxInput::Search {
+            keyword: Some(keyword.to_owned()),
+            query: query.trim().to_owned(),
+        });
+    }
+
+    if let Some(url) = parse_url(text) {
+        return Some(OmniboxInput::Url(url));
+    }
+
+    Some(OmniboxInput::Search {
+        keyword: None,
+        query: text.to_owned(),
+    })
+}
+
+fn parse_url(text: &str) -> Option<String> {
+    if text.contains(char::is_whitespace) {
+        return None;
+    }
+
+    if let Some((scheme, _)) = text.split_once(':')
+        && (KNOWN_SCHEMES.contains(&scheme.to_ascii_lowercase().as_str())
+            || scheme.eq_ignore_ascii_case(INTERNAL_SCHEME))
+    {
+        return Url::parse(text).ok().map(Into::into);
+    }
+
+    let mut url = Url::parse(&format!("http://{text}")).ok()?;
+    // `user@example.com`のような入力は、URLではなくメールアドレスなどとして検索する。
+    if !url.username().is_empty() || url.password().is_some() {
+        return None;
+    }
+
+    let has_port_or_path = url.port().is_some() || text.contains('/');
+    let is_secure = match url.host()? {
+        // `1.5`のような数値もIPv4として解析されるので、4つの部分を全て書いたものに限る。
+        Host::Ipv4(_) => {
+            if authority_host(text).matches('.').count() != 3 {
+                return None;
+            }
+            false
+        }
+        Host::Ipv6(_) => false,
+        Host::Domain(domain) => {
+            let domain = domain.trim_end_matches('.');
+            if domain == "localhost" || domain.ends_with(".localhost") {
+                false
+            } else if let Some((_, tld)) = domain.rsplit_once('.') {
+                let is_valid_tld = tld.starts_with("xn--")
+                    || (tld.len() >= 2 && tld.chars().all(|c| c.is_ascii_alphabetic()));
+                if !is_valid_tld {
+                    return None;
+                }
+                true
+            } else if has_port_or_path {
+                // 社内のホスト名などは、ポートかパスがあればURLとみなす。
+                false
+            } else {
+                return None;
+            }
+        }
+    };
+
+    if is_secure {
+        url.set_scheme("https").ok()?;
+    }
+
+    Some(url.into())
+}
+
+/// スキームのない入力から、ホストの部分を取り出す。
+fn authority_host(text: &str) -> &str {
+    let authority = text.split(['/', '?', '#']).next().unwrap_or(text);
+
+    match authority.rsplit_once(':') {
+        Some((host, port)) if port.chars().all(|c| c.is_ascii_digit()) => host,
+        _ => authority,
+    }
+}
+
+/// 候補を選んだ時の動作。
+#[derive(Clone, Debug)]
+pub enum OmniboxAction {
+    Navigate(TabLocationData),
+    /// 既に開いているタブへ切り替える。
+    SwitchToTab {
+        workspace_id: Id<WorkspaceMarker>,
+        tab_id: Id<TabMarker>,
+    },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OmniboxSuggestionKind {
+    /// 入力したURL。
+    Url,
+    Search,
+    History,
+    Bookmark,
+    OpenTab,
+    File,
+}
+
+#[derive(Clone, Debug)]
+pub struct OmniboxSuggestion {
+    pub kind: OmniboxSuggestionKind,
+    pub title: String,
+    /// タイトルの横に表示する、URLやパス。
+    pub description: String,
+    pub action: OmniboxAction,
+    /// 大きいほど上に表示する。
+    pub relevance: u32,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct OmniboxResult {
+    /// 関連する順に並べた候補。
+    pub suggestions: Vec<OmniboxSuggestion>,
+    /// 入力の後ろに補う文字列。入力で始まるURLを閲覧したことがあれば使う。
+    pub inline_completion: Option<String>,
+}
+
+/// 開いているタブ。全てのワークスペースのものを候補にする。
+#[derive(Clone, Debug)]
+pub struct OpenTabEntry {
+    pub workspace_id: Id<WorkspaceMarker>,
+    pub tab_id: Id<TabMarker>,
+    pub title: String,
+    pub url: String,
+}
+
+/// 候補を探す対象。
+#[derive(Clone, Debug, Default)]
+pub struct OmniboxSources {
+    pub history: Vec<HistoryData>,
+    pub bookmarks: Vec<BookmarkData>,
+    pub open_tabs: Vec<OpenTabEntry>,
+    pub files: Vec<PathBuf>,
+}
+
+/// 候補を探すのに使う、入力の語。検索エンジンのキーワードや`?`は除く。
+pub fn omnibox_terms(text: &str, engines: &[SearchEngineData]) -> Vec<String> {
+    let text = match parse_omnibox_input(text, engines) {
+        Some(OmniboxInput::Search { query, .. }) => query,
+        Some(OmniboxInput::Url(_)) => text.trim().to_owned(),
+        None => return Vec::new(),
+    };
+
+    text.to_lowercase()
+        .split_whitespace()
+        .map(ToOwned::to_owned)
+        .collect()
+}
+
+/// 入力に対する候補を、関連する順に返す。
+/// 入力したURL・検索を先頭にし、開いているタブ・ブックマーク・閲覧履歴・ファイルを続ける。
+/// 同じページを指す候補は、最も関連するものだけを残す。
+pub fn omnibox_suggestions(
+    text: &str,
+    engines: &[SearchEngineData],
+    default_engine: Option<&SearchEngineData>,
+    sources: &OmniboxSources,
+    now: i64,
+) -> OmniboxResult {
+    let text = text.trim();
+    let Some(input) = parse_omnibox_input(text, engines) else {
+        return OmniboxResult::default();
+    };
+    let terms = omnibox_terms(text, engines);
+
+    let mut candidates = Vec::new();
+    let mut inline_completion = None;
+
+    if !text.contains(char::is_whitespace)
+        && let Some((history, completion)) = sources
+            .history
+            .iter()
+            .filter_map(|history| {
+                completion_of(text, &history.url).map(|completion| (history, completion))
+            })
+            .max_by_key(|(history, _)| frecency(history, now))
+    {
+        inline_completion = Some(completion.to_owned());
+        candidates.push(web_page_suggestion(
+            OmniboxSuggestionKind::History,
+            &history.title,
+            &history.url,
+            1400,
+        ));
+    }
+
+    match &input {
+        OmniboxInput::Url(url) => {
+            candidates.push(web_page_suggestion(
+                OmniboxSuggestionKind::Url,
+                "",
+                url,
+                1300,
+            ));
+            if let Some(engine) = default_engine {
+                candidates.push(search_suggestion(engine, text, 800));
+            }
+        }
+        OmniboxInput::Search {
+            keyword: Some(keyword),
+            query,
+        } => {
+            if let Some(engine) = engines.iter().find(|engine| &engine.keyword == keyword)
+                && !query.is_empty()
+            {
+                candidates.push(search_suggestion(engine, query, 1250));
+            }
+        }
+        OmniboxInput::Search {
+            keyword: None,
+            query,
+        } => {
+            if let Some(engine) = default_engine {
+                candidates.push(search_suggestion(engine, query, 1150));
+            }
+        }
+    }
+
+    for tab in &sources.open_tabs {
+        if let Some(score) = match_terms(&terms, &[&tab.title, strip_url_prefix(&tab.url)]) {
+            candidates.push((
+                normalize_url(&tab.url),
+                OmniboxSuggestion {
+                    kind: OmniboxSuggestionKind::OpenTab,
+                    title: title_or_url(&tab.title, &tab.url),
+                    description: tab.url.clone(),
+                    action: OmniboxAction::SwitchToTab {
+                        workspace_id: tab.workspace_id,
+                        tab_id: tab.tab_id,
+                    },
+                    relevance: 1000 + score * 10,
+                },
+            ));
+        }
+    }
+
+    for bookmark in &sources.bookmarks {
+        if let Some(score) =
+            match_terms(&terms, &[&bookmark.title, strip_url_prefix(&bookmark.url)])
+        {
+            candidates.push(web_page_suggestion(
+                OmniboxSuggestionKind::Bookmark,
+                &bookmark.title,
+                &bookmark.url,
+                950 + score * 10,
+            ));
+        }
+    }
+
+    for history in &sources.history {
+        if let Some(score) = match_terms(&terms, &[&history.title, strip_url_prefix(&history.url)])
+        {
+            candidates.push(web_page_suggestion(
+                OmniboxSuggestionKind::History,
+                &history.title,
+                &history.url,
+                700 + score * 10 + frecency(history, now),
+            ));
+        }
+    }
+
+    for path in &sources.files {
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let full_path = path.to_string_lossy();
+
+        if let Some(score) = match_terms(&terms, &[&name, &full_path]) {
+            candidates.push((
+                full_path.clone().into_owned(),
+                OmniboxSuggestion {
+                    kind: OmniboxSuggestionKind::File,
+                    title: name,
+                    description: full_path.into_owned(),
+                    action: OmniboxAction::Navigate(TabLocationData::FileViewer {
+                        path: path.clone(),
+                    }),
+                    relevance: 600 + score * 10,
+                },
+            ));
+        }
+    }
+
+    // 並べ替えは安定なので、同じ関連度なら先に加えた種類を優先する。
+    candidates.sort_by_key(|(_, suggestion)| Reverse(suggestion.relevance));
+
+    let mut seen = HashSet::new();
+    let suggestions = candidates
+        .into_iter()
+        .filter(|(key, _)| seen.insert(key.clone()))
+        .map(|(_, suggestion)| suggestion)
+        .take(MAX_SUGGESTIONS)
+        .collect();
+
+    OmniboxResult {
+        suggestions,
+        inline_completion,
+    }
+}
+
+fn web_page_suggestion(
+    kind: OmniboxSuggestionKind,
+    title: &str,
+    url: &str,
+    relevance: u32,
+) -> (String, OmniboxSuggestion) {
+    (
+        normalize_url(url),
+        OmniboxSuggestion {
+            kind,
+            title: title_or_url(title, url),
+            description: url.to_owned(),
+            action: OmniboxAction::Navigate(TabLocationData::WebPage {
+                url: url.to_owned(),
+            }),
+            relevance,
+        },
+    )
+}
+
+fn search_suggestion(
+    engine: &SearchEngineData,
+    query: &str,
+    relevance: u32,
+) -> (String, OmniboxSuggestion) {
+    let url = engine.search_url(query);
+
+    (
+        normalize_url(&url),
+        OmniboxSuggestion {
+            kind: OmniboxSuggestionKind::Search,
+            title: query.to_owned(),
+            description: format!("{}で検索", engine.name),
+            action: OmniboxAction::Navigate(TabLocationData::WebPage { url }),
+            relevance,
+        },
+    )
+}
+
+fn title_or_url(title: &str, url: &str) -> String {
+    if title.is_empty() { url } else { title }.to_owned()
+}
+
+/// 比べる時に省く、URLの先頭のスキームと`www.`を取り除く。
+fn strip_url_prefix(url: &str) -> &str {
+    let url = url
+        .strip_prefix("https://")
+        .or_else(|| url.strip_prefix("http://"))
+        .unwrap_or(url);
+
+    url.strip_prefix("www.").unwrap_or(url)
+}
+
+/// URLが入力で始まっていれば、入力の後ろに補う部分を返す。
+/// 入力がスキームや`www.`を含んでいなければ、それらを省いたURLと比べる。
+fn completion_of<'a>(text: &str, url: &'a str) -> Option<&'a str> {
+    let without_scheme = url
+        .strip_prefix("https://")
+        .or_else(|| url.strip_prefix("http://"))
+        .unwrap_or(url);
+
+    [url, without_scheme, strip_url_prefix(url)]
+        .into_iter()
+        .find_map(|candidate| {
+            let prefix = candidate.get(..text.len())?;
+            (prefix.eq_ignore_ascii_case(text) && candidate.len() > text.len())
+                .then(|| &candidate[text.len()..])
+        })
+}
+
+/// 全ての語が、いずれかの文字列に含まれていればスコアを返す。
+/// 語毎に、先頭に一致すれば3、単語の先頭に一致すれば2、途中に含まれていれば1を加える。
+fn match_terms(terms: &[String], haystacks: &[&str]) -> Option<u32> {
+    if terms.is_empty() {
+        return None;
+    }
+
+    let haystacks = haystacks
+        .iter()
+        .map(|haystack| haystack.to_lowercase())
+        .collect::<Vec<_>>();
+
+    terms
+        .iter()
+        .map(|term| {
+            haystacks
+                .iter()
+                .filter_map(|haystack| term_score(term, haystack))
+                .max()
+        })
+        .sum()
+}
+
+fn term_score(term: &str, haystack: &str) -> Option<u32> {
+    if haystack.starts_with(term) {
+        return Some(3);
+    }
+
+    let mut indices = haystack
+        .match_indices(term)
+        .map(|(index, _)| index)
+        .peekable();
+    indices.peek()?;
+
+    let is_word_start = indices.any(|index| {
+        !haystack[..index]
+            .chars()
+            .next_back()
+            .is_some_and(char::is_alphanumeric)
+    });
+
+    Some(if is_word_start { 2 } else { 1 })
+}
+
+/// 訪問回数と最後に訪問してからの日数から、`0..=200`の範囲で閲覧履歴の重みを決める。
+fn frecency(history: &HistoryData, now: i64) -> u32 {
+    let days = (now - history.last_visited_at).max(0) / (24 * 60 * 60);
+    let recency = match days {
+        0..=3 => 100,
+        4..=14 => 70,
+        15..=31 => 50,
+        32..=90 => 30,
+        _ => 10,
+    };
+
+    (history.visit_count.min(20) * recency / 10).min(200)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn engine(name: &str, keyword: &str, url_template: &str) -> SearchEngineData {
+        SearchEngineData {
+            id: Id::default(),
+            name: name.to_owned(),
+            keyword: keyword.to_owned(),
+            url_template: url_template.to_owned(),
+            suggest_url_template: None,
+        }
+    }
+
+    fn engines() -> Vec<SearchEngineData> {
+        vec![
+            engine(
+                "Google",
+                "g",
+                "https://www.google.com/search?q={searchTerms}",
+            ),
+            engine(
+                "Wikipedia",
+                "w",
+                "https://ja.wikipedia.org/w/index.php?search={searchTerms}",
+            ),
+        ]
+    }
+
+    fn parse(text: &str) -> Option<OmniboxInput> {
+        parse_omnibox_input(text, &engines())
+    }
+
+    fn url(url: &str) -> Option<OmniboxInput> {
+        Some(OmniboxInput::Url(url.to_owned()))
+    }
+
+    fn search(keyword: Option<&str>, query: &str) -> Option<OmniboxInput> {
+        Some(OmniboxInput::Search {
+            keyword: keyword.map(ToOwned::to_owned),
+            query: query.to_owned(),
+        })
+    }
+
+    fn history(url: &str, title: &str, visit_count: u32) -> HistoryData {
+        HistoryData {
+            url: url.to_owned(),
+            title: title.to_owned(),
+            visit_count,
+            last_visited_at: 0,
+        }
+    }
+
+    #[test]
+    fn adds_missing_scheme() {
+        assert_eq!(parse("example.com"), url("https://example.com/"));
+        assert_eq!(
+            parse("  example.com/path?q=1  "),
+            url("https://example.com/path?q=1")
+        );
+        assert_eq!(parse("192.168.0.1"), url("http://192.168.0.1/"));
+        assert_eq!(parse("[::1]:8080"), url("http://[::1]:8080/"));
+        assert_eq!(parse("http://example.com"), url("http://example.com/"));
+        assert_eq!(parse("about:blank"), url("about:blank"));
+        assert_eq!(parse("memex://history"), url("memex://history"));
+    }
+
+    #[test]
+    fn converts_idn_hosts() {
+        assert_eq!(parse("例え.jp"), url("https://xn--r8jz45g.jp/"));
+        assert_eq!(
+            parse("日本語.みんな"),
+            url("https://xn--wgv71a119e.xn--q9jyb4c/")
+        );
+    }
+
+    #[test]
+    fn opens_local_and_intranet_hosts() {
+        assert_eq!(parse("localhost"), url("http://localhost/"));
+        assert_eq!(parse("localhost:3000"), url("http://localhost:3000/"));
+        assert_eq!(
+            parse("app.localhost/login"),
+            url("http://app.localhost/login")
+        );
+        assert_eq!(parse("intranet:8080"), url("http://intranet:8080/"));
+        assert_eq!(
+            parse("wiki/ページ"),
+            url("http://wiki/%E3%83%9A%E3%83%BC%E3%82%B8")
+        );
+        // ポートもパスもない1語は、ホスト名ではなく検索語とみなす。
+        assert_eq!(parse("intranet"), search(None, "intranet"));
+    }
+
+    #[test]
+    fn distinguishes_urls_from_searches() {
+        assert_eq!(parse(""), None);
+        assert_eq!(parse("rust lang"), search(None, "rust lang"));
+        assert_eq!(parse("1.5"), search(None, "1.5"));
+        assert_eq!(parse("user@example.com"), search(None, "user@example.com"));
+        assert_eq!(parse("foo.bar123"), search(None, "foo.bar123"));
+        assert_eq!(parse("?example.com"), search(None, "example.com"));
+        assert_eq!(parse("?"), None);
+    }
+
+    #[test]
+    fn uses_keyword_shortcuts() {
+        assert_eq!(parse("g rust lang"), search(Some("g"), "rust lang"));
+        assert_eq!(parse("w  東京 "), search(Some("w"), "東京"));
+        // キーワードだけでは検索しない。
+        assert_eq!(parse("w"), search(None, "w"));
+        // 登録されていないキーワードは、検索語の一部とみなす。
+        assert_eq!(parse("x rust"), search(None, "x rust"));
+
+        let engines = engines();
+        let result = omnibox_suggestions(
+            "w 東京",
+            &engines,
+            engines.first(),
+            &OmniboxSources::default(),
+            0,
+        );
+        let first = &result.suggestions[0];
+        assert_eq!(first.kind, OmniboxSuggestionKind::Search);
+        assert_eq!(first.description, "Wikipediaで検索");
+        assert!(matches!(
+            &first.action,
+            OmniboxAction::Navigate(TabLocationData::WebPage { url })
+                if url == "https://ja.wikipedia.org/w/index.php?search=%E6%9D%B1%E4%BA%AC"
+        ));
+    }
+
+    #[test]
+    fn ranks_suggestions() {
+        let engines = engines();
+        let workspace_id = Id::default();
+        let tab_id = Id::default();
+        let sources = OmniboxSources {
+            history: vec![
+                history("https://learn.example.com/rust", "Learn Rust", 3),
+                history("https://doc.rust-lang.org/book/", "The Rust Book", 20),
+                history("https://example.com/trust", "Trust", 1),
+            ],
+            bookmarks: vec![BookmarkData {
+                id: Id::default(),
+                workspace_id: Some(workspace_id),
+                url: "https://crates.io/".to_owned(),
+                title: "Rust crates".to_owned(),
+                created_at: 0,
+            }],
+            open_tabs: vec![OpenTabEntry {
+                workspace_id,
+                tab_id,
+                title: "Rust Playground".to_owned(),
+                url: "https://play.rust-lang.org/".to_owned(),
+            }],
+            files: vec![PathBuf::from("/notes/rust.md")],
+        };
+
+        let result = omnibox_suggestions("rust", &engines, engines.first(), &sources, 0);
+        let kinds = result
+            .suggestions
+            .iter()
+            .map(|suggestion| (suggestion.kind, suggestion.title.as_str()))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            kinds,
+            [
+                (OmniboxSuggestionKind::Search, "rust"),
+                (OmniboxSuggestionKind::OpenTab, "Rust Playground"),
+                (OmniboxSuggestionKind::Bookmark, "Rust crates"),
+                (OmniboxSuggestionKind::History, "The Rust Book"),
+                (OmniboxSuggestionKind::History, "Learn Rust"),
+                (OmniboxSuggestionKind::History, "Trust"),
+                (OmniboxSuggestionKind::File, "rust.md"),
+            ]
+        );
+        assert_eq!(result.inline_completion, None);
+    }
+
+    #[test]
+    fn completes_visited_url_inline() {
+        let engines = engines();
+        let sources = OmniboxSources {
+            history: vec![
+                history("https://www.example.com/docs", "Docs", 2),
+                history("https://example.org/", "Example", 10),
+            ],
+            ..Default::default()
+        };
+
+        let result = omnibox_suggestions("exa", &engines, engines.first(), &sources, 0);
+        assert_eq!(result.inline_completion.as_deref(), Some("mple.org/"));
+        assert_eq!(result.suggestions[0].kind, OmniboxSuggestionKind::History);
+        assert_eq!(result.suggestions[0].title, "Example");
+
+        // 同じページを指す候補は、最も関連するものだけを残す。
+        let result = omnibox_suggestions("example.org", &engines, engines.first(), &sources, 0);
+        let urls = result
+            .suggestions
+            .iter()
+            .filter(|suggestion| suggestion.description == "https://example.org/")
+            .count();
+        assert_eq!(urls, 1);
+    }
+}
@@ -0,0 +1,248 @@
+use regex::Regex;
+use url::{Position, Url};
+
+/// URLのオリジンを`scheme://host:port`の形で返す。
+/// `file:`や`data:`のように、オリジンが定まらないURLの場合は`None`を返す。
+pub fn origin_of(url: &str) -> Option<String> {
+    let origin = Url::parse(url).ok()?.origin();
+
+    origin.is_tuple().then(|| origin.ascii_serialization())
+}
+
+/// 同じページを指すURLを同じ文字列にする。
+/// フラグメントと、`utm_`で始まる計測用のクエリを取り除く。解析できないURLはそのまま返す。
+pub fn normalize_url(url: &str) -> String {
+    let Ok(mut url) = Url::parse(url) else {
+        return url.to_owned();
+    };
+
+    url.set_fragment(None);
+
+    // 取り除くものがなければ、クエリの書き方を変えないようそのままにする。
+    if url.query_pairs().any(|(key, _)| key.starts_with("utm_")) {
+        let query = url
+            .query_pairs()
+            .filter(|(key, _)| !key.starts_with("utm_"))
+            .map(|(key, value)| (key.into_owned(), value.into_owned()))
+            .collect::<Vec<_>>();
+
+        if query.is_empty() {
+            url.set_query(None);
+        } else {
+            url.query_pairs_mut().clear().extend_pairs(query);
+        }
+    }
+
+    url.into()
+}
+
+/// 何にも一致しない正規表現。形式が不正なパターンに使う。
+const NEVER_MATCHING_REGEX: &str = r"[^\s\S]";
+
+/// サイトのパターンを判定するURL。フラグメントは除く。
+/// レンダラーでは`location.href`から`#`以降を除いたものを使う。
+pub(crate) fn site_pattern_target(url: &Url) -> &str {
+    &url[..Position::AfterQuery]
+}
+
+/// `@match`の形のサイトのパターンを、[`site_pattern_target`]の全体に一致させる正規表現に変換する。
+/// ユーザースクリプトはこの正規表現をレンダラーのJavaScriptで、ユーザースタイルはRustで評価する。
+///
+/// - `<all_urls>`は、`http`・`https`・`file`・`ftp`の全てのURLに一致する。
+/// - `<scheme>://<host>/<path>`では、スキームの`*`は`http`と`https`に、ホストの`*`は全てのホストに、
+///   `*.`で始まるホストはそのドメインとサブドメインに、パスの`*`は任意の文字列に一致する。
+///   ホストにポートがなければ、全てのポートに一致する。
+/// - 形式が不正なパターンは、何にも一致しない。
+pub(crate) fn match_pattern_regex(pattern: &str) -> String {
+    if pattern == "<all_urls>" {
+        return "^(https?|file|ftp)://".to_owned();
+    }
+
+    let Some((scheme, rest)) = pattern.split_once("://") else {
+        return NEVER_MATCHING_REGEX.to_owned();
+    };
+    let (host, path) = rest.split_at(rest.find('/').unwrap_or(rest.len()));
+
+    let scheme = match scheme {
+        "*" => "https?".to_owned(),
+        scheme if scheme.contains('*') => return NEVER_MATCHING_REGEX.to_owned(),
+        scheme => escape_glob(scheme, ""),
+    };
+    let port = if host.is_empty() || host.contains(':') {
+        ""
+    } else {
+        r"(:\d+)?"
+    };
+    let host = match host {
+        "*" => "[^/]*".to_owned(),
+        host => match host.strip_prefix("*.") {
+            Some(domain) if !domain.contains('*') => {
+                format!(r"([^/]+\.)?{}", escape_glob(domain, ""))
+            }
+            _ if host.contains('*') => return NEVER_MATCHING_REGEX.to_owned(),
+            _ => escape_glob(host, ""),
+        },
+    };
+    let path = escape_glob(if path.is_empty() { "/" } else { path }, ".*");
+
+    format!("^{scheme}://{host}{port}{path}$")
+}
+
+/// `@include`・`@exclude`の形のパターンを正規表現に変換する。
+/// `/.../`で囲まれていればそのまま正規表現として扱い、それ以外は`*`を任意の文字列とするURL全体のパターンとして扱う。
+pub(crate) fn glob_pattern_regex(pattern: &str) -> String {
+    if let Some(regex) = pattern
+        .strip_prefix('/')
+        .and_then(|pattern| pattern.strip_suffix('/'))
+        .filter(|regex| !regex.is_empty())
+    {
+        return regex.to_owned();
+    }
+
+    format!("^{}$", escape_glob(pattern, ".*"))
+}
+
+/// URLが`@match`の形のサイトのパターンに一致するか。[`match_pattern_regex`]の正規表現で判定する。
+pub(crate) fn matches_site_pattern(pattern: &str, url: &Url) -> bool {
+    Regex::new(&match_pattern_regex(pattern))
+        .is_ok_and(|regex| regex.is_match(site_pattern_target(url)))
+}
+
+/// 正規表現の特殊文字をエスケープし、`*`を`wildcard`に置き換える。
+fn escape_glob(glob: &str, wildcard: &str) -> String {
+    glob.split('*')
+        .map(regex::escape)
+        .collect::<Vec<_>>()
+        .join(wildcard)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matches(pattern: &str, url: &str) -> bool {
+        matches_site_pattern(pattern, &Url::parse(url).unwrap())
+    }
+
+    #[test]
+    fn matches_all_urls() {
+        assert!(matches("<all_urls>", "https://example.com/"));
+        assert!(matches("<all_urls>", "http://example.com/path?q=1"));
+        assert!(matches("<all_urls>", "file:///home/user/index.html"));
+        assert!(!matches("<all_urls>", "memex://history"));
+        assert!(!matches("<all_urls>", "about:blank"));
+    }
+
+    #[test]
+    fn matches_scheme_wildcard() {
+        assert!(matches("*://example.com/*", "http://example.com/"));
+        assert!(matches("*://example.com/*", "https://example.com/"));
+        assert!(!matches("*://example.com/*", "ftp://example.com/"));
+        assert!(matches("https://example.com/*", "https://example.com/"));
+        assert!(!matches("https://example.com/*", "http://example.com/"));
+        assert!(!matches("ht*://example.com/*", "https://example.com/"));
+    }
+
+    #[test]
+    fn matches_subdomains() {
+        assert!(matches("https://*.example.com/*", "https://example.com/"));
+        assert!(matches(
+            "https://*.example.com/*",
+            "https://www.example.com/"
+        ));
+        assert!(matches(
+            "https://*.example.com/*",
+            "https://a.b.example.com/"
+        ));
+        assert!(!matches(
+            "https://*.example.com/*",
+            "https://notexample.com/"
+        ));
+        assert!(!matches(
+            "https://*.example.com/*",
+            "https://example.com.evil.test/"
+        ));
+        assert!(matches("https://*/*", "https://example.org/"));
+        assert!(!matches(
+            "https://example.com/*",
+            "https://www.example.com/"
+        ));
+    }
+
+    #[test]
+    fn matches_ports() {
+        assert!(matches("http://localhost/*", "http://localhost:8080/"));
+        assert!(matches("http://localhost:8080/*", "http://localhost:8080/"));
+        assert!(!matches(
+            "http://localhost:8080/*",
+            "http://localhost:3000/"
+        ));
+    }
+
+    #[test]
+    fn matches_path_globs() {
+        assert!(matches(
+            "https://example.com/docs/*",
+            "https://example.com/docs/"
+        ));
+        assert!(matches(
+            "https://example.com/docs/*",
+            "https://example.com/docs/a/b"
+        ));
+        assert!(!matches(
+            "https://example.com/docs/*",
+            "https://example.com/blog/"
+        ));
+        assert!(matches(
+            "https://example.com/*.pdf",
+            "https://example.com/a/b.pdf"
+        ));
+        assert!(!matches(
+            "https://example.com/*.pdf",
+            "https://example.com/a.pdfx"
+        ));
+        // パスのない`@match`は、トップページだけに一致する。
+        assert!(matches("https://example.com", "https://example.com/"));
+        assert!(!matches("https://example.com", "https://example.com/a"));
+    }
+
+    #[test]
+    fn matches_query_but_not_fragment() {
+        assert!(matches(
+            "https://example.com/search?q=*",
+            "https://example.com/search?q=rust"
+        ));
+        assert!(matches("https://example.com/", "https://example.com/#top"));
+        assert!(!matches("https://example.com/", "https://example.com/?q=1"));
+    }
+
+    #[test]
+    fn escapes_regex_characters() {
+        assert!(matches(
+            "https://example.com/a+b",
+            "https://example.com/a+b"
+        ));
+        assert!(!matches(
+            "https://example.com/a+b",
+            "https://example.com/aab"
+        ));
+        assert!(!matches("https://example.com/*", "https://exampleXcom/"));
+    }
+
+    #[test]
+    fn rejects_malformed_patterns() {
+        assert!(!matches("example.com", "https://example.com/"));
+        assert!(!matches("https://www.*.com/*", "https://www.example.com/"));
+        assert!(!matches("", "https://example.com/"));
+    }
+
+    #[test]
+    fn converts_globs_and_regexes() {
+        assert_eq!(
+            glob_pattern_regex("https://example.com/*"),
+            r"^https://example\.com/.*$"
+        );
+        assert_eq!(glob_pattern_regex(r"/^https:\/\/a\./"), r"^https:\/\/a\.");
+        assert_eq!(glob_pattern_regex("//"), r"^//$");
+    }
+}
@@ -0,0 +1,376 @@
+use std::{cmp::Reverse, path::PathBuf};
+
+use crate::{Id, TabMarker, WorkspaceMarker};
+
+/// 1文字一致する毎の点数。
+const SCORE_MATCH: i32 = 16;
+/// 直前の文字に続けて一致した場合の加点。
+const BONUS_CONSECUTIVE: i32 = 8;
+/// 単語の先頭で一致した場合の加点。
+const BONUS_WORD_START: i32 = 10;
+/// 文字列の先頭で一致した場合の加点。
+const BONUS_FIRST: i32 = 12;
+/// 一致した文字の間が空いた場合の減点。空いた文字数に比例して増える。
+const PENALTY_GAP_START: i32 = 3;
+const PENALTY_GAP_EXTENSION: i32 = 1;
+
+/// コマンドパレットで選んだ時の動作。
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PaletteTarget {
+    /// アプリのコマンド。アクションの名前を持つ。
+    Command(String),
+    Tab {
+        workspace_id: Id<WorkspaceMarker>,
+        tab_id: Id<TabMarker>,
+    },
+    Workspace(Id<WorkspaceMarker>),
+    Bookmark {
+        url: String,
+    },
+    File(PathBuf),
+}
+
+impl PaletteTarget {
+    /// 一致の度合いが同じ場合に、上に表示する順。
+    fn priority(&self) -> u8 {
+        match self {
+            Self::Command(_) => 0,
+            Self::Tab { .. } => 1,
+            Self::Workspace(_) => 2,
+            Self::Bookmark { .. } => 3,
+            Self::File(_) => 4,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct PaletteItem {
+    pub target: PaletteTarget,
+    pub title: String,
+    /// タイトルの横に表示する、キーバインディング・URL・パスなど。タイトルに一致しない場合にも探す。
+    pub detail: String,
+}
+
+/// 文字列が検索語に曖昧一致した結果。
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FuzzyMatch {
+    pub score: i32,
+    /// 一致した文字の位置。`char`単位で数える。
+    pub positions: Vec<usize>,
+}
+
+#[derive(Clone, Debug)]
+pub struct PaletteMatch {
+    pub item: PaletteItem,
+    pub score: i32,
+    /// タイトルで一致した文字の位置。詳細で一致した場合は空。
+    pub positions: Vec<usize>,
+}
+
+/// 検索語の文字が、順番通りに文字列に含まれていれば一致とする。大文字と小文字は区別しない。
+/// 続けて一致する文字や、単語の先頭で一致する文字が多いほど点数が高くなる。
+/// 検索語の空白は無視する。
+pub fn fuzzy_match(query: &str, text: &str) -> Option<FuzzyMatch> {
+    let query = query
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .map(fold_case)
+        .collect::<Vec<_>>();
+    if query.is_empty() {
+        return Some(FuzzyMatch {
+            score: 0,
+            positions: Vec::new(),
+        });
+    }
+
+    let original = text.chars().collect::<Vec<_>>();
+    let text = original.iter().copied().map(fold_case).collect::<Vec<_>>();
+    if query.len() > text.len() {
+        return None;
+    }
+
+    let bonuses = (0..original.len())
+        .map(|index| position_bonus(&original, index))
+        .collect::<Vec<_>>();
+
+    // `scores[i][j]`は、検索語の`i`文字目を文字列の`j`文字目に一致させた場合の最高点。
+    let mut scores = vec![vec![None::<i32>; text.len()]; query.len()];
+    let mut previous = vec![vec![0usize; text.len()]; query.len()];
+
+    for (j, c) in text.iter().enumerate() {
+        if *c == query[0] {
+            scores[0][j] = Some(SCORE_MATCH + bonuses[j] - (j as i32).min(10));
+        }
+    }
+
+    for i in 1..query.len() {
+        // 2文字以上離れた一致からの最高点。間の文字数による減点を後から引けるよう、位置を足して持つ。
+        let mut best_gapped = None::<(i32, usize)>;
+
+        for j in i..text.len() {
+            if j >= 2
+                && let Some(score) = scores[i - 1][j - 2]
+            {
+                let candidate = score + (j - 2) as i32;
+                if best_gapped.is_none_or(|(best, _)| candidate > best) {
+                    best_gapped = Some((candidate, j - 2));
+                }
+            }
+
+            if text[j] != query[i] {
+                continue;
+            }
+
+            let consecutive = scores[i - 1][j - 1]
+                .map(|score| (score + SCORE_MATCH + bonuses[j] + BONUS_CONSECUTIVE, j - 1));
+            let gapped = best_gapped.map(|(score, k)| {
+                let gap = (j - k - 1) as i32;
+                (
+                    score - k as i32 + SCORE_MATCH + bonuses[j]
+                        - PENALTY_GAP_START
+                        - PENALTY_GAP_EXTENSION * (gap - 1),
+                    k,
+                )
+            });
+
+            if let Some((score, k)) = [consecutive, gapped]
+                .into_iter()
+                .flatten()
+                .max_by_key(|(score, _)| *score)
+            {
+                scores[i][j] = Some(score);
+                previous[i][j] = k;
+            }
+        }
+    }
+
+    let last = query.len() - 1;
+    let (mut j, score) = scores[last]
+        .iter()
+        .enumerate()
+        .filter_map(|(j, score)| score.map(|score| (j, score)))
+        .max_by_key(|(j, score)| (*score, Reverse(*j)))?;
+
+    let mut positions = vec![0; query.len()];
+    for i in (0..query.len()).rev() {
+        positions[i] = j;
+        if i > 0 {
+            j = previous[i][j];
+        }
+    }
+
+    // 同じ点数なら、短い文字列を上にする。
+    let length_penalty = (text.len() as i32 - query.len() as i32) / 8;
+
+    Some(FuzzyMatch {
+        score: score - length_penalty,
+        positions,
+    })
+}
+
+fn fold_case(c: char) -> char {
+    c.to_lowercase().next().unwrap_or(c)
+}
+
+/// 文字列の先頭、区切り文字の直後、小文字から大文字に変わる所を単語の先頭とみなす。
+fn position_bonus(text: &[char], index: usize) -> i32 {
+    if index == 0 {
+        return BONUS_FIRST;
+    }
+
+    let previous = text[index - 1];
+    let current = text[index];
+    if !previous.is_alphanumeric() && current.is_alphanumeric()
+        || previous.is_lowercase() && current.is_uppercase()
+    {
+        BONUS_WORD_START
+    } else {
+        0
+    }
+}
+
+/// 検索語に一致する項目を、一致の度合いが高い順に最大`limit`件返す。
+/// タイトルに一致しない項目は詳細で探し、タイトルでの一致より低く扱う。
+/// 検索語が空なら、渡された順のまま返す。
+pub fn rank_palette_items(query: &str, items: Vec<PaletteItem>, limit: usize) -> Vec<PaletteMatch> {
+    if query.trim().is_empty() {
+        return items
+            .into_iter()
+            .take(limit)
+            .map(|item| PaletteMatch {
+                item,
+                score: 0,
+                positions: Vec::new(),
+            })
+            .collect();
+    }
+
+    let mut matches = items
+        .into_iter()
+        .filter_map(|item| {
+            if let Some(title_match) = fuzzy_match(query, &item.title) {
+                return Some(PaletteMatch {
+                    item,
+                    score: title_match.score,
+                    positions: title_match.positions,
+                });
+            }
+
+            let detail_match = fuzzy_match(query, &item.detail)?;
+            Some(PaletteMatch {
+                item,
+                score: detail_match.score / 2,
+                positions: Vec::new(),
+            })
+        })
+        .collect::<Vec<_>>();
+
+    matches.sort_by_key(|palette_match| {
+        (
+            Reverse(palette_match.score),
+            palette_match.item.target.priority(),
+        )
+    });
+    matches.truncate(limit);
+
+    matches
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(target: PaletteTarget, title: &str, detail: &str) -> PaletteItem {
+        PaletteItem {
+            target,
+            title: title.to_owned(),
+            detail: detail.to_owned(),
+        }
+    }
+
+    fn command(title: &str) -> PaletteItem {
+        item(PaletteTarget::Command(title.to_owned()), title, "")
+    }
+
+    fn score(query: &str, text: &str) -> i32 {
+        fuzzy_match(query, text).unwrap().score
+    }
+
+    #[test]
+    fn folds_case() {
+        let lower = fuzzy_match("newtab", "New Tab").unwrap();
+        let upper = fuzzy_match("NEW TAB", "New Tab").unwrap();
+
+        assert_eq!(lower, upper);
+        assert_eq!(lower.positions, [0, 1, 2, 4, 5, 6]);
+        assert_eq!(
+            fuzzy_match("ÉCOLE", "école").unwrap().positions,
+            [0, 1, 2, 3, 4]
+        );
+        assert_eq!(fuzzy_match("tabs", "New Tab"), None);
+    }
+
+    #[test]
+    fn prefers_word_starts() {
+        // `nt`は、`New Tab`の単語の先頭に一致させる。
+        let word_starts = fuzzy_match("nt", "New Tab").unwrap();
+        assert_eq!(word_starts.positions, [0, 4]);
+
+        assert!(score("nt", "New Tab") > score("nt", "Anthem"));
+        assert!(score("ct", "closeTab") > score("ct", "Inactive"));
+        assert!(score("tab", "Close Tab") > score("tab", "Establish"));
+    }
+
+    #[test]
+    fn prefers_consecutive_matches() {
+        assert!(score("tab", "Tab Groups") > score("tab", "Toggle All Bookmarks"));
+        assert!(score("open", "Open File") > score("open", "Online Preview Engine"));
+    }
+
+    #[test]
+    fn penalizes_gaps() {
+        assert!(score("ab", "a_b") > score("ab", "a___b"));
+        assert!(score("ab", "a___b") > score("ab", "a_________b"));
+        // 途中の一致より、先頭の一致を優先する。
+        assert!(score("tab", "tab") > score("tab", "xxtab"));
+    }
+
+    #[test]
+    fn counts_positions_in_chars() {
+        let japanese = fuzzy_match("新タブ", "新しいタブを開く").unwrap();
+        assert_eq!(japanese.positions, [0, 3, 4]);
+
+        let mixed = fuzzy_match("rust入門", "はじめてのRust入門").unwrap();
+        assert_eq!(mixed.positions, [5, 6, 7, 8, 9, 10]);
+    }
+
+    #[test]
+    fn returns_empty_positions_for_detail_matches() {
+        let items = vec![
+            item(
+                PaletteTarget::Bookmark {
+                    url: "https://example.com/rust".to_owned(),
+                },
+                "プログラミング言語",
+                "https://example.com/rust",
+            ),
+            command("Rust Playground"),
+        ];
+
+        let matches = rank_palette_items("rust", items, 10);
+        assert_eq!(matches.len(), 2);
+
+        assert_eq!(matches[0].item.title, "Rust Playground");
+        assert_eq!(matches[0].positions, [0, 1, 2, 3]);
+
+        assert_eq!(matches[1].item.title, "プログラミング言語");
+        assert!(matches[1].positions.is_empty());
+        assert!(matches[1].score < matches[0].score);
+    }
+
+    #[test]
+    fn breaks_ties_by_target_priority() {
+        let workspace_id = Id::default();
+        let items = vec![
+            item(
+                PaletteTarget::File(PathBuf::from("/notes/Notes")),
+                "Notes",
+                "",
+            ),
+            item(
+                PaletteTarget::Bookmark {
+                    url: "https://example.com/".to_owned(),
+                },
+                "Notes",
+                "",
+            ),
+            item(PaletteTarget::Workspace(workspace_id), "Notes", ""),
+            item(
+                PaletteTarget::Tab {
+                    workspace_id,
+                    tab_id: Id::default(),
+                },
+                "Notes",
+                "",
+            ),
+            command("Notes"),
+        ];
+
+        let priorities = rank_palette_items("notes", items, 10)
+            .iter()
+            .map(|palette_match| palette_match.item.target.priority())
+            .collect::<Vec<_>>();
+        assert_eq!(priorities, [0, 1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn keeps_order_for_empty_query() {
+        let items = vec![command("b"), command("a"), command("c")];
+
+        let titles = rank_palette_items("  ", items, 2)
+            .into_iter()
+            .map(|palette_match| palette_match.item.title)
+            .collect::<Vec<_>>();
+        assert_eq!(titles, ["b", "a"]);
+    }
+}
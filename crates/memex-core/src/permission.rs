@@ -0,0 +1,139 @@
+use memex_cef::{PermissionDecision, PermissionKind, PermissionRequest};
+
+use crate::{
+    Id, TabMarker, WorkspaceMarker,
+    db::{Database, SitePermissionData, get_site_permission, set_site_permission},
+};
+
+/// 権限の判断をどの範囲で記憶するか。
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SitePermissionScope {
+    /// 記憶せず、今回の要求にのみ返答する。
+    Once,
+    /// 全てのワークスペースで記憶する。
+    Global,
+    /// 要求元のタブがあるワークスペースでのみ記憶する。
+    Workspace,
+}
+
+/// タブから届いた権限の要求。
+/// 保存済みの判断の適用と、ユーザーの判断の保存を行う。
+pub struct SitePermissionPrompt {
+    db: Database,
+    tab_id: Id<TabMarker>,
+    workspace_id: Id<WorkspaceMarker>,
+    request: PermissionRequest,
+}
+
+impl SitePermissionPrompt {
+    pub(crate) fn new(
+        db: Database,
+        tab_id: Id<TabMarker>,
+        workspace_id: Id<WorkspaceMarker>,
+        request: PermissionRequest,
+    ) -> Self {
+        Self {
+            db,
+            tab_id,
+            workspace_id,
+            request,
+        }
+    }
+
+    pub fn tab_id(&self) -> Id<TabMarker> {
+        self.tab_id
+    }
+
+    pub fn workspace_id(&self) -> Id<WorkspaceMarker> {
+        self.workspace_id
+    }
+
+    pub fn prompt_id(&self) -> Option<u64> {
+        self.request.prompt_id()
+    }
+
+    pub fn origin(&self) -> &str {
+        self.request.origin()
+    }
+
+    pub fn kinds(&self) -> &[PermissionKind] {
+        self.request.kinds()
+    }
+
+    /// 保存済みの判断を調べる。
+    /// 要求された権限が全て許可済みなら許可、一つでも却下済みなら却下となる。
+    /// どちらでもなければ`None`を返す。
+    pub async fn stored_decision(&self) -> anyhow::Result<Option<PermissionDecision>> {
+        let mut all_allowed = !self.kinds().is_empty();
+
+        for kind in self.kinds() {
+            match get_site_permission(&self.db, self.origin(), Some(self.workspace_id), *kind)
+                .await?
+            {
+                Some(PermissionDecision::Deny) => return Ok(Some(PermissionDecision::Deny)),
+                Some(PermissionDecision::Allow) => {}
+                None => all_allowed = false,
+            }
+        }
+
+        Ok(all_allowed.then_some(PermissionDecision::Allow))
+    }
+
+    /// 保存済みの判断があれば、それで返答して`None`を返す。
+    /// なければユーザーに尋ねる必要があるので、自身をそのまま返す。
+    pub async fn resolve_stored(self) -> anyhow::Result<Option<Self>> {
+        Ok(match self.stored_decision().await? {
+            Some(decision) => {
+                self.request.respond(decision);
+                None
+            }
+            None => Some(self),
+        })
+    }
+
+    /// 要求に返答し、`scope`に応じて判断を保存する。
+    pub async fn respond(
+        self,
+        decision: PermissionDecision,
+        scope: SitePermissionScope,
+    ) -> anyhow::Result<()> {
+        let Self {
+            db,
+            workspace_id,
+            request,
+            ..
+        } = self;
+
+        let origin = request.origin().to_owned();
+        let kinds = request.kinds().to_vec();
+
+        // 保存を待たせないよう、先にページへ返答する。
+        request.respond(decision);
+
+        let workspace_id = match scope {
+            SitePermissionScope::Once => return Ok(()),
+            SitePermissionScope::Global => None,
+            SitePermissionScope::Workspace => Some(workspace_id),
+        };
+
+        for permission in kinds {
+            set_site_permission(
+                &db,
+                &SitePermissionData {
+                    origin: origin.clone(),
+                    workspace_id,
+                    permission,
+                    decision,
+                },
+            )
+            .await?;
+        }
+
+        Ok(())
+    }
+
+    /// 判断を保存せずにプロンプトを閉じる。
+    pub fn dismiss(self) {
+        self.request.dismiss();
+    }
+}
@@ -0,0 +1,684 @@
+use std::{collections::HashMap, io, path::PathBuf};
+
+use anyhow::Context as _;
+use ego_tree::NodeRef;
+use scraper::{ElementRef, Html, Node, Selector, node::Element};
+use url::Url;
+
+use crate::{Id, TabMarker, fs::DataContext};
+
+/// 本文の候補から外す要素の`class`・`id`に含まれる語。
+const NEGATIVE_HINTS: &[&str] = &[
+    "comment",
+    "footer",
+    "sidebar",
+    "sidebox",
+    "nav",
+    "menu",
+    "share",
+    "social",
+    "related",
+    "promo",
+    "advert",
+    "banner",
+    "sponsor",
+    "popup",
+    "cookie",
+    "breadcrumb",
+    "subscribe",
+];
+
+/// 本文から取り除く要素。
+const REMOVED_TAGS: &[&str] = &[
+    "script", "style", "noscript", "nav", "aside", "footer", "header", "form", "iframe", "button",
+    "input", "select", "textarea", "svg", "canvas", "template", "object", "embed",
+];
+
+/// 本文に残す要素。これ以外の要素は、中身だけを残す。
+const ALLOWED_TAGS: &[&str] = &[
+    "p",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "ul",
+    "ol",
+    "li",
+    "blockquote",
+    "pre",
+    "code",
+    "em",
+    "i",
+    "strong",
+    "b",
+    "a",
+    "img",
+    "figure",
+    "figcaption",
+    "br",
+    "hr",
+    "table",
+    "thead",
+    "tbody",
+    "tr",
+    "th",
+    "td",
+];
+
+const VOID_TAGS: &[&str] = &["img", "br", "hr"];
+
+/// 本文に残すURLのスキーム。`javascript:`や`data:`などは、リンクごと取り除く。
+const ALLOWED_SCHEMES: &[&str] = &["http", "https"];
+/// リンクにだけ許すスキーム。
+const ALLOWED_LINK_SCHEMES: &[&str] = &["mailto"];
+
+/// タブのリーダービューを書き出すパス。
+pub(crate) fn reader_view_path(data: &DataContext, tab_id: Id<TabMarker>) -> PathBuf {
+    data.reader_dir().join(format!("{}.html", *tab_id))
+}
+
+/// リーダービューのファイルを削除する。タブを閉じた時に使う。
+pub(crate) async fn remove_reader_view(data: &DataContext, tab_id: Id<TabMarker>) {
+    match async_fs::remove_file(reader_view_path(data, tab_id)).await {
+        Ok(()) => {}
+        Err(error) if error.kind() == io::ErrorKind::NotFound => {}
+        Err(error) => log::warn!("リーダービューの削除に失敗しました：{error}"),
+    }
+}
+
+/// リーダーモードで取り出した記事。
+#[derive(Clone, Debug)]
+pub struct Article {
+    pub source_url: String,
+    pub title: String,
+    pub byline: Option<String>,
+    /// 公開日時。ページに書かれている形式のまま。
+    pub published: Option<String>,
+    /// 整形した本文のHTML。
+    pub content_html: String,
+    /// 本文のMarkdown。
+    pub markdown: String,
+}
+
+impl Article {
+    /// リーダービューとして表示する、単体のHTMLの文書を作る。
+    pub fn to_reader_html(&self) -> String {
+        let meta = [self.byline.as_deref(), self.published.as_deref()]
+            .into_iter()
+            .flatten()
+            .map(escape_html)
+            .collect::<Vec<_>>()
+            .join(" · ");
+
+        format!(
+            r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>{title}</title>
+<style>
+:root {{ color-scheme: light dark; }}
+body {{ max-width: 42em; margin: 0 auto; padding: 3em 1.5em; font: 18px/1.7 serif; }}
+img {{ max-width: 100%; height: auto; }}
+pre {{ overflow-x: auto; }}
+.meta {{ color: GrayText; font-size: 0.9em; }}
+</style>
+</head>
+<body>
+<h1>{title}</h1>
+<p class="meta">{meta}</p>
+<p class="meta"><a href="{source_url}">{source_url}</a></p>
+<article>
+{content}
+</article>
+</body>
+</html>
+"#,
+            title = escape_html(&self.title),
+            source_url = escape_html(&self.source_url),
+            content = self.content_html,
+        )
+    }
+
+    /// メモとして保存する、見出しと出典を含めたMarkdownの文書を作る。
+    pub fn to_note(&self) -> String {
+        let mut note = format!("# {}\n\n", self.title);
+
+        if let Some(byline) = &self.byline {
+            note.push_str(&format!("- 著者: {byline}\n"));
+        }
+        if let Some(published) = &self.published {
+            note.push_str(&format!("- 公開日: {published}\n"));
+        }
+        note.push_str(&format!("- 出典: <{}>\n\n", self.source_url));
+        note.push_str(&self.markdown);
+        note.push('\n');
+
+        note
+    }
+}
+
+/// ページのHTMLから記事を取り出す。
+/// 段落の文字数で要素に点数を付け、最も点数の高い要素を本文とする。
+pub fn extract_article(html: &str, url: &str) -> anyhow::Result<Article> {
+    let base_url = Url::parse(url).context("ページのURLが不正です。")?;
+    let document = Html::parse_document(html);
+
+    let title = meta_content(&document, r#"meta[property="og:title"]"#)
+        .or_else(|| first_text(&document, "title"))
+        .or_else(|| first_text(&document, "h1"))
+        .unwrap_or_else(|| url.to_owned());
+    let byline = meta_content(&document, r#"meta[name="author"]"#)
+        .or_else(|| first_text(&document, r#"[rel="author"], [itemprop="author"], .byline"#));
+    let published = meta_content(&document, r#"meta[property="article:published_time"]"#)
+        .or_else(|| meta_content(&document, r#"meta[itemprop="datePublished"]"#))
+        .or_else(|| {
+            let selector = Selector::parse("time[datetime]").unwrap();
+            document
+                .select(&selector)
+                .next()
+                .and_then(|time| time.value().attr("datetime"))
+                .map(ToOwned::to_owned)
+        });
+
+    let content = find_content(&document).context("本文が見つかりませんでした。")?;
+
+    let mut content_html = String::new();
+    for child in content.children() {
+        render_html(child, &base_url, &mut content_html);
+    }
+
+    let mut markdown = String::new();
+    for child in content.children() {
+        render_markdown(child, &base_url, 0, &mut markdown);
+    }
+
+    Ok(Article {
+        source_url: url.to_owned(),
+        title,
+        byline,
+        published,
+        content_html,
+        markdown: normalize_markdown(&markdown),
+    })
+}
+
+fn meta_content(document: &Html, selector: &str) -> Option<String> {
+    let selector = Selector::parse(selector).unwrap();
+
+    document
+        .select(&selector)
+        .find_map(|element| element.value().attr("content"))
+        .map(|content| content.trim().to_owned())
+        .filter(|content| !content.is_empty())
+}
+
+fn first_text(document: &Html, selector: &str) -> Option<String> {
+    let selector = Selector::parse(selector).unwrap();
+
+    document
+        .select(&selector)
+        .map(|element| collapse_whitespace(&element.text().collect::<String>()))
+        .find(|text| !text.is_empty())
+}
+
+fn has_negative_hint(element: &Element) -> bool {
+    let hints = element
+        .attr("class")
+        .into_iter()
+        .chain(element.attr("id"))
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase();
+
+    NEGATIVE_HINTS.iter().any(|hint| hints.contains(hint))
+}
+
+fn is_removed(element: &Element) -> bool {
+    REMOVED_TAGS.contains(&element.name()) || has_negative_hint(element)
+}
+
+/// リンクの文字数が全体に占める割合。
+fn link_density(element: ElementRef) -> f64 {
+    let text_length = element.text().map(str::len).sum::<usize>();
+    if text_length == 0 {
+        return 1.0;
+    }
+
+    let selector = Selector::parse("a").unwrap();
+    let link_length = element
+        .select(&selector)
+        .flat_map(|link| link.text())
+        .map(str::len)
+        .sum::<usize>();
+
+    link_length as f64 / text_length as f64
+}
+
+fn find_content(document: &Html) -> Option<ElementRef<'_>> {
+    let paragraph_selector = Selector::parse("p, pre").unwrap();
+    let mut scores = HashMap::new();
+
+    for paragraph in document.select(&paragraph_selector) {
+        let text = paragraph.text().collect::<String>();
+        let text = text.trim();
+
+        if text.chars().count() < 25 {
+            continue;
+        }
+
+        let score = 1.0
+            + text.matches([',', '、', '，']).count() as f64
+            + (text.chars().count() as f64 / 100.0).min(3.0);
+
+        let mut ancestors = paragraph.ancestors().filter_map(ElementRef::wrap);
+        if let Some(parent) = ancestors.next() {
+            *scores.entry(parent.id()).or_insert(0.0) += score;
+        }
+        if let Some(grandparent) = ancestors.next() {
+            *scores.entry(grandparent.id()).or_insert(0.0) += score / 2.0;
+        }
+    }
+
+    let best = scores
+        .into_iter()
+        .filter_map(|(id, score)| {
+            let element = ElementRef::wrap(document.tree.get(id)?)?;
+            let is_excluded = element
+                .ancestors()
+                .filter_map(ElementRef::wrap)
+                .chain([element])
+                .any(|element| is_removed(element.value()));
+
+            (!is_excluded).then(|| (element, score * (1.0 - link_density(element))))
+        })
+        .max_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(element, _)| element);
+
+    best.or_else(|| {
+        let body_selector = Selector::parse("body").unwrap();
+        document.select(&body_selector).next()
+    })
+}
+
+fn render_html(node: NodeRef<Node>, base_url: &Url, out: &mut String) {
+    match node.value() {
+        Node::Text(text) => out.push_str(&escape_html(text)),
+        Node::Element(element) => {
+            if is_removed(element) {
+                return;
+            }
+
+            let name = element.name();
+            if !ALLOWED_TAGS.contains(&name) {
+                for child in node.children() {
+                    render_html(child, base_url, out);
+                }
+                return;
+            }
+
+            out.push('<');
+            out.push_str(name);
+            for attribute in ["href", "src"] {
+                if let Some(url) = element
+                    .attr(attribute)
+                    .and_then(|url| resolve_url(base_url, url, attribute == "href"))
+                {
+                    out.push_str(&format!(r#" {attribute}="{}""#, escape_html(url.as_str())));
+                }
+            }
+            if let Some(alt) = element.attr("alt") {
+                out.push_str(&format!(r#" alt="{}""#, escape_html(alt)));
+            }
+            out.push('>');
+
+            if VOID_TAGS.contains(&name) {
+                return;
+            }
+
+            for child in node.children() {
+                render_html(child, base_url, out);
+            }
+
+            out.push_str(&format!("</{name}>"));
+        }
+        _ => {}
+    }
+}
+
+fn render_markdown(node: NodeRef<Node>, base_url: &Url, depth: usize, out: &mut String) {
+    let element = match node.value() {
+        Node::Text(text) => {
+            out.push_str(&collapse_inline_whitespace(text));
+            return;
+        }
+        Node::Element(element) if !is_removed(element) => element,
+        _ => return,
+    };
+
+    let children = |out: &mut String| {
+        for child in node.children() {
+            render_markdown(child, base_url, depth, out);
+        }
+    };
+    match element.name() {
+        "p" | "figure" | "figcaption" | "table" => {
+            out.push_str("\n\n");
+            children(out);
+            out.push_str("\n\n");
+        }
+        name @ ("h1" | "h2" | "h3" | "h4" | "h5" | "h6") => {
+            let level = name[1..].parse::<usize>().unwrap_or(1);
+            out.push_str(&format!("\n\n{} ", "#".repeat(level)));
+            children(out);
+            out.push_str("\n\n");
+        }
+        "br" => out.push_str("  \n"),
+        "hr" => out.push_str("\n\n---\n\n"),
+        "em" | "i" => {
+            out.push('*');
+            children(out);
+            out.push('*');
+        }
+        "strong" | "b" => {
+            out.push_str("**");
+            children(out);
+            out.push_str("**");
+        }
+        "code" => {
+            out.push('`');
+            out.push_str(
+                &ElementRef::wrap(node)
+                    .map(|code| code.text().collect::<String>())
+                    .unwrap_or_default(),
+            );
+            out.push('`');
+        }
+        "pre" => {
+            let code = ElementRef::wrap(node)
+                .map(|pre| pre.text().collect::<String>())
+                .unwrap_or_default();
+            out.push_str(&format!("\n\n```\n{}\n```\n\n", code.trim_end()));
+        }
+        "a" => {
+            let mut text = String::new();
+            children(&mut text);
+
+            let href = element
+                .attr("href")
+                .and_then(|href| resolve_url(base_url, href, true));
+            match href {
+                Some(href) if !text.trim().is_empty() => {
+                    out.push_str(&format!("[{}]({href})", text.trim()));
+                }
+                _ => out.push_str(&text),
+            }
+        }
+        "img" => {
+            if let Some(src) = element
+                .attr("src")
+                .and_then(|src| resolve_url(base_url, src, false))
+            {
+                let alt = element.attr("alt").unwrap_or_default();
+                out.push_str(&format!("![{alt}]({src})"));
+            }
+        }
+        name @ ("ul" | "ol") => {
+            out.push_str("\n\n");
+            let items = node
+                .children()
+                .filter(|child| child.value().as_element().is_some_and(|e| e.name() == "li"));
+
+            for (index, item) in items.enumerate() {
+                let marker = if name == "ol" {
+                    format!("{}. ", index + 1)
+                } else {
+                    "- ".to_owned()
+                };
+
+                let mut text = String::new();
+                for child in item.children() {
+                    render_markdown(child, base_url, depth + 1, &mut text);
+                }
+
+                let indent = "  ".repeat(depth);
+                let text = normalize_markdown(&text).replace('\n', &format!("\n{indent}  "));
+                out.push_str(&format!("{indent}{marker}{text}\n"));
+            }
+            out.push_str("\n\n");
+        }
+        "blockquote" => {
+            let mut text = String::new();
+            children(&mut text);
+
+            out.push_str("\n\n");
+            for line in normalize_markdown(&text).lines() {
+                out.push_str(&format!("> {line}\n"));
+            }
+            out.push_str("\n\n");
+        }
+        "tr" => {
+            let cells = node
+                .children()
+                .filter_map(ElementRef::wrap)
+                .map(|cell| collapse_whitespace(&cell.text().collect::<String>()))
+                .collect::<Vec<_>>();
+            out.push_str(&format!("| {} |\n", cells.join(" | ")));
+        }
+        _ => children(out),
+    }
+}
+
+/// 相対URLをページのURLで解決する。許可していないスキームであれば`None`を返す。
+fn resolve_url(base_url: &Url, url: &str, is_link: bool) -> Option<Url> {
+    let url = base_url.join(url.trim()).ok()?;
+    let scheme = url.scheme();
+
+    (ALLOWED_SCHEMES.contains(&scheme) || is_link && ALLOWED_LINK_SCHEMES.contains(&scheme))
+        .then_some(url)
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn collapse_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// 文章中の空白を1つにまとめる。前後の空白は、単語の区切りとして1つだけ残す。
+fn collapse_inline_whitespace(text: &str) -> String {
+    let collapsed = collapse_whitespace(text);
+    if collapsed.is_empty() {
+        return if text.is_empty() {
+            String::new()
+        } else {
+            " ".to_owned()
+        };
+    }
+
+    let leading = if text.starts_with(char::is_whitespace) {
+        " "
+    } else {
+        ""
+    };
+    let trailing = if text.ends_with(char::is_whitespace) {
+        " "
+    } else {
+        ""
+    };
+
+    format!("{leading}{collapsed}{trailing}")
+}
+
+/// 行末の空白と、続く空行をまとめる。コードブロックの中はそのまま残す。
+fn normalize_markdown(markdown: &str) -> String {
+    let mut normalized = String::new();
+    let mut blank_lines = 0;
+    let mut in_code_block = false;
+
+    for line in markdown.lines() {
+        if in_code_block {
+            in_code_block = line.trim() != "```";
+            normalized.push_str(line);
+            normalized.push('\n');
+            continue;
+        }
+
+        if line.trim().is_empty() {
+            blank_lines += 1;
+            if blank_lines == 1 {
+                normalized.push('\n');
+            }
+            continue;
+        }
+        blank_lines = 0;
+
+        let trimmed = line.trim();
+        in_code_block = trimmed == "```";
+
+        // 箇条書きの字下げは残し、文章の先頭の空白は取り除く。
+        let is_list_item = trimmed.starts_with("- ")
+            || trimmed
+                .split_once(". ")
+                .is_some_and(|(number, _)| number.parse::<usize>().is_ok());
+        if is_list_item {
+            normalized.push_str(line.trim_end());
+        } else {
+            normalized.push_str(trimmed);
+        }
+        // 改行の`  `は残す。
+        if line.ends_with("  ") {
+            normalized.push_str("  ");
+        }
+        normalized.push('\n');
+    }
+
+    normalized.trim().to_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ARTICLE: &str = r#"<!DOCTYPE html>
+<html>
+<head>
+  <title>サイト名</title>
+  <meta property="og:title" content="記事のタイトル">
+  <meta name="author" content="山田太郎">
+  <meta property="article:published_time" content="2026-01-02T03:04:05Z">
+</head>
+<body>
+  <nav class="menu"><a href="/">ホーム</a><a href="/about">このサイトについて</a></nav>
+  <div id="main">
+    <article>
+      <h2>はじめに</h2>
+      <p>この段落は本文です。十分な長さがあり、読点、カンマ、を含んでいるので、本文として選ばれます。</p>
+      <p>詳しくは<a href="/docs/guide">ガイド</a>と<a href="javascript:alert(1)">危険なリンク</a>、
+        <a href="mailto:author@example.com">作者への連絡</a>を参照してください。これも本文の段落です。</p>
+      <img src="images/figure.png" alt="図">
+      <img src="data:image/png;base64,AAAA" alt="埋め込み">
+      <ul>
+        <li>一つ目の<strong>項目</strong></li>
+        <li>二つ目の<em>項目</em></li>
+      </ul>
+      <pre>fn main() {
+    println!("hello");
+}</pre>
+      <script>alert("script");</script>
+    </article>
+  </div>
+  <div class="comments"><p>コメント欄の段落です。これは長いコメントですが、本文には含まれないはずです。</p></div>
+  <footer>フッター</footer>
+</body>
+</html>"#;
+
+    fn article() -> Article {
+        extract_article(ARTICLE, "https://example.com/posts/1").unwrap()
+    }
+
+    #[test]
+    fn extracts_metadata() {
+        let article = article();
+
+        assert_eq!(article.title, "記事のタイトル");
+        assert_eq!(article.byline.as_deref(), Some("山田太郎"));
+        assert_eq!(article.published.as_deref(), Some("2026-01-02T03:04:05Z"));
+        assert_eq!(article.source_url, "https://example.com/posts/1");
+    }
+
+    #[test]
+    fn extracts_content_without_boilerplate() {
+        let html = article().content_html;
+
+        assert!(html.contains("<h2>はじめに</h2>"));
+        assert!(html.contains("この段落は本文です。"));
+        assert!(!html.contains("このサイトについて"));
+        assert!(!html.contains("コメント欄"));
+        assert!(!html.contains("<script"));
+        assert!(!html.contains("alert"));
+    }
+
+    #[test]
+    fn resolves_and_filters_urls() {
+        let html = article().content_html;
+
+        assert!(html.contains(r#"<a href="https://example.com/docs/guide">"#));
+        assert!(html.contains(r#"<a href="mailto:author@example.com">"#));
+        assert!(
+            html.contains(r#"<img src="https://example.com/posts/images/figure.png" alt="図">"#)
+        );
+        assert!(html.contains("<a>危険なリンク</a>"));
+        assert!(!html.contains("javascript:"));
+        assert!(!html.contains("data:"));
+    }
+
+    #[test]
+    fn converts_to_markdown() {
+        let markdown = article().markdown;
+
+        assert!(markdown.starts_with("## はじめに\n\nこの段落は本文です。"));
+        assert!(markdown.contains("[ガイド](https://example.com/docs/guide)"));
+        assert!(markdown.contains("[作者への連絡](mailto:author@example.com)"));
+        assert!(markdown.contains("と危険なリンク、"));
+        assert!(markdown.contains("![図](https://example.com/posts/images/figure.png)"));
+        assert!(!markdown.contains("埋め込み"));
+        assert!(markdown.contains("- 一つ目の**項目**\n- 二つ目の*項目*"));
+        assert!(markdown.contains("```\nfn main() {\n    println!(\"hello\");\n}\n```"));
+    }
+
+    #[test]
+    fn converts_nested_lists_and_quotes() {
+        let html = r#"<html><body><div>
+            <p>入れ子の箇条書きと引用を含む、本文として選ばれる程度に長い段落です。</p>
+            <ol>
+              <li>親<ul><li>子</li></ul></li>
+              <li>次</li>
+            </ol>
+            <blockquote><p>引用の一行目</p><p>引用の二行目</p></blockquote>
+        </div></body></html>"#;
+        let article = extract_article(html, "https://example.com/").unwrap();
+
+        assert!(article.markdown.contains("1. 親\n\n    - 子\n2. 次"));
+        assert!(
+            article
+                .markdown
+                .ends_with("> 引用の一行目\n>\n> 引用の二行目")
+        );
+    }
+
+    #[test]
+    fn builds_note() {
+        let note = article().to_note();
+
+        assert!(note.starts_with(
+            "# 記事のタイトル\n\n- 著者: 山田太郎\n- 公開日: 2026-01-02T03:04:05Z\n- 出典: <https://example.com/posts/1>\n\n## はじめに"
+        ));
+    }
+}
@@ -0,0 +1,439 @@
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
+
+use anyhow::Context as _;
+use url::{Url, form_urlencoded};
+
+use crate::{
+    Id, SearchEngineMarker, WorkspaceMarker,
+    db::{self, Database, SearchEngineData},
+    origin_of,
+};
+
+/// 検索語を置き換える、URLのテンプレートの部分。OpenSearchと同じ書き方。
+pub const SEARCH_TERMS_PLACEHOLDER: &str = "{searchTerms}";
+
+/// OpenSearchの名前空間。
+const OPEN_SEARCH_NAMESPACE: &str = "http://a9.com/-/spec/opensearch/1.1/";
+
+/// ダウンロードする検索候補の応答の、最大のバイト数。
+pub(crate) const SEARCH_SUGGESTIONS_MAX_BYTES: usize = 256 * 1024;
+
+impl SearchEngineData {
+    pub fn search_url(&self, query: &str) -> String {
+        expand_template(&self.url_template, query)
+    }
+
+    /// 検索候補を取得するURL。候補のURLがなければ`None`を返す。
+    pub fn suggest_url(&self, query: &str) -> Option<String> {
+        self.suggest_url_template
+            .as_deref()
+            .map(|template| expand_template(template, query))
+    }
+
+    /// 検索エンジンのトップページ。ホームページに使う。
+    pub fn home_url(&self) -> Option<String> {
+        origin_of(&self.search_url("")).map(|origin| format!("{origin}/"))
+    }
+}
+
+fn expand_template(template: &str, query: &str) -> String {
+    let query = form_urlencoded::byte_serialize(query.as_bytes()).collect::<String>();
+
+    template.replace(SEARCH_TERMS_PLACEHOLDER, &query)
+}
+
+/// 検索エンジンの内容を確かめ、前後の空白を取り除く。
+pub(crate) fn normalize_search_engine(
+    mut data: SearchEngineData,
+) -> anyhow::Result<SearchEngineData> {
+    data.name = data.name.trim().to_owned();
+    data.keyword = data.keyword.trim().to_owned();
+    data.url_template = data.url_template.trim().to_owned();
+    data.suggest_url_template = data
+        .suggest_url_template
+        .map(|template| template.trim().to_owned())
+        .filter(|template| !template.is_empty());
+
+    anyhow::ensure!(!data.name.is_empty(), "検索エンジンの名前が空です。");
+    anyhow::ensure!(
+        !data.keyword.is_empty() && !data.keyword.contains(char::is_whitespace),
+        "検索エンジンのキーワードは、空白を含まない文字列にしてください。"
+    );
+
+    let templates = std::iter::once(&data.url_template).chain(&data.suggest_url_template);
+    for template in templates {
+        anyhow::ensure!(
+            template.contains(SEARCH_TERMS_PLACEHOLDER),
+            "URLに{SEARCH_TERMS_PLACEHOLDER}がありません：{template}"
+        );
+        Url::parse(&expand_template(template, "memex"))
+            .with_context(|| format!("URLの形式が正しくありません：{template}"))?;
+    }
+
+    Ok(data)
+}
+
+/// OpenSearch description XMLから読み込んだ検索エンジン。
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OpenSearchDescription {
+    pub name: String,
+    pub url_template: String,
+    pub suggest_url_template: Option<String>,
+}
+
+/// OpenSearch description XMLを読み込む。
+/// `text/html`の`Url`を検索結果に、`application/x-suggestions+json`の`Url`を検索候補に使う。
+pub fn parse_open_search_description(xml: &str) -> anyhow::Result<OpenSearchDescription> {
+    let document =
+        roxmltree::Document::parse(xml).context("OpenSearchのXMLを読み込めませんでした。")?;
+    let root = document.root_element();
+    anyhow::ensure!(
+        root.has_tag_name((OPEN_SEARCH_NAMESPACE, "OpenSearchDescription")),
+        "OpenSearch description XMLではありません。"
+    );
+
+    let child_text = |name: &str| {
+        root.children()
+            .find(|node| node.has_tag_name((OPEN_SEARCH_NAMESPACE, name)))
+            .and_then(|node| node.text())
+            .map(|text| text.trim().to_owned())
+            .filter(|text| !text.is_empty())
+    };
+    let name = child_text("ShortName")
+        .or_else(|| child_text("LongName"))
+        .context("OpenSearchのXMLに名前がありません。")?;
+
+    let mut url_template = None;
+    let mut suggest_url_template = None;
+
+    for node in root
+        .children()
+        .filter(|node| node.has_tag_name((OPEN_SEARCH_NAMESPACE, "Url")))
+    {
+        // POSTで検索するものは、URLだけでは開けないので使わない。
+        if node
+            .attribute("method")
+            .is_some_and(|method| !method.eq_ignore_ascii_case("get"))
+        {
+            continue;
+        }
+
+        let slot = match node.attribute("type") {
+            Some("text/html") => &mut url_template,
+            Some("application/x-suggestions+json") => &mut suggest_url_template,
+            _ => continue,
+        };
+        if slot.is_some() {
+            continue;
+        }
+
+        let Some(template) = node.attribute("template") else {
+            continue;
+        };
+        let mut template = template.to_owned();
+
+        // Firefoxの書き方。クエリのパラメータを`Param`要素で書く。
+        let params = node
+            .children()
+            .filter(|param| param.has_tag_name("Param"))
+            .filter_map(|param| Some((param.attribute("name")?, param.attribute("value")?)))
+            .map(|(name, value)| format!("{name}={value}"))
+            .collect::<Vec<_>>();
+        if !params.is_empty() {
+            let separator = if template.contains('?') { '&' } else { '?' };
+            template = format!("{template}{separator}{}", params.join("&"));
+        }
+
+        *slot = Some(resolve_template_parameters(&template)?);
+    }
+
+    Ok(OpenSearchDescription {
+        name,
+        url_template: url_template.context("OpenSearchのXMLに検索結果のURLがありません。")?,
+        suggest_url_template,
+    })
+}
+
+/// `{searchTerms}`以外のテンプレートのパラメータを置き換える。
+/// 省略できるもの（`{name?}`）は空にし、置き換えられない必須のものがあればエラーにする。
+fn resolve_template_parameters(template: &str) -> anyhow::Result<String> {
+    let mut resolved = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find('{') {
+        resolved.push_str(&rest[..start]);
+
+        let end = rest[start..]
+            .find('}')
+            .map(|end| start + end)
+            .with_context(|| format!("テンプレートの括弧が閉じていません：{template}"))?;
+        let parameter = &rest[start + 1..end];
+
+        match parameter.trim_end_matches('?') {
+            "searchTerms" => resolved.push_str(SEARCH_TERMS_PLACEHOLDER),
+            "inputEncoding" | "outputEncoding" => resolved.push_str("UTF-8"),
+            "language" => resolved.push('*'),
+            "startIndex" | "startPage" if !parameter.ends_with('?') => resolved.push('1'),
+            "count" if !parameter.ends_with('?') => resolved.push_str("10"),
+            _ if parameter.ends_with('?') => {}
+            _ => anyhow::bail!("テンプレートのパラメータ{{{parameter}}}には対応していません。"),
+        }
+
+        rest = &rest[end + 1..];
+    }
+    resolved.push_str(rest);
+
+    Ok(resolved)
+}
+
+/// OpenSearchの`application/x-suggestions+json`の応答から、検索候補を取り出す。
+/// `["検索語", ["候補1", "候補2", ...], ...]`の形式。
+pub(crate) fn parse_search_suggestions(json: &[u8]) -> anyhow::Result<Vec<String>> {
+    let value = serde_json::from_slice::<serde_json::Value>(json)
+        .context("検索候補の応答を読み込めませんでした。")?;
+
+    let suggestions = value
+        .get(1)
+        .and_then(|suggestions| suggestions.as_array())
+        .context("検索候補の応答の形式が正しくありません。")?;
+
+    Ok(suggestions
+        .iter()
+        .filter_map(|suggestion| suggestion.as_str())
+        .map(ToOwned::to_owned)
+        .collect())
+}
+
+#[derive(Default)]
+struct SearchEngineState {
+    /// 並び順。先頭のものを、既定を設定していないワークスペースで使う。
+    engines: Vec<SearchEngineData>,
+    workspace_defaults: HashMap<Id<WorkspaceMarker>, Id<SearchEngineMarker>>,
+}
+
+/// 検索エンジンと、ワークスペース毎の既定の検索エンジン。
+/// タブのホームページのURLをすぐ決められるよう、メモリに持っておく。
+#[derive(Clone, Default)]
+pub(crate) struct SearchEngines {
+    state: Rc<RefCell<SearchEngineState>>,
+}
+
+impl SearchEngines {
+    pub(crate) async fn load(&self, db: &Database) -> anyhow::Result<()> {
+        let engines = db::list_search_engines(db).await?;
+        let workspace_defaults = db::list_workspace_search_engines(db)
+            .await?
+            .into_iter()
+            .map(|record| (record.workspace_id, record.search_engine_id))
+            .collect();
+
+        self.state.replace(SearchEngineState {
+            engines,
+            workspace_defaults,
+        });
+
+        Ok(())
+    }
+
+    pub(crate) fn list(&self) -> Vec<SearchEngineData> {
+        self.state.borrow().engines.clone()
+    }
+
+    pub(crate) fn get(&self, id: Id<SearchEngineMarker>) -> Option<SearchEngineData> {
+        self.state
+            .borrow()
+            .engines
+            .iter()
+            .find(|engine| engine.id == id)
+            .cloned()
+    }
+
+    pub(crate) fn order(&self) -> Vec<Id<SearchEngineMarker>> {
+        self.state
+            .borrow()
+            .engines
+            .iter()
+            .map(|engine| engine.id)
+            .collect()
+    }
+
+    /// ワークスペースで既定にした検索エンジン。なければ並び順で最初のものを返す。
+    pub(crate) fn default_for(
+        &self,
+        workspace_id: Id<WorkspaceMarker>,
+    ) -> Option<SearchEngineData> {
+        let state = self.state.borrow();
+        let selected = state.workspace_defaults.get(&workspace_id);
+
+        selected
+            .and_then(|id| state.engines.iter().find(|engine| engine.id == *id))
+            .or_else(|| state.engines.first())
+            .cloned()
+    }
+
+    /// ワークスペース毎に設定した既定の検索エンジン。
+    pub(crate) fn workspace_default(
+        &self,
+        workspace_id: Id<WorkspaceMarker>,
+    ) -> Option<Id<SearchEngineMarker>> {
+        self.state
+            .borrow()
+            .workspace_defaults
+            .get(&workspace_id)
+            .copied()
+    }
+
+    /// ホームページのURL。ワークスペースの既定の検索エンジンのトップページを使う。
+    pub(crate) fn home_url(&self, workspace_id: Id<WorkspaceMarker>) -> String {
+        self.default_for(workspace_id)
+            .and_then(|engine| engine.home_url())
+            .unwrap_or_else(|| "about:blank".to_owned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn engine(url_template: &str) -> SearchEngineData {
+        SearchEngineData {
+            id: Id::default(),
+            name: "Example".to_owned(),
+            keyword: "ex".to_owned(),
+            url_template: url_template.to_owned(),
+            suggest_url_template: None,
+        }
+    }
+
+    #[test]
+    fn parses_open_search_description() {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<OpenSearchDescription xmlns="http://a9.com/-/spec/opensearch/1.1/"
+                       xmlns:moz="http://www.mozilla.org/2006/browser/search/">
+  <ShortName>Wikipedia (ja)</ShortName>
+  <Description>Wikipedia (ja)</Description>
+  <Image height="16" width="16" type="image/x-icon">https://ja.wikipedia.org/static/favicon/wikipedia.ico</Image>
+  <Url type="text/html" method="get" template="https://ja.wikipedia.org/w/index.php?title=特別:検索&amp;search={searchTerms}"/>
+  <Url type="application/x-suggestions+json" method="get" template="https://ja.wikipedia.org/w/api.php?action=opensearch&amp;search={searchTerms}&amp;namespace=0"/>
+  <Url type="application/x-suggestions+xml" method="get" template="https://ja.wikipedia.org/w/api.php?action=opensearch&amp;format=xml&amp;search={searchTerms}"/>
+  <moz:SearchForm>https://ja.wikipedia.org/wiki/特別:検索</moz:SearchForm>
+</OpenSearchDescription>"#;
+
+        assert_eq!(
+            parse_open_search_description(xml).unwrap(),
+            OpenSearchDescription {
+                name: "Wikipedia (ja)".to_owned(),
+                url_template: "https://ja.wikipedia.org/w/index.php?title=特別:検索&search={searchTerms}"
+                    .to_owned(),
+                suggest_url_template: Some(
+                    "https://ja.wikipedia.org/w/api.php?action=opensearch&search={searchTerms}&namespace=0"
+                        .to_owned()
+                ),
+            }
+        );
+    }
+
+    #[test]
+    fn resolves_template_parameters() {
+        let xml = r#"<OpenSearchDescription xmlns="http://a9.com/-/spec/opensearch/1.1/">
+  <LongName>Example Search</LongName>
+  <Url type="text/html" method="POST" template="https://example.com/post"/>
+  <Url type="text/html" template="https://example.com/search?q={searchTerms}&amp;lang={language?}&amp;n={count?}&amp;page={startPage}&amp;ie={inputEncoding}">
+    <Param name="source" value="memex"/>
+  </Url>
+</OpenSearchDescription>"#;
+        let description = parse_open_search_description(xml).unwrap();
+
+        assert_eq!(description.name, "Example Search");
+        assert_eq!(
+            description.url_template,
+            "https://example.com/search?q={searchTerms}&lang=*&n=&page=1&ie=UTF-8&source=memex"
+        );
+        assert_eq!(description.suggest_url_template, None);
+    }
+
+    #[test]
+    fn rejects_unsupported_descriptions() {
+        let missing_parameter = r#"<OpenSearchDescription xmlns="http://a9.com/-/spec/opensearch/1.1/">
+  <ShortName>Example</ShortName>
+  <Url type="text/html" template="https://example.com/search?q={searchTerms}&amp;geo={geo:box}"/>
+</OpenSearchDescription>"#;
+        assert!(parse_open_search_description(missing_parameter).is_err());
+
+        let unclosed = r#"<OpenSearchDescription xmlns="http://a9.com/-/spec/opensearch/1.1/">
+  <ShortName>Example</ShortName>
+  <Url type="text/html" template="https://example.com/search?q={searchTerms"/>
+</OpenSearchDescription>"#;
+        assert!(parse_open_search_description(unclosed).is_err());
+
+        let no_html_url = r#"<OpenSearchDescription xmlns="http://a9.com/-/spec/opensearch/1.1/">
+  <ShortName>Example</ShortName>
+  <Url type="application/x-suggestions+json" template="https://example.com/suggest?q={searchTerms}"/>
+</OpenSearchDescription>"#;
+        assert!(parse_open_search_description(no_html_url).is_err());
+
+        let wrong_namespace = r#"<OpenSearchDescription>
+  <ShortName>Example</ShortName>
+  <Url type="text/html" template="https://example.com/search?q={searchTerms}"/>
+</OpenSearchDescription>"#;
+        assert!(parse_open_search_description(wrong_namespace).is_err());
+    }
+
+    #[test]
+    fn encodes_search_terms() {
+        let engine = engine("https://example.com/search?q={searchTerms}");
+
+        assert_eq!(
+            engine.search_url("rust & c++"),
+            "https://example.com/search?q=rust+%26+c%2B%2B"
+        );
+        assert_eq!(
+            engine.search_url("東京 100%"),
+            "https://example.com/search?q=%E6%9D%B1%E4%BA%AC+100%25"
+        );
+        assert_eq!(
+            engine.search_url("a/b?c=d#e"),
+            "https://example.com/search?q=a%2Fb%3Fc%3Dd%23e"
+        );
+        assert_eq!(engine.suggest_url("rust"), None);
+        assert_eq!(engine.home_url().as_deref(), Some("https://example.com/"));
+    }
+
+    #[test]
+    fn validates_search_engines() {
+        let normalized = normalize_search_engine(SearchEngineData {
+            name: " Example ".to_owned(),
+            keyword: " ex ".to_owned(),
+            suggest_url_template: Some(" ".to_owned()),
+            ..engine(" https://example.com/?q={searchTerms} ")
+        })
+        .unwrap();
+        assert_eq!(normalized.name, "Example");
+        assert_eq!(normalized.keyword, "ex");
+        assert_eq!(
+            normalized.url_template,
+            "https://example.com/?q={searchTerms}"
+        );
+        assert_eq!(normalized.suggest_url_template, None);
+
+        assert!(normalize_search_engine(engine("https://example.com/")).is_err());
+        assert!(normalize_search_engine(engine("example.com/?q={searchTerms}")).is_err());
+        assert!(
+            normalize_search_engine(SearchEngineData {
+                keyword: "e x".to_owned(),
+                ..engine("https://example.com/?q={searchTerms}")
+            })
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn parses_search_suggestions() {
+        let json = br#"["rust", ["rust lang", "rustup", 1], ["", ""], []]"#;
+        assert_eq!(
+            parse_search_suggestions(json).unwrap(),
+            ["rust lang", "rustup"]
+        );
+        assert!(parse_search_suggestions(br#"{"q": "rust"}"#).is_err());
+    }
+}
@@ -0,0 +1,52 @@
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
+
+use crate::{
+    Id, WorkspaceMarker,
+    db::{self, Database},
+    origin_of,
+};
+
+/// ワークスペース毎の、オリジンの訪問回数。
+/// ナビゲーションの通知は同期的なので、回数をメモリに溜めておき、`flush`でまとめて保存する。
+#[derive(Clone, Default)]
+pub(crate) struct SiteVisits {
+    pending: Rc<RefCell<HashMap<(Id<WorkspaceMarker>, String), u32>>>,
+}
+
+impl SiteVisits {
+    pub(crate) fn record(&self, workspace_id: Id<WorkspaceMarker>, url: &str) {
+        let Some(origin) = origin_of(url) else {
+            return;
+        };
+
+        *self
+            .pending
+            .borrow_mut()
+            .entry((workspace_id, origin))
+            .or_default() += 1;
+    }
+
+    /// 溜まっている訪問回数を保存する。保存に失敗したら、次に保存できるよう戻しておく。
+    pub(crate) async fn flush(&self, db: &Database) -> anyhow::Result<()> {
+        let visits = self
+            .pending
+            .take()
+            .into_iter()
+            .map(|((workspace_id, origin), count)| (workspace_id, origin, count))
+            .collect::<Vec<_>>();
+        if visits.is_empty() {
+            return Ok(());
+        }
+
+        if let Err(error) = db::add_site_visits(db, &visits).await {
+            let mut pending = self.pending.borrow_mut();
+            for (workspace_id, origin, count) in visits {
+                *pending.entry((workspace_id, origin)).or_default() += count;
+            }
+
+            return Err(error);
+        }
+
+        Ok(())
+    }
+}
@@ -1,32 +1,94 @@
-use memex_cef::{EventHandler, WebView, WebViewContext};
+use std::{
+    cell::{Cell, RefCell},
+    path::PathBuf,
+    rc::Rc,
+    time::{Instant, SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::Context as _;
+use memex_cef::{
+    BeforeUnloadRequest, EventHandler, FindResult, Highlight, IpcRequest, JsDialogRequest,
+    PdfPrintSettings, PermissionRequest, RenderProcessTermination, ScreenshotArea, UIThreadMarker,
+    WebView, WebViewContext,
+};
 
 use crate::{
-    BrowserContext, Id, TabMarker,
-    db::{TabData, TabLocationData, update_location},
+    AnnotationMarker, Article, BrowserContext, FaviconFetch, Id, IpcCall, IpcContext,
+    SitePermissionPrompt, TabCrash, TabGroupMarker, TabMarker, UserScript, WorkspaceMarker,
+    ZoomScope,
+    crash::CrashState,
+    db::{
+        AnnotationData, PageArchiveData, TabData, TabLocationData, add_page_archive,
+        get_page_archive_by_path, update_location,
+    },
+    extract_article,
+    favicon::{SharedFaviconState, cached_favicon},
+    fs::utils,
+    lifecycle::IS_PLAYING_AUDIO_EXPRESSION,
+    normalize_url, origin_of,
+    reader::reader_view_path,
+    thumbnail::{THUMBNAIL_SCALE, remove_thumbnail, thumbnail_path},
+    zoom::DEFAULT_ZOOM_LEVEL,
 };
 
 pub struct Tab {
     id: Id<TabMarker>,
+    /// タブがあるワークスペース。タブを他のワークスペースへ移すと変わる。
+    workspace_id: Rc<Cell<Id<WorkspaceMarker>>>,
     browser_context: BrowserContext,
     pub(crate) initial_location: TabLocationData,
     webview: Option<WebView>,
     webview_context: WebViewContext,
+    /// 最後に通知されたページのタイトル。
+    title: Rc<RefCell<String>>,
+    /// 最後に選択された時刻。破棄するタブを選ぶのに使う。
+    last_active: Cell<Instant>,
+    is_pinned: bool,
+    group_id: Option<Id<TabGroupMarker>>,
+    /// ロードした後に、`discard`で`WebView`を閉じたか。
+    is_discarded: bool,
+    crash_state: Rc<RefCell<CrashState>>,
+    favicon: SharedFaviconState,
+    /// 保存したアーカイブを開いている場合の、そのメタデータ。
+    archive: RefCell<Option<PageArchiveData>>,
 }
 
 impl Tab {
-    pub fn new(browser_context: BrowserContext, data: TabData) -> anyhow::Result<Self> {
+    pub fn new(
+        browser_context: BrowserContext,
+        workspace_id: Id<WorkspaceMarker>,
+        data: TabData,
+    ) -> anyhow::Result<Self> {
+        let workspace_id = Rc::new(Cell::new(workspace_id));
+        let title = Rc::new(RefCell::new(String::new()));
+        let crash_state = Rc::new(RefCell::new(CrashState::default()));
+        let favicon = SharedFaviconState::default();
         let event_handler = TabEventHandler {
             id: data.id,
+            workspace_id: workspace_id.clone(),
             context: browser_context.clone(),
+            title: title.clone(),
+            crash_state: crash_state.clone(),
+            favicon: favicon.clone(),
+            committed_url: RefCell::new(String::new()),
         };
         let webview_context = WebViewContext::new(event_handler);
 
         Ok(Self {
             id: data.id,
+            workspace_id,
             browser_context,
             initial_location: data.location,
             webview: None,
             webview_context,
+            title,
+            last_active: Cell::new(Instant::now()),
+            is_pinned: data.is_pinned,
+            group_id: data.group_id,
+            is_discarded: false,
+            crash_state,
+            favicon,
+            archive: RefCell::new(None),
         })
     }
 
@@ -34,63 +96,718 @@ impl Tab {
         self.id
     }
 
+    pub fn workspace_id(&self) -> Id<WorkspaceMarker> {
+        self.workspace_id.get()
+    }
+
+    /// タブを他のワークスペースへ移す。
+    /// 表示中のページにも、移した先のワークスペースのズームレベル・スタイルシート・ハイライトを適用する。
+    /// ユーザースクリプトは`WebView`の作成時に渡すので、読み込み直すまでは元のワークスペースのものが使われる。
+    pub(crate) fn set_workspace(
+        &mut self,
+        workspace_id: Id<WorkspaceMarker>,
+    ) -> anyhow::Result<()> {
+        self.workspace_id.set(workspace_id);
+        self.group_id = None;
+
+        let Some(webview) = self.webview.as_ref() else {
+            return Ok(());
+        };
+        let url = webview.current_url();
+
+        let zoom_level = origin_of(&url)
+            .and_then(|origin| self.browser_context.zoom_levels.get(&origin, workspace_id));
+        webview.set_zoom_level(zoom_level.unwrap_or_default())?;
+
+        let style_sheets = self
+            .browser_context
+            .user_styles
+            .style_sheets_for(workspace_id, &url);
+        webview.set_style_sheets(&style_sheets)?;
+
+        self.apply_highlights()
+    }
+
+    /// ファビコンのキャッシュのパス。
+    pub fn favicon(&self) -> Option<PathBuf> {
+        self.favicon.borrow().path.clone()
+    }
+
+    /// タブの場所のファビコンを、キャッシュから読み込む。
+    /// 読み込む前のタブや、まだファビコンが通知されていないタブにも表示できる。
+    pub(crate) async fn restore_favicon(&self) -> anyhow::Result<()> {
+        let TabLocationData::WebPage { url } = self.location() else {
+            return Ok(());
+        };
+
+        let generation = self.favicon.borrow().generation;
+        let path =
+            cached_favicon(&self.browser_context.data, &self.browser_context.db, &url).await?;
+
+        {
+            let mut favicon = self.favicon.borrow_mut();
+            if favicon.generation != generation || path.is_none() {
+                return Ok(());
+            }
+            favicon.path = path.clone();
+        }
+
+        self.browser_context
+            .delegate
+            .on_tab_favicon_change(self.id, path);
+
+        Ok(())
+    }
+
+    /// 保存したアーカイブを開いていれば、その元のURLや取得した日時を返す。
+    pub fn archive(&self) -> Option<PageArchiveData> {
+        self.archive.borrow().clone()
+    }
+
+    /// タブで開くファイルが、保存したアーカイブかをデータベースで確かめる。
+    /// タブを作った後、読み込む前に呼ぶ。
+    pub(crate) async fn restore_archive(&self) -> anyhow::Result<()> {
+        let archive = match &self.initial_location {
+            TabLocationData::FileViewer { path } => {
+                get_page_archive_by_path(&self.browser_context.db, path).await?
+            }
+            _ => None,
+        };
+
+        if let Some(archive) = &archive
+            && self.title.borrow().is_empty()
+        {
+            self.title.replace(archive.title.clone());
+        }
+        self.archive.replace(archive);
+
+        Ok(())
+    }
+
+    /// ホームページのURL。ワークスペースの既定の検索エンジンのトップページ。
+    fn home_url(&self) -> String {
+        self.browser_context
+            .search_engines
+            .home_url(self.workspace_id.get())
+    }
+
     pub fn is_loaded(&self) -> bool {
         self.webview.is_some()
     }
 
+    pub fn webview(&self) -> Option<&WebView> {
+        self.webview.as_ref()
+    }
+
     pub fn load(&mut self) -> anyhow::Result<()> {
         anyhow::ensure!(self.webview.is_none(), "既にこのタブはロード済みです。");
-        let url = get_url(&self.initial_location);
+        let url = get_url(&self.initial_location, &self.home_url())?;
+        let injected_scripts = self
+            .browser_context
+            .user_scripts
+            .get(self.workspace_id.get())
+            .iter()
+            .map(UserScript::to_injected_script)
+            .collect::<Vec<_>>();
 
-        self.webview = Some(WebView::new(
+        let webview = WebView::new(
             &mut self.browser_context.profile,
             self.webview_context.clone(),
             self.browser_context.window_handle,
-            url,
+            &url,
             self.browser_context.rect.get(),
-        )?);
+            &injected_scripts,
+        )?;
+        self.crash_state.borrow_mut().webview = Some(webview.downgrade());
+        self.webview = Some(webview);
+
+        if self.is_discarded {
+            self.is_discarded = false;
+            self.browser_context
+                .delegate
+                .on_tab_discard_change(self.id, false);
+        }
+
+        Ok(())
+    }
+
+    /// 使われていないために`WebView`を閉じられているか。
+    /// 破棄されたタブも場所は保っており、`load`で読み込み直せる。
+    pub fn is_discarded(&self) -> bool {
+        self.is_discarded
+    }
+
+    /// `WebView`を閉じてメモリを空ける。場所は保ち、次の`load`でそこを読み込み直す。
+    pub fn discard(&mut self, utm: UIThreadMarker) -> anyhow::Result<()> {
+        if self.webview.is_none() {
+            return Ok(());
+        }
+
+        self.unload(utm)?;
+        self.is_discarded = true;
+
+        self.browser_context
+            .delegate
+            .on_tab_discard_change(self.id, true);
 
         Ok(())
     }
 
+    /// 場所を保ったまま`WebView`を閉じる。ワークスペースごと閉じる場合に使い、破棄としては通知しない。
+    pub(crate) fn unload(&mut self, utm: UIThreadMarker) -> anyhow::Result<()> {
+        self.initial_location = self.location();
+
+        self.crash_state.borrow_mut().webview = None;
+        if let Some(webview) = self.webview.take() {
+            webview.close(utm)?;
+        }
+
+        Ok(())
+    }
+
+    /// レンダラープロセスがクラッシュし、まだ読み込み直していなければ、そのクラッシュを返す。
+    pub fn crash(&self) -> Option<TabCrash> {
+        self.crash_state.borrow().crash.clone()
+    }
+
+    pub fn crash_count(&self) -> u32 {
+        self.crash_state.borrow().crash_count()
+    }
+
+    /// クラッシュしたページを、新しいレンダラープロセスで読み込み直す。
+    pub fn reload_after_crash(&self) -> anyhow::Result<()> {
+        self.loaded_webview()?.reload();
+        self.crash_state.borrow_mut().crash = None;
+
+        Ok(())
+    }
+
+    pub fn last_active(&self) -> Instant {
+        self.last_active.get()
+    }
+
+    /// タブが使われたことを記録する。
+    pub(crate) fn mark_active(&self) {
+        self.last_active.set(Instant::now());
+    }
+
+    /// 固定されたタブは先頭に並び、閉じることも破棄することもできない。
+    pub fn is_pinned(&self) -> bool {
+        self.is_pinned
+    }
+
+    pub(crate) fn set_pinned(&mut self, is_pinned: bool) {
+        self.is_pinned = is_pinned;
+    }
+
+    pub fn group_id(&self) -> Option<Id<TabGroupMarker>> {
+        self.group_id
+    }
+
+    pub(crate) fn set_group_id(&mut self, group_id: Option<Id<TabGroupMarker>>) {
+        self.group_id = group_id;
+    }
+
+    /// 音声を再生中か。ロードされていなければ`false`を返す。
+    pub async fn is_playing_audio(&self) -> anyhow::Result<bool> {
+        let Some(webview) = self.webview.as_ref() else {
+            return Ok(false);
+        };
+
+        let value = webview
+            .dev_tools()
+            .evaluate(IS_PLAYING_AUDIO_EXPRESSION)
+            .await?;
+
+        Ok(value.as_bool().unwrap_or_default())
+    }
+
+    pub fn title(&self) -> String {
+        self.title.borrow().clone()
+    }
+
+    /// 保存したアーカイブとリーダービューを開いているタブは読み取り専用で、他の場所へは移動できない。
+    pub fn is_read_only(&self) -> bool {
+        let TabLocationData::FileViewer { path } = &self.initial_location else {
+            return false;
+        };
+
+        self.archive.borrow().is_some() || path.starts_with(self.browser_context.data.reader_dir())
+    }
+
     pub fn location(&self) -> TabLocationData {
-        if let Some(webview) = self.webview.as_ref() {
-            TabLocationData::WebPage {
-                url: webview.current_url(),
-            }
-        } else {
-            self.initial_location.clone()
+        if self.is_read_only() {
+            return self.initial_location.clone();
+        }
+
+        let Some(webview) = self.webview.as_ref() else {
+            return self.initial_location.clone();
+        };
+        let url = webview.current_url();
+
+        // ファイルを開いたまま移動していなければ、ファイルの場所のままにする。
+        if matches!(self.initial_location, TabLocationData::FileViewer { .. })
+            && get_url(&self.initial_location, "").is_ok_and(|initial_url| initial_url == url)
+        {
+            return self.initial_location.clone();
         }
+
+        TabLocationData::WebPage { url }
     }
 
     pub async fn navigate(&mut self, location: TabLocationData) -> anyhow::Result<()> {
-        update_location(&self.browser_context.db, self.id, &location).await;
+        anyhow::ensure!(!self.is_read_only(), "読み取り専用のタブは移動できません。");
+
+        update_location(&self.browser_context.db, self.id, &location).await?;
 
         if let Some(webview) = self.webview.as_ref() {
-            webview.navigate(get_url(&location))?;
+            webview.navigate(&get_url(&location, &self.home_url())?)?;
         } else {
             self.initial_location = location;
+            self.restore_archive().await?;
         }
 
         Ok(())
     }
-}
 
-fn get_url(location: &TabLocationData) -> &str {
-    match &location {
-        TabLocationData::NativeHomePage => "https://www.google.com",
-        TabLocationData::WebPage { url } => url.as_str(),
-        _ => unimplemented!(),
+    pub async fn zoom_in(&self, scope: ZoomScope) -> anyhow::Result<()> {
+        let webview = self.loaded_webview()?;
+        let level = webview.zoom_in()?;
+
+        self.save_zoom_level(level, scope).await
+    }
+
+    pub async fn zoom_out(&self, scope: ZoomScope) -> anyhow::Result<()> {
+        let webview = self.loaded_webview()?;
+        let level = webview.zoom_out()?;
+
+        self.save_zoom_level(level, scope).await
+    }
+
+    /// 倍率を既定に戻し、このワークスペースで次にページを開いた時も既定の倍率になるようにする。
+    /// `ZoomScope::Workspace`では、全体のズームレベルが残っていれば、
+    /// それが適用されないようワークスペースに既定の倍率を記憶する。なければワークスペースのものを削除する。
+    /// `ZoomScope::Global`では、全体のものとこのワークスペースのものを削除する。
+    /// 他のワークスペースで記憶しているものは残る。
+    pub async fn reset_zoom(&self, scope: ZoomScope) -> anyhow::Result<()> {
+        self.loaded_webview()?.reset_zoom()?;
+
+        let Some(origin) = self.current_origin() else {
+            return Ok(());
+        };
+        let workspace_id = self.workspace_id.get();
+        let zoom_levels = &self.browser_context.zoom_levels;
+        let db = &self.browser_context.db;
+
+        match scope {
+            ZoomScope::Workspace => {
+                if zoom_levels.get_scoped(&origin, None).is_some() {
+                    zoom_levels
+                        .set(db, &origin, Some(workspace_id), DEFAULT_ZOOM_LEVEL)
+                        .await
+                } else {
+                    zoom_levels.remove(db, &origin, Some(workspace_id)).await
+                }
+            }
+            ZoomScope::Global => {
+                zoom_levels.remove(db, &origin, Some(workspace_id)).await?;
+                zoom_levels.remove(db, &origin, None).await
+            }
+        }
+    }
+
+    /// ページのスクリーンショットを撮り、PNGのバイト列を返す。
+    pub async fn capture_screenshot(&self, area: ScreenshotArea) -> anyhow::Result<Vec<u8>> {
+        self.loaded_webview()?.capture_screenshot(area, 1.0).await
+    }
+
+    /// タブのサムネイルのPNGのパスを返す。
+    /// キャッシュがなければ撮影するが、タブがロードされていなければ`None`を返す。
+    pub async fn thumbnail(&self) -> anyhow::Result<Option<PathBuf>> {
+        let path = thumbnail_path(&self.browser_context.data, self.id);
+
+        if utils::exists(&path).await? {
+            return Ok(Some(path));
+        }
+
+        let Some(webview) = self.webview.as_ref() else {
+            return Ok(None);
+        };
+
+        let png = webview
+            .capture_screenshot(ScreenshotArea::Viewport, THUMBNAIL_SCALE)
+            .await?;
+        async_fs::write(&path, png)
+            .await
+            .context("サムネイルの書き込みに失敗しました。")?;
+
+        Ok(Some(path))
+    }
+
+    /// 現在のページをサブリソースも含めてMHTMLとしてワークスペースのディレクトリに保存する。
+    /// 保存したアーカイブは`TabLocationData::FileViewer`で読み取り専用のタブとして開ける。
+    pub async fn archive_page(&self) -> anyhow::Result<PageArchiveData> {
+        let webview = self.loaded_webview()?;
+        let TabLocationData::WebPage { url: source_url } = self.location() else {
+            anyhow::bail!("Webページ以外はアーカイブできません。");
+        };
+
+        let mhtml = webview.capture_mhtml().await?;
+        let captured_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .context("現在時刻の取得に失敗しました。")?
+            .as_secs() as i64;
+
+        let directory = self.workspace_dir().await?;
+        let title = self.title();
+        let file_name = utils::sanitize_file_name(&title);
+        let path = utils::unique_path(&directory, &file_name, "mhtml").await?;
+
+        async_fs::write(&path, mhtml)
+            .await
+            .context("アーカイブの書き込みに失敗しました。")?;
+
+        let data = PageArchiveData {
+            id: Id::default(),
+            workspace_id: self.workspace_id.get(),
+            path,
+            source_url,
+            title,
+            captured_at,
+        };
+        add_page_archive(&self.browser_context.db, &data).await?;
+
+        Ok(data)
+    }
+
+    /// 現在のページをPDFとしてワークスペースのディレクトリに保存し、保存先のパスを返す。
+    /// ファイル名はページのタイトルから作る。
+    pub async fn save_as_pdf(&self) -> anyhow::Result<PathBuf> {
+        let webview = self.loaded_webview()?;
+        let directory = self.workspace_dir().await?;
+        let file_name = utils::sanitize_file_name(&self.title());
+        let path = utils::unique_path(&directory, &file_name, "pdf").await?;
+
+        webview
+            .print_to_pdf(&path, &PdfPrintSettings::default())
+            .await?;
+
+        Ok(path)
+    }
+
+    /// 現在のページから記事の本文を取り出す。
+    pub async fn extract_article(&self) -> anyhow::Result<Article> {
+        let webview = self.loaded_webview()?;
+        let TabLocationData::WebPage { url } = self.location() else {
+            anyhow::bail!("Webページ以外はリーダーモードで表示できません。");
+        };
+
+        let html = webview
+            .dev_tools()
+            .evaluate("document.documentElement.outerHTML")
+            .await?;
+        let html = html
+            .as_str()
+            .context("ページのHTMLを取得できませんでした。")?;
+
+        extract_article(html, &url)
+    }
+
+    /// 記事をリーダービューのHTMLとして書き出し、そのタブの場所を返す。
+    /// 返した場所を開いたタブは読み取り専用になる。
+    pub async fn reader_view(&self, article: &Article) -> anyhow::Result<TabLocationData> {
+        let path = reader_view_path(&self.browser_context.data, self.id);
+
+        async_fs::write(&path, article.to_reader_html())
+            .await
+            .context("リーダービューの書き込みに失敗しました。")?;
+
+        Ok(TabLocationData::FileViewer { path })
+    }
+
+    /// 記事をMarkdownのメモとしてワークスペースのディレクトリに保存し、保存先のパスを返す。
+    pub async fn save_article_as_note(&self, article: &Article) -> anyhow::Result<PathBuf> {
+        let directory = self.workspace_dir().await?;
+        let file_name = utils::sanitize_file_name(&article.title);
+        let path = utils::unique_path(&directory, &file_name, "md").await?;
+
+        async_fs::write(&path, article.to_note())
+            .await
+            .context("メモの書き込みに失敗しました。")?;
+
+        Ok(path)
+    }
+
+    /// 表示中のページの注釈を、ページ内の順で返す。
+    pub fn annotations(&self) -> Vec<AnnotationData> {
+        let Some(webview) = self.webview.as_ref() else {
+            return Vec::new();
+        };
+
+        self.browser_context
+            .annotations
+            .list_for_url(self.workspace_id.get(), &webview.current_url())
+    }
+
+    /// 選択中の文章をハイライトし、注釈を付ける。何も選択されていなければ`None`を返す。
+    pub async fn annotate_selection(&self, note: &str) -> anyhow::Result<Option<AnnotationData>> {
+        let webview = self.loaded_webview()?;
+        let Some(anchor) = webview.selection_anchor().await? else {
+            return Ok(None);
+        };
+        let created_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .context("現在時刻の取得に失敗しました。")?
+            .as_secs() as i64;
+
+        let data = AnnotationData {
+            id: Id::default(),
+            workspace_id: self.workspace_id.get(),
+            url: normalize_url(&webview.current_url()),
+            anchor,
+            note: note.to_owned(),
+            created_at,
+        };
+        self.browser_context
+            .annotations
+            .add(&self.browser_context.db, data.clone())
+            .await?;
+        self.apply_highlights()?;
+
+        Ok(Some(data))
+    }
+
+    pub async fn update_annotation_note(
+        &self,
+        id: Id<AnnotationMarker>,
+        note: &str,
+    ) -> anyhow::Result<()> {
+        self.browser_context
+            .annotations
+            .update_note(&self.browser_context.db, id, note)
+            .await?;
+
+        self.apply_highlights()
+    }
+
+    pub async fn remove_annotation(&self, id: Id<AnnotationMarker>) -> anyhow::Result<()> {
+        self.browser_context
+            .annotations
+            .remove(&self.browser_context.db, id)
+            .await?;
+
+        self.apply_highlights()
+    }
+
+    fn apply_highlights(&self) -> anyhow::Result<()> {
+        let Some(webview) = self.webview.as_ref() else {
+            return Ok(());
+        };
+        let highlights = self
+            .browser_context
+            .annotations
+            .highlights_for(self.workspace_id.get(), &webview.current_url());
+
+        webview.set_highlights(&highlights)
+    }
+
+    /// ワークスペースのディレクトリのパスを返す。なければ作成する。
+    async fn workspace_dir(&self) -> anyhow::Result<PathBuf> {
+        let directory = self
+            .browser_context
+            .data
+            .workspace_dir(self.workspace_id.get());
+
+        if !utils::exists(&directory).await? {
+            async_fs::create_dir_all(&directory)
+                .await
+                .context("ワークスペースディレクトリの作成に失敗しました。")?;
+        }
+
+        Ok(directory)
+    }
+
+    fn loaded_webview(&self) -> anyhow::Result<&WebView> {
+        self.webview
+            .as_ref()
+            .context("このタブはまだロードされていません。")
+    }
+
+    /// 表示中のウェブページのオリジン。ファイルなどを表示している場合は`None`を返す。
+    fn current_origin(&self) -> Option<String> {
+        let TabLocationData::WebPage { url } = self.location() else {
+            return None;
+        };
+
+        origin_of(&url)
+    }
+
+    async fn save_zoom_level(&self, level: f64, scope: ZoomScope) -> anyhow::Result<()> {
+        let Some(origin) = self.current_origin() else {
+            return Ok(());
+        };
+
+        let workspace_id = match scope {
+            ZoomScope::Global => None,
+            ZoomScope::Workspace => Some(self.workspace_id.get()),
+        };
+
+        self.browser_context
+            .zoom_levels
+            .set(&self.browser_context.db, &origin, workspace_id, level)
+            .await
     }
 }
 
+fn get_url(location: &TabLocationData, home_url: &str) -> anyhow::Result<String> {
+    Ok(match &location {
+        TabLocationData::NativeHomePage => home_url.to_owned(),
+        TabLocationData::WebPage { url } => url.clone(),
+        TabLocationData::FileViewer { path } => {
+            let path = std::path::absolute(path)?;
+
+            url::Url::from_file_path(&path)
+                .map_err(|_| anyhow::anyhow!("ファイルのURLへの変換に失敗：{}", path.display()))?
+                .into()
+        }
+    })
+}
+
 pub struct TabEventHandler {
     id: Id<TabMarker>,
+    workspace_id: Rc<Cell<Id<WorkspaceMarker>>>,
     context: BrowserContext,
+    title: Rc<RefCell<String>>,
+    crash_state: Rc<RefCell<CrashState>>,
+    favicon: SharedFaviconState,
+    /// ナビゲーションが確定したページのURL。タイトルを閲覧履歴に記録するのに使う。
+    committed_url: RefCell<String>,
 }
 
 impl EventHandler for TabEventHandler {
     fn on_title_change(&self, title: String) {
+        self.title.replace(title.clone());
+        self.context.history.record_title(
+            self.workspace_id.get(),
+            &self.committed_url.borrow(),
+            &title,
+        );
         self.context.delegate.on_tab_title_change(self.id, title);
     }
+
+    fn on_navigation_commit(&self, url: String) {
+        let data = self.context.data.clone();
+        let id = self.id;
+        self.context
+            .spawn(async move { remove_thumbnail(&data, id).await });
+        self.committed_url.replace(url.clone());
+        self.context
+            .site_visits
+            .record(self.workspace_id.get(), &url);
+        self.context
+            .history
+            .record_visit(self.workspace_id.get(), &url);
+        {
+            let mut favicon = self.favicon.borrow_mut();
+            favicon.page_url = url.clone();
+            favicon.generation += 1;
+        }
+        self.context.delegate.on_tab_navigation_commit(self.id, url);
+    }
+
+    fn on_favicon_urls_change(&self, urls: Vec<String>) {
+        self.favicon.borrow_mut().generation += 1;
+
+        let fetch = FaviconFetch::new(self.context.clone(), self.id, self.favicon.clone(), urls);
+        self.context.delegate.on_tab_favicon_request(fetch);
+    }
+
+    fn on_render_process_terminated(&self, termination: RenderProcessTermination) {
+        log::warn!("タブのレンダラープロセスが終了しました：{termination:?}");
+
+        let crash = self.crash_state.borrow_mut().record(termination);
+        if let Some(delay) = crash.reload_after {
+            let crash_state = self.crash_state.clone();
+            let crash_count = crash.crash_count;
+            self.context.spawn(async move {
+                async_io::Timer::after(delay).await;
+                crash_state.borrow_mut().auto_reload(crash_count);
+            });
+        }
+        self.context.delegate.on_tab_crash(self.id, crash);
+    }
+
+    fn zoom_level_for(&self, url: &str) -> Option<f64> {
+        let origin = origin_of(url)?;
+
+        self.context
+            .zoom_levels
+            .get(&origin, self.workspace_id.get())
+    }
+
+    fn style_sheets_for(&self, url: &str) -> Vec<String> {
+        self.context
+            .user_styles
+            .style_sheets_for(self.workspace_id.get(), url)
+    }
+
+    fn highlights_for(&self, url: &str) -> Vec<Highlight> {
+        self.context
+            .annotations
+            .highlights_for(self.workspace_id.get(), url)
+    }
+
+    fn on_permission_request(&self, request: PermissionRequest) {
+        let prompt = SitePermissionPrompt::new(
+            self.context.db.clone(),
+            self.id,
+            self.workspace_id.get(),
+            request,
+        );
+
+        self.context.delegate.on_site_permission_request(prompt);
+    }
+
+    fn on_permission_prompt_dismiss(&self, prompt_id: u64) {
+        self.context
+            .delegate
+            .on_site_permission_prompt_dismiss(self.id, prompt_id);
+    }
+
+    fn on_js_dialog(&self, request: JsDialogRequest) {
+        self.context.delegate.on_tab_js_dialog(self.id, request);
+    }
+
+    fn on_before_unload_dialog(&self, request: BeforeUnloadRequest) {
+        self.context
+            .delegate
+            .on_tab_before_unload_dialog(self.id, request);
+    }
+
+    fn on_js_dialog_reset(&self) {
+        self.context.delegate.on_tab_js_dialog_reset(self.id);
+    }
+
+    fn on_find_result(&self, result: FindResult) {
+        self.context.delegate.on_tab_find_result(self.id, result);
+    }
+
+    fn on_ipc_request(&self, request: IpcRequest) {
+        let context = IpcContext {
+            db: self.context.db.clone(),
+            tab_id: self.id,
+            workspace_id: self.workspace_id.get(),
+            url: request.url().to_owned(),
+        };
+        let call = IpcCall::new(self.context.ipc_router.clone(), context, request);
+
+        self.context.spawn(call.handle());
+    }
+
+    fn on_injected_script_error(&self, name: String, error: String) {
+        log::warn!(
+            "タブ{:?}のユーザースクリプト{name}でエラーが発生しました：{error}",
+            self.id
+        );
+    }
 }
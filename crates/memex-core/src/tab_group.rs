@@ -0,0 +1,113 @@
+use std::collections::HashSet;
+
+use crate::{Id, TabGroupMarker, TabMarker};
+
+/// 固定されたタブを先頭に、同じグループのタブを連続するように並べ直す。
+/// グループのタブは、そのグループの最初のタブがあった位置にまとめる。
+/// `tab_state`は、タブが固定されているかと、入っているグループを返す。
+pub(crate) fn normalize_tab_order(
+    tab_order: &[Id<TabMarker>],
+    tab_state: impl Fn(&Id<TabMarker>) -> (bool, Option<Id<TabGroupMarker>>),
+) -> Vec<Id<TabMarker>> {
+    let is_pinned = |id: &Id<TabMarker>| tab_state(id).0;
+    let group_of = |id: &Id<TabMarker>| tab_state(id).1;
+
+    let mut normalized = tab_order
+        .iter()
+        .copied()
+        .filter(is_pinned)
+        .collect::<Vec<_>>();
+    let mut placed_groups = HashSet::new();
+
+    for id in tab_order.iter().filter(|id| !is_pinned(id)) {
+        match group_of(id) {
+            None => normalized.push(*id),
+            Some(group_id) => {
+                if placed_groups.insert(group_id) {
+                    normalized.extend(
+                        tab_order
+                            .iter()
+                            .filter(|id| !is_pinned(id) && group_of(id) == Some(group_id)),
+                    );
+                }
+            }
+        }
+    }
+
+    normalized
+}
+
+/// タブグループの名前の前後の空白を除く。空の名前は付けられない。
+pub(crate) fn normalize_tab_group_name(name: &str) -> anyhow::Result<String> {
+    let name = name.trim();
+    anyhow::ensure!(!name.is_empty(), "タブグループの名前が空です。");
+
+    Ok(name.to_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    struct Tabs {
+        order: Vec<Id<TabMarker>>,
+        states: HashMap<Id<TabMarker>, (bool, Option<Id<TabGroupMarker>>)>,
+    }
+
+    impl Tabs {
+        /// `(固定されているか, グループ)`の並びからタブを作る。
+        fn new(states: &[(bool, Option<Id<TabGroupMarker>>)]) -> Self {
+            let order = states.iter().map(|_| Id::default()).collect::<Vec<_>>();
+            let states = order.iter().copied().zip(states.iter().copied()).collect();
+
+            Self { order, states }
+        }
+
+        fn normalize(&self) -> Vec<usize> {
+            normalize_tab_order(&self.order, |id| self.states[id])
+                .iter()
+                .map(|id| self.order.iter().position(|tab| tab == id).unwrap())
+                .collect()
+        }
+    }
+
+    #[test]
+    fn moves_pinned_tabs_first() {
+        let tabs = Tabs::new(&[(false, None), (true, None), (false, None), (true, None)]);
+
+        assert_eq!(tabs.normalize(), [1, 3, 0, 2]);
+    }
+
+    #[test]
+    fn keeps_grouped_tabs_contiguous() {
+        let a = Some(Id::default());
+        let b = Some(Id::default());
+        let tabs = Tabs::new(&[
+            (false, None),
+            (false, a),
+            (false, b),
+            (false, None),
+            (false, a),
+            (false, b),
+        ]);
+
+        // グループは、最初のタブがあった位置にまとめる。
+        assert_eq!(tabs.normalize(), [0, 1, 4, 2, 5, 3]);
+    }
+
+    #[test]
+    fn keeps_normalized_order() {
+        let a = Some(Id::default());
+        let tabs = Tabs::new(&[(true, None), (false, a), (false, a), (false, None)]);
+
+        assert_eq!(tabs.normalize(), [0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn normalizes_group_name() {
+        assert_eq!(normalize_tab_group_name("  仕事 ").unwrap(), "仕事");
+        assert!(normalize_tab_group_name(" \t\n").is_err());
+    }
+}
@@ -0,0 +1,20 @@
+use std::{io, path::PathBuf};
+
+use crate::{Id, TabMarker, fs::DataContext};
+
+/// サムネイルの、ページに対する縮小率。
+pub(crate) const THUMBNAIL_SCALE: f64 = 0.25;
+
+/// タブのサムネイルをキャッシュするパス。
+pub(crate) fn thumbnail_path(data: &DataContext, tab_id: Id<TabMarker>) -> PathBuf {
+    data.thumbnail_dir().join(format!("{}.png", *tab_id))
+}
+
+/// キャッシュしたサムネイルを削除する。ページが変わった時や、タブを閉じた時に使う。
+pub(crate) async fn remove_thumbnail(data: &DataContext, tab_id: Id<TabMarker>) {
+    match async_fs::remove_file(thumbnail_path(data, tab_id)).await {
+        Ok(()) => {}
+        Err(error) if error.kind() == io::ErrorKind::NotFound => {}
+        Err(error) => log::warn!("サムネイルの削除に失敗しました：{error}"),
+    }
+}
@@ -0,0 +1,341 @@
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+use anyhow::Context as _;
+use futures_lite::StreamExt as _;
+use memex_cef::{INJECTED_SCRIPT_ERROR_FUNCTION, InjectedScript};
+
+use crate::{
+    Id, WorkspaceMarker,
+    fs::{DataContext, utils},
+    origin::{glob_pattern_regex, match_pattern_regex},
+};
+
+/// ユーザースクリプトのファイルの拡張子。
+const USER_SCRIPT_EXTENSION: &str = ".user.js";
+
+/// ユーザースクリプトを実行する時点。
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RunAt {
+    /// ページのスクリプトより先に実行する。
+    DocumentStart,
+    /// `DOMContentLoaded`の時点で実行する。
+    #[default]
+    DocumentEnd,
+    /// `load`の時点で実行する。
+    DocumentIdle,
+}
+
+impl RunAt {
+    fn parse(value: &str) -> anyhow::Result<Self> {
+        Ok(match value {
+            "document-start" => Self::DocumentStart,
+            "document-end" => Self::DocumentEnd,
+            "document-idle" => Self::DocumentIdle,
+            _ => anyhow::bail!("不明な`@run-at`です：{value}"),
+        })
+    }
+}
+
+/// `// ==UserScript==`のメタデータを持つスクリプト。
+#[derive(Clone, Debug)]
+pub struct UserScript {
+    pub path: PathBuf,
+    pub name: String,
+    pub matches: Vec<String>,
+    pub includes: Vec<String>,
+    pub excludes: Vec<String>,
+    pub run_at: RunAt,
+    pub code: String,
+}
+
+impl UserScript {
+    /// メタデータを解析する。`@name`がなければファイル名を名前とする。
+    pub fn parse(path: &Path, code: String) -> anyhow::Result<Self> {
+        let mut script = Self {
+            path: path.to_owned(),
+            name: path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .unwrap_or_default()
+                .trim_end_matches(USER_SCRIPT_EXTENSION)
+                .to_owned(),
+            matches: Vec::new(),
+            includes: Vec::new(),
+            excludes: Vec::new(),
+            run_at: RunAt::default(),
+            code: String::new(),
+        };
+
+        let mut lines = code.lines().map(str::trim);
+        lines
+            .find(|line| *line == "// ==UserScript==")
+            .context("`// ==UserScript==`がありません。")?;
+
+        let mut is_closed = false;
+
+        for line in lines {
+            if line == "// ==/UserScript==" {
+                is_closed = true;
+                break;
+            }
+
+            let Some(line) = line.strip_prefix("//") else {
+                continue;
+            };
+            let Some((key, value)) = line.trim().split_once(char::is_whitespace) else {
+                continue;
+            };
+            let value = value.trim().to_owned();
+
+            match key {
+                "@name" => script.name = value,
+                "@match" => script.matches.push(value),
+                "@include" => script.includes.push(value),
+                "@exclude" => script.excludes.push(value),
+                "@run-at" => script.run_at = RunAt::parse(&value)?,
+                _ => {}
+            }
+        }
+
+        anyhow::ensure!(is_closed, "`// ==/UserScript==`がありません。");
+
+        script.code = code;
+
+        Ok(script)
+    }
+
+    /// URLの判定と実行時点の制御を含めた、レンダラーで評価するスクリプトに変換する。
+    pub(crate) fn to_injected_script(&self) -> InjectedScript {
+        let mut includes = self
+            .matches
+            .iter()
+            .map(|pattern| match_pattern_regex(pattern))
+            .chain(
+                self.includes
+                    .iter()
+                    .map(|pattern| glob_pattern_regex(pattern)),
+            )
+            .collect::<Vec<_>>();
+
+        // `@match`も`@include`もなければ、全てのページで実行する。
+        if includes.is_empty() {
+            includes.push(".*".to_owned());
+        }
+
+        let excludes = self
+            .excludes
+            .iter()
+            .map(|pattern| glob_pattern_regex(pattern))
+            .collect::<Vec<_>>();
+
+        let run = match self.run_at {
+            RunAt::DocumentStart => "run();",
+            RunAt::DocumentEnd => {
+                r#"if (document.readyState === "loading") {
+    document.addEventListener("DOMContentLoaded", run, { once: true });
+  } else {
+    run();
+  }"#
+            }
+            RunAt::DocumentIdle => {
+                r#"if (document.readyState === "complete") {
+    run();
+  } else {
+    globalThis.addEventListener("load", run, { once: true });
+  }"#
+            }
+        };
+
+        let name = serde_json::Value::String(self.name.clone());
+
+        let source = format!(
+            r#"(() => {{
+  const report = globalThis.{report};
+  const url = location.href.replace(/#.*$/s, "");
+  const includes = {includes};
+  const excludes = {excludes};
+  if (
+    !includes.some((source) => new RegExp(source).test(url)) ||
+    excludes.some((source) => new RegExp(source).test(url))
+  ) {{
+    return;
+  }}
+  const run = () => {{
+    try {{
+{code}
+    }} catch (error) {{
+      report?.({name}, String(error?.stack ?? error));
+    }}
+  }};
+  {run}
+}})();"#,
+            report = INJECTED_SCRIPT_ERROR_FUNCTION,
+            includes = serde_json::Value::from(includes),
+            excludes = serde_json::Value::from(excludes),
+            code = self.code,
+        );
+
+        InjectedScript {
+            name: self.name.clone(),
+            source,
+        }
+    }
+}
+
+/// ディレクトリ内の`*.user.js`を読み込む。解析できなかったファイルはログに残して飛ばす。
+async fn load_user_scripts(directory: &Path) -> anyhow::Result<Vec<UserScript>> {
+    if !utils::exists(directory).await? {
+        return Ok(Vec::new());
+    }
+
+    let mut scripts = Vec::new();
+    let mut entries = async_fs::read_dir(directory).await?;
+
+    while let Some(entry) = entries.next().await {
+        let path = entry?.path();
+
+        if !path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| name.ends_with(USER_SCRIPT_EXTENSION))
+        {
+            continue;
+        }
+
+        let code = async_fs::read_to_string(&path).await?;
+
+        match UserScript::parse(&path, code) {
+            Ok(script) => scripts.push(script),
+            Err(error) => {
+                log::warn!(
+                    "ユーザースクリプト{}の読み込みに失敗しました：{error:#}",
+                    path.display()
+                );
+            }
+        }
+    }
+
+    scripts.sort_by(|a, b| a.path.cmp(&b.path));
+
+    Ok(scripts)
+}
+
+/// 全体とワークスペース毎のユーザースクリプト。
+/// タブの`WebView`の作成時に渡せるよう、読み込み済みのワークスペースの分をメモリに持っておく。
+#[derive(Clone, Default)]
+pub struct UserScripts {
+    scripts: Rc<RefCell<HashMap<Option<Id<WorkspaceMarker>>, Vec<UserScript>>>>,
+}
+
+impl UserScripts {
+    pub(crate) async fn load(
+        &self,
+        data: &DataContext,
+        workspace_id: Id<WorkspaceMarker>,
+    ) -> anyhow::Result<()> {
+        let global = load_user_scripts(&data.user_script_dir()).await?;
+        let workspace = load_user_scripts(&data.workspace_user_script_dir(workspace_id)).await?;
+
+        let mut scripts = self.scripts.borrow_mut();
+        scripts.insert(None, global);
+        scripts.insert(Some(workspace_id), workspace);
+
+        Ok(())
+    }
+
+    /// ワークスペースで使うユーザースクリプトを返す。全体のものを先に並べる。
+    pub fn get(&self, workspace_id: Id<WorkspaceMarker>) -> Vec<UserScript> {
+        let scripts = self.scripts.borrow();
+
+        [None, Some(workspace_id)]
+            .iter()
+            .filter_map(|key| scripts.get(key))
+            .flatten()
+            .cloned()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(code: &str) -> anyhow::Result<UserScript> {
+        UserScript::parse(Path::new("/scripts/example.user.js"), code.to_owned())
+    }
+
+    #[test]
+    fn parses_metadata() {
+        let script = parse(
+            r#"// ==UserScript==
+// @name        Example
+// @match       https://*.example.com/*
+// @match       *://example.org/*
+// @include     https://example.net/*
+// @exclude     https://www.example.com/admin/*
+// @run-at      document-start
+// @version     1.0
+// ==/UserScript==
+console.log("example");"#,
+        )
+        .unwrap();
+
+        assert_eq!(script.name, "Example");
+        assert_eq!(
+            script.matches,
+            ["https://*.example.com/*", "*://example.org/*"]
+        );
+        assert_eq!(script.includes, ["https://example.net/*"]);
+        assert_eq!(script.excludes, ["https://www.example.com/admin/*"]);
+        assert_eq!(script.run_at, RunAt::DocumentStart);
+        assert!(script.code.ends_with(r#"console.log("example");"#));
+    }
+
+    #[test]
+    fn defaults_name_and_run_at() {
+        let script = parse("// ==UserScript==\n// ==/UserScript==\n").unwrap();
+
+        assert_eq!(script.name, "example");
+        assert!(script.matches.is_empty());
+        assert_eq!(script.run_at, RunAt::DocumentEnd);
+    }
+
+    #[test]
+    fn rejects_invalid_metadata() {
+        assert!(parse("console.log(1);").is_err());
+        assert!(parse("// ==UserScript==\n// @name Example\n").is_err());
+        assert!(parse("// ==UserScript==\n// @run-at later\n// ==/UserScript==\n").is_err());
+    }
+
+    #[test]
+    fn injects_shared_pattern_regexes() {
+        let script = parse(
+            "// ==UserScript==\n// @match https://*.example.com/*\n// @exclude *.pdf\n// ==/UserScript==\n",
+        )
+        .unwrap();
+        let source = script.to_injected_script().source;
+
+        let includes =
+            serde_json::Value::from(vec![match_pattern_regex("https://*.example.com/*")]);
+        let excludes = serde_json::Value::from(vec![glob_pattern_regex("*.pdf")]);
+        assert!(source.contains(&format!("const includes = {includes};")));
+        assert!(source.contains(&format!("const excludes = {excludes};")));
+    }
+
+    #[test]
+    fn runs_everywhere_without_patterns() {
+        let script = parse("// ==UserScript==\n// ==/UserScript==\n").unwrap();
+
+        assert!(
+            script
+                .to_injected_script()
+                .source
+                .contains(r#"const includes = [".*"];"#)
+        );
+    }
+}
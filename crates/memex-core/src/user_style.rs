@@ -0,0 +1,261 @@
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    path::{Path, PathBuf},
+    rc::Rc,
+    time::SystemTime,
+};
+
+use futures_lite::StreamExt as _;
+use url::Url;
+
+use crate::{
+    Id, WorkspaceMarker,
+    db::{self, Database},
+    fs::{DataContext, utils},
+    origin::matches_site_pattern,
+};
+
+/// ワークスペースで強制的に暗い配色にする場合に適用する、組み込みのスタイルシート。
+/// 配色を反転し、画像や動画は元の色に戻す。
+const FORCE_DARK_STYLE_SHEET: &str = r#"
+:root {
+    color-scheme: dark;
+    background-color: #fff;
+    filter: invert(1) hue-rotate(180deg);
+}
+
+img, picture, video, canvas, iframe, embed, object, svg image, [style*="background-image"] {
+    filter: invert(1) hue-rotate(180deg);
+}
+"#;
+
+/// ワークスペースに置かれた、サイト毎のCSS。
+/// 先頭のコメントに`@match`で適用するサイトを書く。なければ全てのサイトに適用する。
+///
+/// ```css
+/// /* ==UserStyle==
+/// @match https://*.example.com/*
+/// ==/UserStyle== */
+/// ```
+#[derive(Clone, Debug)]
+pub struct UserStyle {
+    pub path: PathBuf,
+    pub matches: Vec<String>,
+    pub css: String,
+}
+
+impl UserStyle {
+    pub fn parse(path: &Path, css: String) -> Self {
+        let matches = css
+            .trim_start()
+            .strip_prefix("/*")
+            .and_then(|comment| comment.split_once("*/"))
+            .map(|(comment, _)| {
+                comment
+                    .lines()
+                    .filter_map(|line| line.trim().strip_prefix("@match"))
+                    .map(|pattern| pattern.trim().to_owned())
+                    .filter(|pattern| !pattern.is_empty())
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Self {
+            path: path.to_owned(),
+            matches,
+            css,
+        }
+    }
+
+    pub fn applies_to(&self, url: &Url) -> bool {
+        self.matches.is_empty()
+            || self
+                .matches
+                .iter()
+                .any(|pattern| matches_site_pattern(pattern, url))
+    }
+}
+
+/// 変更の検出に使う、CSSのファイルの一覧と更新日時。
+type Fingerprint = Vec<(PathBuf, Option<SystemTime>)>;
+
+async fn fingerprint(directory: &Path) -> anyhow::Result<Fingerprint> {
+    if !utils::exists(directory).await? {
+        return Ok(Vec::new());
+    }
+
+    let mut fingerprint = Vec::new();
+    let mut entries = async_fs::read_dir(directory).await?;
+
+    while let Some(entry) = entries.next().await {
+        let entry = entry?;
+        let path = entry.path();
+
+        if path.extension().is_some_and(|extension| extension == "css") {
+            let modified = entry.metadata().await?.modified().ok();
+            fingerprint.push((path, modified));
+        }
+    }
+
+    fingerprint.sort();
+
+    Ok(fingerprint)
+}
+
+#[derive(Default)]
+struct WorkspaceStyles {
+    styles: Vec<UserStyle>,
+    fingerprint: Fingerprint,
+    force_dark: bool,
+}
+
+/// ワークスペース毎のCSSと、強制ダークモードの設定。
+/// ナビゲーションの確定時にすぐ適用できるよう、読み込み済みのワークスペースの分をメモリに持っておく。
+#[derive(Clone, Default)]
+pub struct UserStyles {
+    workspaces: Rc<RefCell<HashMap<Id<WorkspaceMarker>, WorkspaceStyles>>>,
+}
+
+impl UserStyles {
+    pub(crate) async fn load(
+        &self,
+        data: &DataContext,
+        db: &Database,
+        workspace_id: Id<WorkspaceMarker>,
+    ) -> anyhow::Result<()> {
+        let force_dark = db::get_force_dark(db, workspace_id).await?;
+        self.workspaces
+            .borrow_mut()
+            .entry(workspace_id)
+            .or_default()
+            .force_dark = force_dark;
+
+        self.refresh(data, workspace_id).await?;
+
+        Ok(())
+    }
+
+    /// CSSのファイルが追加・変更・削除されていれば読み込み直す。
+    /// 読み込み直した場合は`true`を返す。
+    pub(crate) async fn refresh(
+        &self,
+        data: &DataContext,
+        workspace_id: Id<WorkspaceMarker>,
+    ) -> anyhow::Result<bool> {
+        let directory = data.workspace_user_style_dir(workspace_id);
+        let fingerprint = fingerprint(&directory).await?;
+
+        if self
+            .workspaces
+            .borrow()
+            .get(&workspace_id)
+            .is_some_and(|styles| styles.fingerprint == fingerprint)
+        {
+            return Ok(false);
+        }
+
+        let mut styles = Vec::new();
+
+        for (path, _) in &fingerprint {
+            match async_fs::read_to_string(path).await {
+                Ok(css) => styles.push(UserStyle::parse(path, css)),
+                Err(error) => {
+                    log::warn!("CSS{}の読み込みに失敗しました：{error}", path.display());
+                }
+            }
+        }
+
+        let mut workspaces = self.workspaces.borrow_mut();
+        let workspace = workspaces.entry(workspace_id).or_default();
+        workspace.styles = styles;
+        workspace.fingerprint = fingerprint;
+
+        Ok(true)
+    }
+
+    pub fn is_force_dark(&self, workspace_id: Id<WorkspaceMarker>) -> bool {
+        self.workspaces
+            .borrow()
+            .get(&workspace_id)
+            .is_some_and(|styles| styles.force_dark)
+    }
+
+    pub(crate) async fn set_force_dark(
+        &self,
+        db: &Database,
+        workspace_id: Id<WorkspaceMarker>,
+        force_dark: bool,
+    ) -> anyhow::Result<()> {
+        db::set_force_dark(db, workspace_id, force_dark).await?;
+
+        self.workspaces
+            .borrow_mut()
+            .entry(workspace_id)
+            .or_default()
+            .force_dark = force_dark;
+
+        Ok(())
+    }
+
+    /// ページに適用するスタイルシートを返す。強制ダークモードのものを先に並べる。
+    pub fn style_sheets_for(&self, workspace_id: Id<WorkspaceMarker>, url: &str) -> Vec<String> {
+        let workspaces = self.workspaces.borrow();
+        let Some(workspace) = workspaces.get(&workspace_id) else {
+            return Vec::new();
+        };
+        let Ok(url) = Url::parse(url) else {
+            return Vec::new();
+        };
+
+        workspace
+            .force_dark
+            .then(|| FORCE_DARK_STYLE_SHEET.to_owned())
+            .into_iter()
+            .chain(
+                workspace
+                    .styles
+                    .iter()
+                    .filter(|style| style.applies_to(&url))
+                    .map(|style| style.css.clone()),
+            )
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(css: &str) -> UserStyle {
+        UserStyle::parse(Path::new("/styles/example.css"), css.to_owned())
+    }
+
+    fn applies_to(style: &UserStyle, url: &str) -> bool {
+        style.applies_to(&Url::parse(url).unwrap())
+    }
+
+    #[test]
+    fn parses_matches() {
+        let style = parse(
+            "/* ==UserStyle==\n@match https://*.example.com/*\n@match\n  @match *://example.org/docs/*\n==/UserStyle== */\nbody { color: red; }",
+        );
+
+        assert_eq!(
+            style.matches,
+            ["https://*.example.com/*", "*://example.org/docs/*"]
+        );
+        assert!(applies_to(&style, "https://www.example.com/"));
+        assert!(applies_to(&style, "http://example.org/docs/a"));
+        assert!(!applies_to(&style, "https://example.org/"));
+    }
+
+    #[test]
+    fn applies_everywhere_without_matches() {
+        let style = parse("body { color: red; }\n/* @match https://example.com/* */");
+
+        assert!(style.matches.is_empty());
+        assert!(applies_to(&style, "https://example.org/"));
+        assert!(applies_to(&style, "memex://history"));
+    }
+}
@@ -1,23 +1,47 @@
-use std::collections::HashMap;
+use std::{
+    collections::HashMap,
+    ffi::OsStr,
+    path::{Path, PathBuf},
+};
 
-use memex_cef::WebViewBounds;
+use anyhow::Context as _;
+use memex_cef::{UIThreadMarker, WebViewBounds};
 
 use crate::{
-    BrowserContext, Id, Tab, TabMarker, WorkspaceMarker,
-    db::{WorkspaceData, WorkspaceIconData},
-    fs::FileSystemItem,
+    BrowserContext, Id, Tab, TabGroupMarker, TabMarker, WorkspaceMarker, annotations_to_markdown,
+    db::{
+        self, TabData, TabGroupColor, TabGroupData, TabLayoutData, TabLocationData, WorkspaceData,
+        WorkspaceIconData, WorkspaceIconImageData,
+    },
+    fs::{FileSystemItem, utils},
+    icon::{remove_stale_icon_images, write_icon_images},
+    reader::remove_reader_view,
+    tab_group::{normalize_tab_group_name, normalize_tab_order},
+    thumbnail::remove_thumbnail,
 };
 
+/// タブの並び・選択・固定・グループの状態。並びを変えた後で、保存に失敗した時に戻すのに使う。
+pub(crate) struct TabArrangement {
+    tab_order: Vec<Id<TabMarker>>,
+    selected_tab: Option<Id<TabMarker>>,
+    tab_groups: Vec<TabGroupData>,
+    /// タブごとの固定とグループ。
+    tab_states: HashMap<Id<TabMarker>, (bool, Option<Id<TabGroupMarker>>)>,
+}
+
 pub struct Workspace {
     browser_context: BrowserContext,
 
     id: Id<WorkspaceMarker>,
     name: String,
     icon: WorkspaceIconData,
+    icon_images: Vec<WorkspaceIconImageData>,
 
     tab_order: Vec<Id<TabMarker>>,
     selected_tab: Option<Id<TabMarker>>,
-    tabs: HashMap<Id<TabMarker>, Tab>,
+    pub(crate) tabs: HashMap<Id<TabMarker>, Tab>,
+    /// タブグループ。最初のタブの位置の順に並べる。
+    tab_groups: Vec<TabGroupData>,
     files: Vec<FileSystemItem>,
 
     is_loaded: bool,
@@ -25,22 +49,32 @@ pub struct Workspace {
 }
 
 impl Workspace {
-    pub fn new(browser_context: BrowserContext, data: WorkspaceData) -> Self {
-        Self {
+    /// 保存されたワークスペースを作る。タブの`WebView`は、ワークスペースが読み込まれるまで作らない。
+    pub fn new(browser_context: BrowserContext, data: WorkspaceData) -> anyhow::Result<Self> {
+        let tab_order = data.tabs.iter().map(|tab| tab.id).collect();
+        let tabs = data
+            .tabs
+            .into_iter()
+            .map(|tab| Ok((tab.id, Tab::new(browser_context.clone(), data.id, tab)?)))
+            .collect::<anyhow::Result<_>>()?;
+
+        Ok(Self {
             browser_context,
 
             id: data.id,
             name: data.name,
             icon: data.icon,
+            icon_images: data.icon_images,
 
-            tab_order: data.tabs,
+            tab_order,
             selected_tab: data.selected_tab,
-            tabs: HashMap::new(),
+            tabs,
+            tab_groups: data.tab_groups,
             files: Vec::new(),
 
             is_loaded: false,
             is_loading: false,
-        }
+        })
     }
 
     pub fn id(&self) -> Id<WorkspaceMarker> {
@@ -51,10 +85,149 @@ impl Workspace {
         &self.name
     }
 
+    pub async fn rename(&mut self, name: &str) -> anyhow::Result<()> {
+        let name = normalize_workspace_name(name)?;
+        db::rename_workspace(&self.browser_context.db, self.id, &name).await?;
+
+        self.name = name;
+        self.browser_context
+            .delegate
+            .on_workspace_rename(self.id, self.name.clone());
+
+        Ok(())
+    }
+
     pub fn icon(&self) -> &WorkspaceIconData {
         &self.icon
     }
 
+    pub fn icon_images(&self) -> &[WorkspaceIconImageData] {
+        &self.icon_images
+    }
+
+    /// 一辺が`size`以上の中で最も小さいアイコンの画像を返す。なければ最も大きいものを返す。
+    pub fn icon_image(&self, size: u32) -> Option<&Path> {
+        self.icon_images
+            .iter()
+            .find(|image| image.size >= size)
+            .or_else(|| self.icon_images.last())
+            .map(|image| image.path.as_path())
+    }
+
+    /// アイコンを絵文字・文字・既定のものにする。画像は[`Self::set_icon_image`]で設定する。
+    pub async fn set_icon(&mut self, icon: WorkspaceIconData) -> anyhow::Result<()> {
+        anyhow::ensure!(
+            matches!(
+                icon,
+                WorkspaceIconData::Emoji(_)
+                    | WorkspaceIconData::Text(_)
+                    | WorkspaceIconData::Default
+            ),
+            "画像やホームのアイコンは設定できません。"
+        );
+        self.ensure_icon_editable()?;
+
+        db::set_workspace_icon(&self.browser_context.db, self.id, &icon, &[]).await?;
+        self.replace_icon(icon, Vec::new());
+
+        let icon_dir = self.browser_context.data.workspace_icon_dir(self.id);
+        remove_stale_icon_images(&icon_dir, None).await;
+
+        Ok(())
+    }
+
+    /// 画像ファイルをワークスペースのディレクトリに複製し、サイズ毎に縮小してアイコンにする。
+    pub async fn set_icon_image(&mut self, path: &Path) -> anyhow::Result<()> {
+        let source = async_fs::read(path)
+            .await
+            .context("アイコンの画像を読み込めませんでした。")?;
+        let extension = path
+            .extension()
+            .and_then(OsStr::to_str)
+            .filter(|extension| extension.chars().all(|c| c.is_ascii_alphanumeric()))
+            .unwrap_or("img");
+
+        self.save_icon_image(source, extension).await
+    }
+
+    /// ワークスペースで最も多く訪問したサイトのファビコンから、アイコンを作る。
+    /// ファビコンのダウンロードに、このワークスペースの読み込み済みのタブを使う。
+    pub async fn generate_icon_from_favicon(&mut self) -> anyhow::Result<()> {
+        self.ensure_icon_editable()?;
+
+        let db = &self.browser_context.db;
+        self.browser_context.site_visits.flush(db).await?;
+        let origin = db::most_visited_origin(db, self.id)
+            .await?
+            .context("このワークスペースで訪問したサイトがありません。")?;
+
+        let webview = self
+            .selected_tab
+            .and_then(|id| self.tabs.get(&id))
+            .and_then(Tab::webview)
+            .or_else(|| self.tabs.values().find_map(Tab::webview))
+            .context("ファビコンのダウンロードに使える、読み込み済みのタブがありません。")?;
+        let source = webview
+            .download_favicon(&format!("{origin}/favicon.ico"), 256)
+            .await?;
+
+        self.save_icon_image(source, "png").await
+    }
+
+    async fn save_icon_image(&mut self, source: Vec<u8>, extension: &str) -> anyhow::Result<()> {
+        self.ensure_icon_editable()?;
+
+        // 画像の表示がパスでキャッシュされても古い画像が出ないよう、毎回別のディレクトリに書き出す。
+        let icon_dir = self.browser_context.data.workspace_icon_dir(self.id);
+        let directory = icon_dir.join(uuid::Uuid::now_v7().to_string());
+        async_fs::create_dir_all(&directory)
+            .await
+            .context("アイコンのディレクトリの作成に失敗しました。")?;
+
+        let source_path = directory.join(format!("source.{extension}"));
+        async_fs::write(&source_path, &source)
+            .await
+            .context("アイコンの画像の複製に失敗しました。")?;
+
+        let icon = WorkspaceIconData::Image(source_path);
+        let result = async {
+            let images = write_icon_images(directory.clone(), source).await?;
+            db::set_workspace_icon(&self.browser_context.db, self.id, &icon, &images).await?;
+
+            anyhow::Ok(images)
+        }
+        .await;
+        let images = match result {
+            Ok(images) => images,
+            Err(error) => {
+                _ = async_fs::remove_dir_all(&directory).await;
+                return Err(error);
+            }
+        };
+
+        self.replace_icon(icon, images);
+        remove_stale_icon_images(&icon_dir, Some(&directory)).await;
+
+        Ok(())
+    }
+
+    fn ensure_icon_editable(&self) -> anyhow::Result<()> {
+        anyhow::ensure!(
+            !matches!(self.icon, WorkspaceIconData::Home),
+            "ホームのアイコンは変更できません。"
+        );
+
+        Ok(())
+    }
+
+    fn replace_icon(&mut self, icon: WorkspaceIconData, images: Vec<WorkspaceIconImageData>) {
+        self.icon = icon.clone();
+        self.icon_images = images;
+        self.browser_context
+            .delegate
+            .on_workspace_icon_change(self.id, icon);
+    }
+
     pub fn tab_order(&self) -> &Vec<Id<TabMarker>> {
         &self.tab_order
     }
@@ -63,11 +236,535 @@ impl Workspace {
         self.selected_tab
     }
 
+    /// タブを選択する。破棄されていれば読み込み直す。
+    pub async fn select_tab(&mut self, id: Id<TabMarker>) -> anyhow::Result<()> {
+        let tab = self.tabs.get_mut(&id).context("そのタブは存在しません。")?;
+
+        tab.mark_active();
+        if self.is_loaded && !tab.is_loaded() {
+            tab.load()?;
+        }
+
+        self.selected_tab = Some(id);
+        db::set_selected_tab(&self.browser_context.db, self.id, Some(id)).await?;
+        self.browser_context
+            .delegate
+            .on_tab_select(self.id, Some(id));
+
+        Ok(())
+    }
+
+    /// 選択中のタブが`previous`から変わっていれば、選択し直して保存する。
+    pub(crate) async fn sync_selection(
+        &mut self,
+        previous: Option<Id<TabMarker>>,
+    ) -> anyhow::Result<()> {
+        if self.selected_tab == previous {
+            return Ok(());
+        }
+
+        match self.selected_tab {
+            Some(id) => self.select_tab(id).await,
+            None => {
+                db::set_selected_tab(&self.browser_context.db, self.id, None).await?;
+                self.browser_context.delegate.on_tab_select(self.id, None);
+
+                Ok(())
+            }
+        }
+    }
+
+    /// 新しいタブを`index`の位置に開く。`None`なら末尾に開く。
+    /// `select`なら開いたタブを選択し、そうでなければ選択されるまで読み込まない。
+    pub async fn open_tab(
+        &mut self,
+        location: TabLocationData,
+        index: Option<usize>,
+        select: bool,
+    ) -> anyhow::Result<Id<TabMarker>> {
+        let data = TabData {
+            id: Id::default(),
+            location,
+            is_pinned: false,
+            group_id: None,
+        };
+        let tab = Tab::new(self.browser_context.clone(), self.id, data)?;
+        let id = tab.id();
+
+        self.add_tab(tab, index.unwrap_or(usize::MAX), select)
+            .await?;
+
+        Ok(id)
+    }
+
+    /// 作ったタブを`index`の位置に加えて保存する。
+    pub(crate) async fn add_tab(
+        &mut self,
+        tab: Tab,
+        index: usize,
+        select: bool,
+    ) -> anyhow::Result<()> {
+        let id = tab.id();
+        let previous_selection = self.selected_tab;
+
+        self.attach_tab(tab, index);
+        if select {
+            self.selected_tab = Some(id);
+        }
+
+        let tab = self.tabs.get(&id).context("そのタブは存在しません。")?;
+        db::add_tab(&self.browser_context.db, self.id, tab, &self.tab_layout()).await?;
+        if let Err(error) = tab.restore_favicon().await {
+            log::warn!("ファビコンの読み込みに失敗しました：{error:#}");
+        }
+        if let Err(error) = tab.restore_archive().await {
+            log::warn!("アーカイブの情報の読み込みに失敗しました：{error:#}");
+        }
+
+        self.browser_context.delegate.on_tab_open(self.id, id);
+        self.notify_tab_order_change();
+        self.sync_selection(previous_selection).await
+    }
+
+    /// タブを閉じる。固定されたタブは閉じられない。
+    /// 選択中のタブを閉じた場合は、その次のタブ、なければ前のタブを選択する。
+    pub async fn close_tab(
+        &mut self,
+        id: Id<TabMarker>,
+        utm: UIThreadMarker,
+    ) -> anyhow::Result<()> {
+        let tab = self.tabs.get(&id).context("そのタブは存在しません。")?;
+        anyhow::ensure!(!tab.is_pinned(), "固定されたタブは閉じられません。");
+
+        // 保存する並びを作るために先に取り出し、保存に失敗したら元に戻す。
+        let arrangement = self.tab_arrangement();
+        let previous_selection = self.selected_tab;
+        let mut tab = self.detach_tab(id)?;
+        if let Err(error) = db::remove_tab(&self.browser_context.db, id, &self.tab_layout()).await {
+            self.tabs.insert(id, tab);
+            self.restore_tab_arrangement(arrangement);
+            return Err(error);
+        }
+        tab.unload(utm)?;
+        remove_thumbnail(&self.browser_context.data, id).await;
+        remove_reader_view(&self.browser_context.data, id).await;
+
+        self.browser_context.delegate.on_tab_close(self.id, id);
+        self.notify_tab_order_change();
+        self.sync_selection(previous_selection).await
+    }
+
+    pub(crate) fn tab_arrangement(&self) -> TabArrangement {
+        TabArrangement {
+            tab_order: self.tab_order.clone(),
+            selected_tab: self.selected_tab,
+            tab_groups: self.tab_groups.clone(),
+            tab_states: self
+                .tabs
+                .iter()
+                .map(|(id, tab)| (*id, (tab.is_pinned(), tab.group_id())))
+                .collect(),
+        }
+    }
+
+    /// タブの並び・選択・固定・グループを`tab_arrangement`の時点に戻す。`tabs`の出し入れは呼び出し側で戻すこと。
+    pub(crate) fn restore_tab_arrangement(&mut self, arrangement: TabArrangement) {
+        self.tab_order = arrangement.tab_order;
+        self.selected_tab = arrangement.selected_tab;
+        self.tab_groups = arrangement.tab_groups;
+
+        for (id, tab) in &mut self.tabs {
+            if let Some((is_pinned, group_id)) = arrangement.tab_states.get(id) {
+                tab.set_pinned(*is_pinned);
+                tab.set_group_id(*group_id);
+            }
+        }
+    }
+
+    /// タブをワークスペースから取り出し、並びを整える。保存はしない。
+    /// 選択中のタブであれば、その次のタブ、なければ前のタブを選択したことにする。
+    pub(crate) fn detach_tab(&mut self, id: Id<TabMarker>) -> anyhow::Result<Tab> {
+        let tab = self.tabs.remove(&id).context("そのタブは存在しません。")?;
+
+        let index = self.tab_order.iter().position(|tab_id| *tab_id == id);
+        self.tab_order.retain(|tab_id| *tab_id != id);
+
+        if self.selected_tab == Some(id) {
+            self.selected_tab = index.and_then(|index| {
+                self.tab_order
+                    .get(index)
+                    .or_else(|| self.tab_order.get(index.wrapping_sub(1)))
+                    .copied()
+            });
+        }
+
+        self.arrange_tabs();
+
+        Ok(tab)
+    }
+
+    /// タブを`index`の位置に加え、並びを整える。保存はしない。
+    /// 選択中のタブがなければ、加えたタブを選択したことにする。
+    pub(crate) fn attach_tab(&mut self, tab: Tab, index: usize) {
+        let id = tab.id();
+        let is_pinned = tab.is_pinned();
+        self.tabs.insert(id, tab);
+
+        let index = self.clamp_tab_index(is_pinned, index);
+        self.tab_order.insert(index, id);
+        self.arrange_tabs();
+
+        if self.selected_tab.is_none() {
+            self.selected_tab = Some(id);
+        }
+    }
+
+    /// タブを固定する、または固定を解除する。
+    /// 固定したタブはグループから外して固定されたタブの末尾に、解除したタブは固定されたタブの直後に移す。
+    pub async fn set_tab_pinned(
+        &mut self,
+        id: Id<TabMarker>,
+        is_pinned: bool,
+    ) -> anyhow::Result<()> {
+        let arrangement = self.tab_arrangement();
+        let tab = self.tabs.get_mut(&id).context("そのタブは存在しません。")?;
+
+        tab.set_pinned(is_pinned);
+        let left_group = is_pinned && tab.group_id().is_some();
+        if left_group {
+            tab.set_group_id(None);
+        }
+
+        self.tab_order.retain(|tab_id| *tab_id != id);
+        let index = self.pinned_tab_count();
+        self.tab_order.insert(index, id);
+
+        self.save_tab_layout(arrangement).await?;
+
+        let delegate = &self.browser_context.delegate;
+        delegate.on_tab_pinned_change(id, is_pinned);
+        if left_group {
+            delegate.on_tab_group_change(id, None);
+        }
+
+        Ok(())
+    }
+
+    /// タブを`index`の位置へ移す。固定されたタブと、そうでないタブの境界は越えられない。
+    pub async fn move_tab(&mut self, id: Id<TabMarker>, index: usize) -> anyhow::Result<()> {
+        let is_pinned = self
+            .tabs
+            .get(&id)
+            .context("そのタブは存在しません。")?
+            .is_pinned();
+        let arrangement = self.tab_arrangement();
+
+        self.tab_order.retain(|tab_id| *tab_id != id);
+        let index = self.clamp_tab_index(is_pinned, index);
+        self.tab_order.insert(index, id);
+
+        self.save_tab_layout(arrangement).await
+    }
+
+    pub fn tab_groups(&self) -> &[TabGroupData] {
+        &self.tab_groups
+    }
+
+    /// タブグループを作り、`tabs`をそのグループに移す。
+    pub async fn create_tab_group(
+        &mut self,
+        name: &str,
+        color: TabGroupColor,
+        tabs: &[Id<TabMarker>],
+    ) -> anyhow::Result<Id<TabGroupMarker>> {
+        for tab_id in tabs {
+            let tab = self.tabs.get(tab_id).context("そのタブは存在しません。")?;
+            anyhow::ensure!(
+                !tab.is_pinned(),
+                "固定されたタブはグループに入れられません。"
+            );
+        }
+
+        let data = TabGroupData {
+            id: Id::default(),
+            workspace_id: self.id,
+            name: normalize_tab_group_name(name)?,
+            color,
+            is_collapsed: false,
+        };
+        let id = data.id;
+
+        // 保存する並びを作るために先に移し、保存に失敗したら元に戻す。
+        let arrangement = self.tab_arrangement();
+        self.tab_groups.push(data.clone());
+        let result = tabs
+            .iter()
+            .try_for_each(|tab_id| self.assign_tab_group(*tab_id, Some(id)));
+        if let Err(error) = result {
+            self.restore_tab_arrangement(arrangement);
+            return Err(error);
+        }
+        self.arrange_tabs();
+
+        if let Err(error) =
+            db::add_tab_group(&self.browser_context.db, &data, &self.tab_layout()).await
+        {
+            self.restore_tab_arrangement(arrangement);
+            return Err(error);
+        }
+
+        self.notify_tab_groups_change();
+        for tab_id in tabs {
+            self.browser_context
+                .delegate
+                .on_tab_group_change(*tab_id, Some(id));
+        }
+        self.notify_tab_order_change();
+
+        Ok(id)
+    }
+
+    pub async fn rename_tab_group(
+        &mut self,
+        id: Id<TabGroupMarker>,
+        name: &str,
+        color: TabGroupColor,
+    ) -> anyhow::Result<()> {
+        let name = normalize_tab_group_name(name)?;
+        let mut group = self.tab_group_mut(id)?.clone();
+        group.name = name;
+        group.color = color;
+
+        db::update_tab_group(&self.browser_context.db, &group).await?;
+        *self.tab_group_mut(id)? = group;
+        self.notify_tab_groups_change();
+
+        Ok(())
+    }
+
+    pub async fn set_tab_group_collapsed(
+        &mut self,
+        id: Id<TabGroupMarker>,
+        is_collapsed: bool,
+    ) -> anyhow::Result<()> {
+        let mut group = self.tab_group_mut(id)?.clone();
+        group.is_collapsed = is_collapsed;
+
+        db::update_tab_group(&self.browser_context.db, &group).await?;
+        *self.tab_group_mut(id)? = group;
+        self.notify_tab_groups_change();
+
+        Ok(())
+    }
+
+    /// タブグループを削除する。グループのタブは閉じずに、グループから外す。
+    pub async fn remove_tab_group(&mut self, id: Id<TabGroupMarker>) -> anyhow::Result<()> {
+        db::remove_tab_group(&self.browser_context.db, id).await?;
+
+        self.tab_groups.retain(|group| group.id != id);
+        self.notify_tab_groups_change();
+
+        for tab in self.tabs.values_mut() {
+            if tab.group_id() == Some(id) {
+                tab.set_group_id(None);
+                self.browser_context
+                    .delegate
+                    .on_tab_group_change(tab.id(), None);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// タブを別のグループの末尾に移す。`None`ならグループから外す。
+    /// 固定されたタブはグループに入れられない。
+    pub async fn move_tab_to_group(
+        &mut self,
+        id: Id<TabMarker>,
+        group_id: Option<Id<TabGroupMarker>>,
+    ) -> anyhow::Result<()> {
+        let arrangement = self.tab_arrangement();
+        if let Err(error) = self.assign_tab_group(id, group_id) {
+            self.restore_tab_arrangement(arrangement);
+            return Err(error);
+        }
+        self.save_tab_layout(arrangement).await?;
+
+        self.browser_context
+            .delegate
+            .on_tab_group_change(id, group_id);
+
+        Ok(())
+    }
+
+    /// タブを別のグループの末尾に移す。保存はしない。
+    fn assign_tab_group(
+        &mut self,
+        id: Id<TabMarker>,
+        group_id: Option<Id<TabGroupMarker>>,
+    ) -> anyhow::Result<()> {
+        if let Some(group_id) = group_id {
+            anyhow::ensure!(
+                self.tab_groups.iter().any(|group| group.id == group_id),
+                "そのタブグループは存在しません。"
+            );
+        }
+
+        let tab = self.tabs.get_mut(&id).context("そのタブは存在しません。")?;
+        anyhow::ensure!(
+            group_id.is_none() || !tab.is_pinned(),
+            "固定されたタブはグループに入れられません。"
+        );
+
+        tab.set_group_id(group_id);
+
+        if let Some(group_id) = group_id {
+            let last_index = self.tab_order.iter().rposition(|tab_id| {
+                *tab_id != id
+                    && self
+                        .tabs
+                        .get(tab_id)
+                        .is_some_and(|tab| tab.group_id() == Some(group_id))
+            });
+
+            if let Some(last_index) = last_index {
+                let current_index = self.tab_order.iter().position(|tab_id| *tab_id == id);
+                self.tab_order.retain(|tab_id| *tab_id != id);
+
+                let index = match current_index {
+                    Some(current_index) if current_index <= last_index => last_index,
+                    _ => last_index + 1,
+                };
+                self.tab_order.insert(index, id);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// タブグループのタブをまとめて`index`の位置へ移す。
+    pub async fn move_tab_group(
+        &mut self,
+        id: Id<TabGroupMarker>,
+        index: usize,
+    ) -> anyhow::Result<()> {
+        self.tab_group_mut(id)?;
+        let arrangement = self.tab_arrangement();
+
+        let (group_tabs, other_tabs) =
+            self.tab_order
+                .iter()
+                .copied()
+                .partition::<Vec<_>, _>(|tab_id| {
+                    self.tabs
+                        .get(tab_id)
+                        .is_some_and(|tab| tab.group_id() == Some(id))
+                });
+
+        let pinned_count = self.pinned_tab_count();
+        let index = index.clamp(pinned_count, other_tabs.len());
+
+        let mut tab_order = other_tabs;
+        tab_order.splice(index..index, group_tabs);
+        self.tab_order = tab_order;
+
+        self.save_tab_layout(arrangement).await
+    }
+
+    fn tab_group_mut(&mut self, id: Id<TabGroupMarker>) -> anyhow::Result<&mut TabGroupData> {
+        self.tab_groups
+            .iter_mut()
+            .find(|group| group.id == id)
+            .context("そのタブグループは存在しません。")
+    }
+
+    fn pinned_tab_count(&self) -> usize {
+        self.tab_order
+            .iter()
+            .filter(|id| self.tabs.get(id).is_some_and(Tab::is_pinned))
+            .count()
+    }
+
+    /// `index`を、固定されたタブとそうでないタブの境界を越えない位置に収める。
+    fn clamp_tab_index(&self, is_pinned: bool, index: usize) -> usize {
+        let pinned_count = self.pinned_tab_count();
+
+        if is_pinned {
+            index.min(pinned_count)
+        } else {
+            index.clamp(pinned_count, self.tab_order.len())
+        }
+    }
+
+    /// タブの並びを整え、タブグループを最初のタブの位置の順に並べ直す。
+    fn arrange_tabs(&mut self) {
+        self.tab_order = normalize_tab_order(&self.tab_order, |id| {
+            self.tabs
+                .get(id)
+                .map_or((false, None), |tab| (tab.is_pinned(), tab.group_id()))
+        });
+
+        let first_index = |group: &TabGroupData| {
+            self.tab_order
+                .iter()
+                .position(|id| {
+                    self.tabs
+                        .get(id)
+                        .is_some_and(|tab| tab.group_id() == Some(group.id))
+                })
+                .unwrap_or(usize::MAX)
+        };
+        let mut tab_groups = std::mem::take(&mut self.tab_groups);
+        tab_groups.sort_by_key(first_index);
+        self.tab_groups = tab_groups;
+    }
+
+    /// タブの並びを整えて、固定・グループの状態と共に保存する。
+    /// 保存に失敗したら、`arrangement`の時点に戻す。
+    async fn save_tab_layout(&mut self, arrangement: TabArrangement) -> anyhow::Result<()> {
+        self.arrange_tabs();
+        if let Err(error) =
+            db::update_tab_layout(&self.browser_context.db, &self.tab_layout()).await
+        {
+            self.restore_tab_arrangement(arrangement);
+            return Err(error);
+        }
+        self.notify_tab_order_change();
+
+        Ok(())
+    }
+
+    pub(crate) fn tab_layout(&self) -> Vec<TabLayoutData> {
+        self.tab_order
+            .iter()
+            .filter_map(|id| self.tabs.get(id))
+            .enumerate()
+            .map(|(position, tab)| TabLayoutData {
+                id: tab.id(),
+                position,
+                is_pinned: tab.is_pinned(),
+                group_id: tab.group_id(),
+            })
+            .collect()
+    }
+
+    pub(crate) fn notify_tab_order_change(&self) {
+        self.browser_context
+            .delegate
+            .on_tab_order_change(self.id, self.tab_order.clone());
+    }
+
+    fn notify_tab_groups_change(&self) {
+        self.browser_context
+            .delegate
+            .on_tab_groups_change(self.id, self.tab_groups.clone());
+    }
+
     pub fn tabs(&self) -> &HashMap<Id<TabMarker>, Tab> {
         &self.tabs
     }
 
-    pub fn tabs_mut(&self) -> &mut HashMap<Id<TabMarker>, Tab> {
+    pub fn tabs_mut(&mut self) -> &mut HashMap<Id<TabMarker>, Tab> {
         &mut self.tabs
     }
 
@@ -91,6 +788,93 @@ impl Workspace {
         }
     }
 
+    pub fn is_force_dark(&self) -> bool {
+        self.browser_context.user_styles.is_force_dark(self.id)
+    }
+
+    /// ページを強制的に暗い配色にするかを切り替え、表示中のページにも適用する。
+    pub async fn set_force_dark(&self, force_dark: bool) -> anyhow::Result<()> {
+        self.browser_context
+            .user_styles
+            .set_force_dark(&self.browser_context.db, self.id, force_dark)
+            .await?;
+
+        self.apply_style_sheets()
+    }
+
+    /// ワークスペースのCSSのファイルが変更されていれば読み込み直し、表示中のページにも適用する。
+    /// ファイルの変更は通知されないので、定期的に呼び出すこと。
+    pub async fn refresh_user_styles(&self) -> anyhow::Result<()> {
+        if self
+            .browser_context
+            .user_styles
+            .refresh(&self.browser_context.data, self.id)
+            .await?
+        {
+            self.apply_style_sheets()?;
+        }
+
+        Ok(())
+    }
+
+    /// ワークスペースの全ての注釈を、Markdownとしてワークスペースのディレクトリに書き出す。
+    pub async fn export_annotations(&self) -> anyhow::Result<PathBuf> {
+        let title = format!("{}の注釈", self.name());
+        let annotations = self.browser_context.annotations.list(self.id);
+        let markdown = annotations_to_markdown(&title, &annotations);
+
+        let directory = self.browser_context.data.workspace_dir(self.id);
+        if !utils::exists(&directory).await? {
+            async_fs::create_dir_all(&directory)
+                .await
+                .context("ワークスペースディレクトリの作成に失敗しました。")?;
+        }
+
+        let file_name = utils::sanitize_file_name(&title);
+        let path = utils::unique_path(&directory, &file_name, "md").await?;
+        async_fs::write(&path, markdown)
+            .await
+            .context("注釈の書き出しに失敗しました。")?;
+
+        Ok(path)
+    }
+
+    fn apply_style_sheets(&self) -> anyhow::Result<()> {
+        for tab in self.tabs.values() {
+            if let Some(webview) = tab.webview() {
+                let style_sheets = self
+                    .browser_context
+                    .user_styles
+                    .style_sheets_for(self.id, &webview.current_url());
+                webview.set_style_sheets(&style_sheets)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// タブの`WebView`を全て閉じ、ファイルの一覧を捨てる。タブの場所などの情報は残す。
+    /// 読み込みの途中で中断された場合にも、読み込み前の状態に戻すのに使う。
+    pub fn unload(&mut self, utm: UIThreadMarker) -> anyhow::Result<()> {
+        if !self.is_loaded && !self.is_loading {
+            return Ok(());
+        }
+
+        for tab in self.tabs.values_mut() {
+            tab.unload(utm)?;
+        }
+        self.files = Vec::new();
+
+        self.is_loaded = false;
+        self.is_loading = false;
+        self.browser_context.delegate.on_workspace_unload(self.id);
+
+        Ok(())
+    }
+
+    /// ファイルの一覧と、ズーム・ユーザースクリプト・注釈などのワークスペースの設定を読み込む。
+    /// `WebView`を作るのは選択中のタブだけで、他のタブは選択された時に読み込む。
+    /// 失敗した場合は途中まで読み込んだ状態で残るので、`unload`で戻すこと。
     pub async fn load(&mut self) -> anyhow::Result<()> {
         anyhow::ensure!(
             !self.is_loaded,
@@ -106,8 +890,36 @@ impl Workspace {
         let path = self.browser_context.data.workspace_dir(self.id);
         self.files = crate::fs::build_file_tree(&path).await?;
 
-        // Load tabs.
-        for tab in self.tabs.values_mut() {
+        // Load zoom levels before the webviews start navigating.
+        self.browser_context
+            .zoom_levels
+            .load(&self.browser_context.db, self.id)
+            .await?;
+
+        // Load user scripts before the webviews are created.
+        self.browser_context
+            .user_scripts
+            .load(&self.browser_context.data, self.id)
+            .await?;
+
+        self.browser_context
+            .user_styles
+            .load(
+                &self.browser_context.data,
+                &self.browser_context.db,
+                self.id,
+            )
+            .await?;
+
+        // Load annotations before the webviews start navigating.
+        self.browser_context
+            .annotations
+            .load(&self.browser_context.db, self.id)
+            .await?;
+
+        // Load only the selected tab. The others are loaded when they are selected.
+        if let Some(tab) = self.selected_tab.and_then(|id| self.tabs.get_mut(&id)) {
+            tab.mark_active();
             tab.load()?;
         }
 
@@ -118,3 +930,11 @@ impl Workspace {
         Ok(())
     }
 }
+
+/// ワークスペースの名前の前後の空白を除く。空の名前は付けられない。
+pub(crate) fn normalize_workspace_name(name: &str) -> anyhow::Result<String> {
+    let name = name.trim();
+    anyhow::ensure!(!name.is_empty(), "ワークスペースの名前が空です。");
+
+    Ok(name.to_owned())
+}
@@ -0,0 +1,105 @@
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
+
+use crate::{
+    Id, WorkspaceMarker,
+    db::{self, Database, ZoomLevelData},
+};
+
+/// 倍率が1倍のズームレベル。
+pub(crate) const DEFAULT_ZOOM_LEVEL: f64 = 0.0;
+
+/// ズームレベルをどの範囲で記憶するか。
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ZoomScope {
+    /// 全てのワークスペースで記憶する。
+    Global,
+    /// タブがあるワークスペースでのみ記憶する。
+    Workspace,
+}
+
+type ZoomLevelKey = (String, Option<Id<WorkspaceMarker>>);
+
+/// オリジン毎のズームレベル。
+/// ナビゲーションの確定時にすぐ適用できるよう、読み込み済みのワークスペースの分をメモリに持っておく。
+#[derive(Clone, Default)]
+pub struct ZoomLevels {
+    levels: Rc<RefCell<HashMap<ZoomLevelKey, f64>>>,
+}
+
+impl ZoomLevels {
+    pub(crate) async fn load(
+        &self,
+        db: &Database,
+        workspace_id: Id<WorkspaceMarker>,
+    ) -> anyhow::Result<()> {
+        let records = db::list_zoom_levels(db, workspace_id).await?;
+        let mut levels = self.levels.borrow_mut();
+
+        for record in records {
+            levels.insert((record.origin, record.workspace_id), record.level);
+        }
+
+        Ok(())
+    }
+
+    /// ズームレベルを取得する。ワークスペース毎のものを、全体のものより優先する。
+    pub fn get(&self, origin: &str, workspace_id: Id<WorkspaceMarker>) -> Option<f64> {
+        let levels = self.levels.borrow();
+
+        levels
+            .get(&(origin.to_owned(), Some(workspace_id)))
+            .or_else(|| levels.get(&(origin.to_owned(), None)))
+            .copied()
+    }
+
+    /// `workspace_id`の範囲で記憶しているズームレベルだけを取得する。`None`なら全体のものを取得する。
+    pub(crate) fn get_scoped(
+        &self,
+        origin: &str,
+        workspace_id: Option<Id<WorkspaceMarker>>,
+    ) -> Option<f64> {
+        self.levels
+            .borrow()
+            .get(&(origin.to_owned(), workspace_id))
+            .copied()
+    }
+
+    pub(crate) async fn set(
+        &self,
+        db: &Database,
+        origin: &str,
+        workspace_id: Option<Id<WorkspaceMarker>>,
+        level: f64,
+    ) -> anyhow::Result<()> {
+        db::set_zoom_level(
+            db,
+            &ZoomLevelData {
+                origin: origin.to_owned(),
+                workspace_id,
+                level,
+            },
+        )
+        .await?;
+
+        self.levels
+            .borrow_mut()
+            .insert((origin.to_owned(), workspace_id), level);
+
+        Ok(())
+    }
+
+    pub(crate) async fn remove(
+        &self,
+        db: &Database,
+        origin: &str,
+        workspace_id: Option<Id<WorkspaceMarker>>,
+    ) -> anyhow::Result<()> {
+        db::remove_zoom_level(db, origin, workspace_id).await?;
+
+        self.levels
+            .borrow_mut()
+            .remove(&(origin.to_owned(), workspace_id));
+
+        Ok(())
+    }
+}